@@ -0,0 +1,64 @@
+PvP_Scalpel_DB = {
+	{
+		["matchKey"] = "2026-03-14T19:42:07|Nagrand Arena|3v3",
+		["telemetryVersion"] = 4,
+		["durationSeconds"] = 187.25,
+		["winner"] = "victory",
+		["matchDetails"] = {
+			["timestamp"] = "2026-03-14T19:42:07",
+			["mapName"] = "Nagrand Arena",
+			["format"] = "Rated Arena 3v3",
+			["build"] = {
+				["version"] = "11.1.0",
+				["build"] = "59888",
+				["interface"] = 110100,
+				["info"] = "Release \"live\" build\nC:\\World of Warcraft\\_retail_",
+			},
+		},
+		["players"] = {
+			{
+				["name"] = "Kröm",
+				["realm"] = "Ravencrest",
+				["isOwner"] = true,
+				["damage"] = 1834512,
+				["healing"] = 0.5,
+				["rating"] = -12,
+			}, -- [1]
+			{
+				["name"] = "Sylv'ara",
+				["realm"] = "Twisting Nether",
+				["isOwner"] = false,
+				["damage"] = 1.25e-7,
+			}, -- [2]
+		},
+		["localSpellCapture"] = {
+			"spellID", -- [1]
+			"castGUID", -- [2]
+			"t", -- [3]
+			["schema"] = {
+				["spellID"] = 1,
+				["castGUID"] = 2,
+				["t"] = 3,
+			},
+			[5] = "sparse",
+			[true] = "flag",
+			[2.5] = "half",
+		},
+	}, -- [1]
+}
+PvP_Scalpel_GC = {
+	["2026-03-14T19:42:07|Nagrand Arena|3v3"] = "pending",
+	["2026-03-13T21:05:44|Dalaran Sewers|2v2"] = "synced",
+}
+PvP_Scalpel_Spell_Data = {
+	[6552] = {
+		["name"] = "Pummel",
+		["school"] = 1,
+		["description"] = "Tab\tseparated\r\nand a bell \007 here",
+	},
+}
+PvP_Scalpel_InteruptSpells = {
+	6552, -- [1]
+	1766, -- [2]
+	47528, -- [3]
+}
@@ -0,0 +1,43 @@
+use crate::fs_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+const CONFIG_FILE: &str = "config.json";
+
+/// Settings the user changed from the defaults, stored as JSON in the app config directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppConfig {
+    /// Folder chosen by the user instead of the detected installs.
+    pub wow_path: Option<String>,
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Unable to resolve app config directory: {e}"))?;
+    Ok(dir.join(CONFIG_FILE))
+}
+
+/// The stored config; defaults when the file is missing or unreadable.
+pub fn load(app: &AppHandle) -> AppConfig {
+    config_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(app: &AppHandle, config: &AppConfig) -> Result<(), String> {
+    let path = config_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Unable to create app config directory: {e}"))?;
+    }
+    let bytes = serde_json::to_vec_pretty(config)
+        .map_err(|e| format!("Unable to serialize app config: {e}"))?;
+    fs_atomic::write_atomic(&path, &bytes).map_err(|e| format!("Unable to write app config: {e}"))
+}
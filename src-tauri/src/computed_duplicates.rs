@@ -0,0 +1,393 @@
+use crate::im_command;
+use crate::match_time;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use ts_rs::TS;
+
+pub const DEFAULT_TOLERANCE_SECONDS: u32 = 120;
+pub const MAX_TOLERANCE_SECONDS: u32 = 3600;
+
+// Share of the smaller roster that must also be in the other capture to count as the same game.
+// Measured against the smaller one so a partial capture of a full lobby still matches.
+const MIN_ROSTER_OVERLAP: f64 = 0.5;
+// Confidence weights; they sum to 1.
+const ROSTER_WEIGHT: f64 = 0.7;
+const TIME_WEIGHT: f64 = 0.3;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/DuplicateMatchRef.ts")]
+pub struct DuplicateMatchRef {
+    pub account: String,
+    pub match_key: String,
+    pub timestamp: String,
+    pub scoreboard_complete: bool,
+    pub player_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/DuplicateMatchSuggestion.ts")]
+pub struct DuplicateMatchSuggestion {
+    /// Most complete capture of the cluster; the one to collapse the others into.
+    pub keep: DuplicateMatchRef,
+    pub duplicates: Vec<DuplicateMatchRef>,
+    /// 0..1, from the weakest pair that links the cluster together.
+    pub confidence: f64,
+    /// Smallest roster overlap (shared players / smaller roster) among the linking pairs.
+    pub roster_overlap: f64,
+    #[ts(type = "number")]
+    pub max_time_delta_seconds: i64,
+}
+
+struct Candidate {
+    reference: DuplicateMatchRef,
+    started_at: i64,
+    map: String,
+    bracket_id: Option<i64>,
+    roster: HashSet<String>,
+    // Tie-breaker when choosing the capture to keep.
+    payload_len: usize,
+}
+
+fn str_at<'a>(value: &'a Value, path: &[&str]) -> Option<&'a str> {
+    path.iter()
+        .try_fold(value, |node, key| node.get(key))
+        .and_then(|v| v.as_str())
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+}
+
+pub fn scoreboard_complete(entry: &Value) -> bool {
+    [
+        &["integrity", "scoreboardComplete"][..],
+        &["soloShuffle", "integrity", "scoreboardComplete"][..],
+    ]
+    .iter()
+    .any(|path| {
+        path.iter()
+            .try_fold(entry, |node, key| node.get(key))
+            .and_then(|v| v.as_bool())
+            == Some(true)
+    })
+}
+
+fn candidate(account: String, match_key: String, entry: &Value) -> Option<Candidate> {
+    let timestamp = str_at(entry, &["matchDetails", "timestamp"])
+        .or_else(|| str_at(entry, &["soloShuffle", "timestamp"]))?;
+    let started_at = match_time::timestamp_seconds(timestamp)?;
+
+    let players = entry
+        .get("players")
+        .and_then(|v| v.as_array())
+        .filter(|players| !players.is_empty())
+        .or_else(|| {
+            entry
+                .pointer("/soloShuffle/matchSummary/players")
+                .and_then(|v| v.as_array())
+        })?;
+    let roster: HashSet<String> = players.iter().map(im_command::player_key).collect();
+    if roster.is_empty() {
+        return None;
+    }
+
+    Some(Candidate {
+        reference: DuplicateMatchRef {
+            account,
+            match_key,
+            timestamp: timestamp.to_string(),
+            scoreboard_complete: scoreboard_complete(entry),
+            player_count: players.len() as u32,
+        },
+        started_at,
+        map: str_at(entry, &["matchDetails", "mapName"])
+            .or_else(|| str_at(entry, &["soloShuffle", "mapName"]))
+            .unwrap_or_default()
+            .to_lowercase(),
+        bracket_id: entry.get("bracketId").and_then(|v| v.as_i64()),
+        roster,
+        payload_len: entry.to_string().len(),
+    })
+}
+
+struct Link {
+    confidence: f64,
+    roster_overlap: f64,
+    time_delta: i64,
+}
+
+// `None` when the two captures cannot be the same game.
+fn link(a: &Candidate, b: &Candidate, tolerance: i64) -> Option<Link> {
+    let time_delta = (a.started_at - b.started_at).abs();
+    if time_delta > tolerance || a.map != b.map {
+        return None;
+    }
+    if matches!((a.bracket_id, b.bracket_id), (Some(x), Some(y)) if x != y) {
+        return None;
+    }
+
+    let shared = a.roster.intersection(&b.roster).count() as f64;
+    let smaller = a.roster.len().min(b.roster.len()) as f64;
+    let roster_overlap = shared / smaller;
+    if roster_overlap < MIN_ROSTER_OVERLAP {
+        return None;
+    }
+
+    let closeness = if tolerance == 0 {
+        1.0
+    } else {
+        1.0 - time_delta as f64 / tolerance as f64
+    };
+    Some(Link {
+        confidence: ROSTER_WEIGHT * roster_overlap + TIME_WEIGHT * closeness,
+        roster_overlap,
+        time_delta,
+    })
+}
+
+fn find_root(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+// Complete scoreboards first, then larger rosters, then the fuller payload.
+fn keep_rank(candidate: &Candidate) -> (bool, u32, usize) {
+    (
+        candidate.reference.scoreboard_complete,
+        candidate.reference.player_count,
+        candidate.payload_len,
+    )
+}
+
+/// Groups rows that look like captures of the same game: same map and bracket, start times
+/// within `tolerance_seconds`, and mostly the same players. Rows without a timestamp or roster
+/// are never matched. Suggestions are ordered by confidence, highest first.
+pub fn find_duplicates(
+    rows: Vec<(String, String, Value)>,
+    tolerance_seconds: u32,
+) -> Vec<DuplicateMatchSuggestion> {
+    let tolerance = i64::from(tolerance_seconds.min(MAX_TOLERANCE_SECONDS));
+    let mut candidates: Vec<Candidate> = rows
+        .into_iter()
+        .filter_map(|(account, match_key, entry)| candidate(account, match_key, &entry))
+        .collect();
+    candidates.sort_by_key(|c| c.started_at);
+
+    let mut parents: Vec<usize> = (0..candidates.len()).collect();
+    let mut links: Vec<(usize, Link)> = Vec::new();
+    for i in 0..candidates.len() {
+        for j in i + 1..candidates.len() {
+            if candidates[j].started_at - candidates[i].started_at > tolerance {
+                break;
+            }
+            if let Some(found) = link(&candidates[i], &candidates[j], tolerance) {
+                let (a, b) = (find_root(&mut parents, i), find_root(&mut parents, j));
+                parents[b] = a;
+                links.push((i, found));
+            }
+        }
+    }
+
+    let mut clusters: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for index in 0..candidates.len() {
+        let root = find_root(&mut parents, index);
+        clusters.entry(root).or_default().push(index);
+    }
+
+    let mut suggestions: Vec<DuplicateMatchSuggestion> = clusters
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let root = find_root(&mut parents, members[0]);
+            let cluster_links: Vec<&Link> = links
+                .iter()
+                .filter(|(index, _)| find_root(&mut parents, *index) == root)
+                .map(|(_, found)| found)
+                .collect();
+
+            let keep = *members
+                .iter()
+                .max_by_key(|index| keep_rank(&candidates[**index]))
+                .unwrap_or(&members[0]);
+            DuplicateMatchSuggestion {
+                keep: candidates[keep].reference.clone(),
+                duplicates: members
+                    .iter()
+                    .filter(|index| **index != keep)
+                    .map(|index| candidates[*index].reference.clone())
+                    .collect(),
+                confidence: cluster_links
+                    .iter()
+                    .map(|found| found.confidence)
+                    .fold(1.0, f64::min),
+                roster_overlap: cluster_links
+                    .iter()
+                    .map(|found| found.roster_overlap)
+                    .fold(1.0, f64::min),
+                max_time_delta_seconds: cluster_links
+                    .iter()
+                    .map(|found| found.time_delta)
+                    .max()
+                    .unwrap_or(0),
+            }
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_duplicates, DuplicateMatchSuggestion};
+    use serde_json::{json, Value};
+
+    fn capture(timestamp: &str, players: &[&str], complete: bool) -> Value {
+        json!({
+            "matchDetails": { "timestamp": timestamp, "mapName": "Nagrand Arena" },
+            "bracketId": 2,
+            "players": players.iter().map(|guid| json!({ "guid": guid })).collect::<Vec<_>>(),
+            "integrity": { "scoreboardComplete": complete },
+        })
+    }
+
+    fn row(account: &str, key: &str, entry: Value) -> (String, String, Value) {
+        (account.to_string(), key.to_string(), entry)
+    }
+
+    fn keys(suggestion: &DuplicateMatchSuggestion) -> Vec<&str> {
+        suggestion
+            .duplicates
+            .iter()
+            .map(|reference| reference.match_key.as_str())
+            .collect()
+    }
+
+    const LOBBY: [&str; 6] = ["p-1", "p-2", "p-3", "p-4", "p-5", "p-6"];
+
+    #[test]
+    fn partial_roster_clusters_with_the_full_capture() {
+        let suggestions = find_duplicates(
+            vec![
+                row("Main", "a", capture("2026-03-14 19:42:07", &LOBBY, true)),
+                row(
+                    "Alt",
+                    "b",
+                    capture("2026-03-14 19:42:30", &LOBBY[..3], false),
+                ),
+                row(
+                    "Alt",
+                    "c",
+                    capture("2026-03-14 19:43:00", &["x-1", "x-2"], false),
+                ),
+            ],
+            120,
+        );
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].keep.match_key, "a");
+        assert_eq!(keys(&suggestions[0]), ["b"]);
+        assert_eq!(suggestions[0].roster_overlap, 1.0);
+        assert_eq!(suggestions[0].max_time_delta_seconds, 23);
+    }
+
+    #[test]
+    fn tolerance_is_inclusive() {
+        let rows = |second: &str| {
+            vec![
+                row("Main", "a", capture("2026-03-14 19:42:00", &LOBBY, true)),
+                row(
+                    "Alt",
+                    "b",
+                    capture(&format!("2026-03-14 19:44:{second}"), &LOBBY, true),
+                ),
+            ]
+        };
+
+        let at_edge = find_duplicates(rows("00"), 120);
+        assert_eq!(at_edge.len(), 1);
+        assert_eq!(at_edge[0].max_time_delta_seconds, 120);
+        assert!((at_edge[0].confidence - 0.7).abs() < 1e-9);
+        assert!(find_duplicates(rows("01"), 120).is_empty());
+    }
+
+    #[test]
+    fn keep_prefers_complete_then_larger_roster() {
+        let suggestions = find_duplicates(
+            vec![
+                row(
+                    "Main",
+                    "partial",
+                    capture("2026-03-14 19:42:00", &LOBBY, false),
+                ),
+                row(
+                    "Alt",
+                    "complete",
+                    capture("2026-03-14 19:42:05", &LOBBY[..4], true),
+                ),
+                row(
+                    "Third",
+                    "smaller",
+                    capture("2026-03-14 19:42:10", &LOBBY[..4], false),
+                ),
+            ],
+            120,
+        );
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].keep.match_key, "complete");
+        assert_eq!(keys(&suggestions[0]), ["partial", "smaller"]);
+
+        let without_complete = find_duplicates(
+            vec![
+                row(
+                    "Main",
+                    "small",
+                    capture("2026-03-14 19:42:00", &LOBBY[..4], false),
+                ),
+                row(
+                    "Alt",
+                    "large",
+                    capture("2026-03-14 19:42:05", &LOBBY, false),
+                ),
+            ],
+            120,
+        );
+        assert_eq!(without_complete[0].keep.match_key, "large");
+    }
+
+    #[test]
+    fn different_bracket_or_disjoint_roster_never_links() {
+        let mut other_bracket = capture("2026-03-14 19:42:05", &LOBBY, true);
+        other_bracket["bracketId"] = json!(3);
+        assert!(find_duplicates(
+            vec![
+                row("Main", "a", capture("2026-03-14 19:42:00", &LOBBY, true)),
+                row("Alt", "b", other_bracket),
+            ],
+            120,
+        )
+        .is_empty());
+
+        assert!(find_duplicates(
+            vec![
+                row(
+                    "Main",
+                    "a",
+                    capture("2026-03-14 19:42:00", &LOBBY[..4], true)
+                ),
+                row(
+                    "Alt",
+                    "b",
+                    capture("2026-03-14 19:42:05", &["p-1", "x-2", "x-3"], true)
+                ),
+            ],
+            120,
+        )
+        .is_empty());
+    }
+}
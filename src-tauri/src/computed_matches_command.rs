@@ -1,34 +1,41 @@
+use crate::computed_duplicates::{self, DuplicateMatchSuggestion};
+use crate::computed_merge::{self, ComputedMatchRef};
+use crate::computed_migrations::{self, ComputedMigrationReport};
+use crate::computed_query::{self, ComputedMatchFilter, ComputedMatchPage};
+use crate::computed_store;
+use crate::file_lock::{self, FileLockGuard};
+use crate::game_monitor::GameFlavor;
+use crate::local_spell_model;
+use crate::log_command;
+use crate::match_model::{self, MatchValidationError};
+use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Manager};
 
-const LEGACY_SCHEMA_VERSION: u8 = 1;
-const SCHEMA_VERSION: u8 = 2;
+const LEGACY_IMPORT_KEY: &str = "legacy_json_imported_at_ms";
+const BACKUP_DIR: &str = "backups";
+const BACKUP_GENERATIONS: u32 = 3;
+// Pre-restore copies kept per account.
+const KEPT_RESTORE_BACKUPS: usize = 3;
+const LOCK_DIR: &str = "locks";
+const STORE_LOCK_NAME: &str = "computed_matches";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ComputedMatchesFile {
-    schema_version: u8,
+    // Files written before versioning have none; their directory version applies.
+    #[serde(default)]
+    schema_version: Option<i64>,
     account: String,
     updated_at_ms: u64,
     entries: HashMap<String, Value>,
 }
 
-impl ComputedMatchesFile {
-    fn empty(account: String, schema_version: u8) -> Self {
-        Self {
-            schema_version,
-            account,
-            updated_at_ms: now_ms(),
-            entries: HashMap::new(),
-        }
-    }
-}
-
 fn now_ms() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -54,93 +61,38 @@ fn sanitize_account(value: &str) -> String {
     }
 }
 
-fn version_dir(schema_version: u8) -> &'static str {
-    match schema_version {
-        LEGACY_SCHEMA_VERSION => "v1",
-        SCHEMA_VERSION => "v2",
-        _ => "v2",
-    }
-}
-
-fn store_dir(app: &AppHandle, schema_version: u8, create_dir: bool) -> Result<PathBuf, String> {
+// Each flavor keeps its own database, backups and locks; retail stays at the top level.
+fn store_root(app: &AppHandle, flavor: GameFlavor) -> Result<PathBuf, String> {
     let mut dir = app
         .path()
         .app_data_dir()
         .map_err(|e| format!("Unable to resolve app data directory: {e}"))?;
     dir.push("computed_outcomes");
-    dir.push(version_dir(schema_version));
-    if create_dir {
-        fs::create_dir_all(&dir)
-            .map_err(|e| format!("Unable to create computed store directory: {e}"))?;
-    }
-    Ok(dir)
-}
-
-fn store_path(
-    app: &AppHandle,
-    account: &str,
-    schema_version: u8,
-    create_dir: bool,
-) -> Result<PathBuf, String> {
-    let mut dir = store_dir(app, schema_version, create_dir)?;
-    dir.push(format!("{}.json", sanitize_account(account)));
-    Ok(dir)
-}
-
-fn read_store(
-    path: &PathBuf,
-    account: &str,
-    schema_version: u8,
-    persist_normalized: bool,
-) -> Result<ComputedMatchesFile, String> {
-    if !path.exists() {
-        return Ok(ComputedMatchesFile::empty(
-            account.to_string(),
-            schema_version,
-        ));
-    }
-
-    let content =
-        fs::read_to_string(path).map_err(|e| format!("Unable to read computed store file: {e}"))?;
-    let mut parsed: ComputedMatchesFile = serde_json::from_str(&content)
-        .map_err(|e| format!("Unable to parse computed store file: {e}"))?;
-
-    if parsed.schema_version != schema_version {
-        return Err(format!(
-            "Unsupported computed store schema version: {}",
-            parsed.schema_version
-        ));
-    }
-
-    parsed.account = account.to_string();
-    if normalize_entries_in_place(&mut parsed) && persist_normalized {
-        write_store_atomic(path, &parsed)?;
-    }
-    Ok(parsed)
+    Ok(flavor.partition(dir))
 }
 
-fn read_store_or_empty(
-    path: &PathBuf,
-    account: &str,
-    schema_version: u8,
-    persist_normalized: bool,
-) -> ComputedMatchesFile {
-    read_store(path, account, schema_version, persist_normalized)
-        .unwrap_or_else(|_| ComputedMatchesFile::empty(account.to_string(), schema_version))
+// `account` is sanitized, so it never contains the `.` separating it from the suffix.
+fn account_backup_path(root: &Path, account: &str, generation: u32) -> PathBuf {
+    root.join(BACKUP_DIR)
+        .join(format!("{account}.sqlite3.bak{generation}"))
 }
 
-fn write_store_atomic(path: &PathBuf, data: &ComputedMatchesFile) -> Result<(), String> {
-    let bytes =
-        serde_json::to_vec(data).map_err(|e| format!("Unable to serialize computed store: {e}"))?;
-    let tmp_path = path.with_extension("tmp");
-    fs::write(&tmp_path, bytes)
-        .map_err(|e| format!("Unable to write computed store temp file: {e}"))?;
-    if path.exists() {
-        let _ = fs::remove_file(path);
+// Copies the account's rows as they are before a write into `.bak1`, shifting the older
+// generations up one (the oldest falls off). The copy is written aside first, so a failed
+// backup leaves the existing generations alone.
+fn rotate_account_backups(root: &Path, conn: &Connection, account: &str) -> Result<(), String> {
+    let fresh = root.join(BACKUP_DIR).join(format!("{account}.sqlite3.tmp"));
+    computed_store::backup_account_to(conn, &fresh, account)?;
+
+    for generation in (1..BACKUP_GENERATIONS).rev() {
+        let from = account_backup_path(root, account, generation);
+        if from.exists() {
+            fs::rename(&from, account_backup_path(root, account, generation + 1))
+                .map_err(|e| format!("Unable to rotate computed store backups: {e}"))?;
+        }
     }
-    fs::rename(&tmp_path, path)
-        .map_err(|e| format!("Unable to replace computed store file: {e}"))?;
-    Ok(())
+    fs::rename(&fresh, account_backup_path(root, account, 1))
+        .map_err(|e| format!("Unable to rotate computed store backups: {e}"))
 }
 
 fn match_key_of(value: &Value) -> Option<String> {
@@ -151,243 +103,487 @@ fn match_key_of(value: &Value) -> Option<String> {
         .filter(|v| !v.is_empty())
 }
 
-fn bracket_id_from_format(format: &str) -> i64 {
-    match format.trim().to_lowercase().as_str() {
-        "solo shuffle" => 1,
-        "battleground blitz" => 2,
-        "rated arena 2v2" => 3,
-        "rated arena 3v3" => 4,
-        "rated arena" => 5,
-        "rated battleground" => 6,
-        "arena skirmish" => 7,
-        "brawl" => 8,
-        "random battleground" => 9,
-        "random epic battleground" => 10,
-        _ => 0,
-    }
+// Pre-SQLite stores lived in `computed_outcomes/v<schema>/<account>.json`, oldest first.
+fn legacy_store_dirs(root: &Path) -> Vec<(i64, PathBuf)> {
+    let Ok(read_dir) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut dirs: Vec<(i64, PathBuf)> = read_dir
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| {
+            let version = path
+                .file_name()
+                .and_then(|v| v.to_str())
+                .and_then(|name| name.strip_prefix('v'))
+                .and_then(|raw| raw.parse::<i64>().ok())?;
+            Some((version, path))
+        })
+        .collect();
+    dirs.sort_by_key(|(version, _)| *version);
+    dirs
 }
 
-fn count_faction_players(value: &Value) -> (usize, usize) {
-    let mut horde = 0_usize;
-    let mut alliance = 0_usize;
-
-    if let Some(players) = value.get("players").and_then(|v| v.as_array()) {
-        for player in players {
-            let faction = player
-                .get("faction")
-                .and_then(|v| v.as_i64().or_else(|| v.as_u64().map(|raw| raw as i64)));
-            match faction {
-                Some(0) => horde += 1,
-                Some(1) => alliance += 1,
-                _ => {}
-            }
-        }
-    }
-
-    (horde, alliance)
+fn legacy_store_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    read_dir
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| path.extension().and_then(|v| v.to_str()) == Some("json"))
+        .collect()
 }
 
-fn normalize_match_entry(mut value: Value) -> (Value, bool) {
-    let mut changed = false;
-    let (horde, alliance) = count_faction_players(&value);
+fn salvage_schema_version(content: &str) -> Option<i64> {
+    let rest = &content[content.find("\"schemaVersion\"")? + "\"schemaVersion\"".len()..];
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+    let digits: String = rest.chars().take_while(|ch| ch.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
 
-    let current_format = value
-        .get("matchDetails")
-        .and_then(|v| v.get("format"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .trim()
-        .to_string();
+// Walks the `entries` object one key/value pair at a time and keeps everything before the
+// first damaged byte, so a truncated file still yields its complete entries.
+fn salvage_entries(content: &str) -> HashMap<String, Value> {
+    let mut out = HashMap::new();
+    let Some(start) = content.find("\"entries\"") else {
+        return out;
+    };
+    let mut rest = content[start + "\"entries\"".len()..].trim_start();
+    let Some(body) = rest.strip_prefix(':').map(str::trim_start) else {
+        return out;
+    };
+    let Some(body) = body.strip_prefix('{') else {
+        return out;
+    };
+    rest = body;
+
+    loop {
+        rest = rest.trim_start().trim_start_matches(',').trim_start();
+        if !rest.starts_with('"') {
+            break;
+        }
 
-    let mut next_format = current_format.clone();
-    let mut bracket_id = bracket_id_from_format(&current_format);
+        let mut stream = serde_json::Deserializer::from_str(rest).into_iter::<String>();
+        let Some(Ok(match_key)) = stream.next() else {
+            break;
+        };
+        rest = rest[stream.byte_offset()..].trim_start();
+        let Some(value_start) = rest.strip_prefix(':') else {
+            break;
+        };
+        rest = value_start;
 
-    if bracket_id == 9 && horde >= 25 && alliance >= 25 {
-        next_format = "Random Epic Battleground".to_string();
-        bracket_id = 10;
+        let mut stream = serde_json::Deserializer::from_str(rest).into_iter::<Value>();
+        let Some(Ok(entry)) = stream.next() else {
+            break;
+        };
+        rest = &rest[stream.byte_offset()..];
+        out.insert(match_key, entry);
     }
 
-    if let Some(root) = value.as_object_mut() {
-        if let Some(match_details) = root.get_mut("matchDetails").and_then(|v| v.as_object_mut()) {
-            if !next_format.is_empty() && next_format != current_format {
-                match_details.insert("format".to_string(), Value::String(next_format));
-                changed = true;
-            }
-        }
+    out
+}
 
-        let existing_bracket_id = root
-            .get("bracketId")
-            .and_then(|v| v.as_i64().or_else(|| v.as_u64().map(|raw| raw as i64)));
-        if existing_bracket_id != Some(bracket_id) {
-            root.insert("bracketId".to_string(), json!(bracket_id));
-            changed = true;
+// Unparsable files are quarantined (never rewritten) after salvaging what entries they still hold.
+fn read_legacy_store(
+    app: &AppHandle,
+    root: &Path,
+    path: &Path,
+    dir_version: i64,
+) -> Option<ComputedMatchesFile> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => {
+            log_command::emit_log(app, "Computed store file unreadable, left in place");
+            return None;
         }
+    };
+
+    if let Ok(parsed) = serde_json::from_str::<ComputedMatchesFile>(&content) {
+        return Some(parsed);
     }
 
-    (value, changed)
+    let entries = salvage_entries(&content);
+    let label = format!("v{dir_version}");
+    match computed_store::quarantine_file(root, path, &label) {
+        Ok(_) => log_command::emit_log(
+            app,
+            &format!(
+                "Computed store file corrupt, quarantined ({} entries salvaged)",
+                entries.len()
+            ),
+        ),
+        Err(_) => log_command::emit_log(app, "Computed store file corrupt, quarantine failed"),
+    }
+
+    Some(ComputedMatchesFile {
+        schema_version: salvage_schema_version(&content),
+        account: String::new(),
+        updated_at_ms: 0,
+        entries,
+    })
 }
 
-fn normalize_entries_in_place(file: &mut ComputedMatchesFile) -> bool {
-    let keys: Vec<String> = file.entries.keys().cloned().collect();
-    let mut changed = false;
+// The JSON files are only read once. Entries keep the schema version of their file (raised to
+// the oldest one the migration registry knows) and are brought forward by the registry; later
+// directories win over earlier ones.
+fn import_legacy_stores(app: &AppHandle, root: &Path, conn: &mut Connection) -> Result<(), String> {
+    if computed_store::meta_value(conn, LEGACY_IMPORT_KEY)?.is_some() {
+        return Ok(());
+    }
 
-    for key in keys {
-        if let Some(entry) = file.entries.get(&key).cloned() {
-            let (normalized, entry_changed) = normalize_match_entry(entry);
-            if entry_changed {
-                file.entries.insert(key, normalized);
-                changed = true;
-            }
+    let mut imported = 0_usize;
+    for (dir_version, dir) in legacy_store_dirs(root) {
+        for path in legacy_store_files(&dir) {
+            let account = path
+                .file_stem()
+                .and_then(|v| v.to_str())
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .unwrap_or_else(|| "unknown".to_string());
+            let Some(file) = read_legacy_store(app, root, &path, dir_version) else {
+                continue;
+            };
+            let rows: Vec<(String, Value)> = file.entries.into_iter().collect();
+            imported += computed_store::upsert_entries(
+                conn,
+                &account,
+                &rows,
+                computed_migrations::normalize_schema_version(
+                    file.schema_version.unwrap_or(dir_version),
+                ),
+                file.updated_at_ms as i64,
+            )?;
         }
     }
 
-    changed
-}
-
-fn merge_entry_maps(target: &mut HashMap<String, Value>, source: HashMap<String, Value>) {
-    source.into_iter().for_each(|(match_key, entry)| {
-        target.insert(match_key, entry);
-    });
+    computed_store::set_meta_value(conn, LEGACY_IMPORT_KEY, &now_ms().to_string())?;
+    if imported > 0 {
+        log_command::emit_log(
+            app,
+            &format!("Imported {imported} computed matches from legacy store"),
+        );
+    }
+    Ok(())
 }
 
-fn promote_legacy_entries(
-    app: &AppHandle,
-    account: &str,
-    legacy_entries: &HashMap<String, Value>,
-) -> Result<(), String> {
-    if legacy_entries.is_empty() {
-        return Ok(());
+fn open_checked(app: &AppHandle, root: &Path) -> Result<Connection, String> {
+    let (conn, recovery) = computed_store::open(root)?;
+    if let Some(recovery) = recovery {
+        log_command::emit_log(
+            app,
+            &format!(
+                "Computed store database corrupt, quarantined as {} ({} entries salvaged)",
+                recovery
+                    .quarantined_path
+                    .file_name()
+                    .and_then(|v| v.to_str())
+                    .unwrap_or("unknown"),
+                recovery.salvaged
+            ),
+        );
     }
+    Ok(conn)
+}
 
-    let current_path = store_path(app, account, SCHEMA_VERSION, true)?;
-    let mut current = read_store_or_empty(&current_path, account, SCHEMA_VERSION, true);
-    let mut changed = false;
+// Store-wide lock for opening, recovery, import and migrations.
+fn lock_store(root: &Path) -> Result<FileLockGuard, String> {
+    file_lock::lock(&root.join(STORE_LOCK_NAME))
+}
 
-    legacy_entries.iter().for_each(|(match_key, entry)| {
-        if !current.entries.contains_key(match_key) {
-            current.entries.insert(match_key.clone(), entry.clone());
-            changed = true;
-        }
-    });
+// Per-account lock held across snapshot + write, so concurrent upserts serialize.
+fn lock_account(root: &Path, account: &str) -> Result<FileLockGuard, String> {
+    file_lock::lock(&root.join(LOCK_DIR).join(account))
+}
 
-    if !changed {
-        return Ok(());
+fn open_store(app: &AppHandle, flavor: GameFlavor) -> Result<Connection, String> {
+    let root = store_root(app, flavor)?;
+    let _store_lock = lock_store(&root)?;
+    let mut conn = open_checked(app, &root)?;
+    import_legacy_stores(app, &root, &mut conn)?;
+
+    let report = computed_migrations::migrate_store(&mut conn, &root.join(BACKUP_DIR), false)?;
+    if !report.pending.is_empty() {
+        log_command::emit_log(
+            app,
+            &format!(
+                "Computed store migrated to schema v{}",
+                report.current_version
+            ),
+        );
     }
+    Ok(conn)
+}
 
-    current.schema_version = SCHEMA_VERSION;
-    current.account = account.to_string();
-    current.updated_at_ms = now_ms();
-    write_store_atomic(&current_path, &current)
+// Rows whose payload no longer parses are left in the store and named in the log.
+fn reported_entries<T>(app: &AppHandle, loaded: computed_store::LoadedEntries<T>) -> Vec<T> {
+    if !loaded.unreadable.is_empty() {
+        log_command::emit_log(
+            app,
+            &format!(
+                "Computed store skipped {} unreadable matches: {}",
+                loaded.unreadable.len(),
+                loaded.unreadable.join(", ")
+            ),
+        );
+    }
+    loaded.entries
 }
 
-fn load_account_store_union(app: &AppHandle, account: &str) -> Result<ComputedMatchesFile, String> {
-    let legacy_path = store_path(app, account, LEGACY_SCHEMA_VERSION, false)?;
-    let legacy = read_store_or_empty(&legacy_path, account, LEGACY_SCHEMA_VERSION, false);
-    if !legacy.entries.is_empty() {
-        let _ = promote_legacy_entries(app, account, &legacy.entries);
+// Entries already tagged by an earlier write keep their flavor.
+fn tag_flavor(mut entry: Value, flavor: GameFlavor) -> Value {
+    if let Some(root) = entry.as_object_mut() {
+        root.entry("flavor")
+            .or_insert_with(|| serde_json::to_value(flavor).unwrap_or(Value::Null));
     }
+    entry
+}
 
-    let current_path = store_path(app, account, SCHEMA_VERSION, true)?;
-    let current = read_store_or_empty(&current_path, account, SCHEMA_VERSION, true);
+/// Keys of the matches already persisted for `account`; installs outside a `_<flavor>_` folder
+/// use the retail store.
+pub fn persisted_match_keys(
+    app: &AppHandle,
+    account: &str,
+    flavor: Option<GameFlavor>,
+) -> Result<HashSet<String>, String> {
+    let conn = open_store(app, flavor.unwrap_or_default())?;
+    computed_store::match_keys(&conn, &sanitize_account(account))
+}
 
-    let mut merged = ComputedMatchesFile::empty(account.to_string(), SCHEMA_VERSION);
-    merged.updated_at_ms = legacy.updated_at_ms.max(current.updated_at_ms);
-    merge_entry_maps(&mut merged.entries, legacy.entries);
-    merge_entry_maps(&mut merged.entries, current.entries);
-    Ok(merged)
+#[tauri::command]
+pub fn load_computed_matches(
+    app: AppHandle,
+    account: String,
+    flavor: Option<GameFlavor>,
+) -> Result<Vec<Value>, String> {
+    let conn = open_store(&app, flavor.unwrap_or_default())?;
+    let loaded = computed_store::load_account(&conn, &sanitize_account(&account))?;
+    Ok(reported_entries(&app, loaded))
 }
 
-fn read_all_stores(
-    app: &AppHandle,
-    schema_version: u8,
-    persist_normalized: bool,
-) -> Result<Vec<(String, ComputedMatchesFile)>, String> {
-    let dir = store_dir(app, schema_version, false)?;
-    if !dir.exists() {
-        return Ok(Vec::new());
+/// Every stored match of `flavor`, or of every flavor with a store when omitted.
+#[tauri::command]
+pub fn load_all_computed_matches(
+    app: AppHandle,
+    flavor: Option<GameFlavor>,
+) -> Result<Vec<Value>, String> {
+    if let Some(flavor) = flavor {
+        let loaded = computed_store::load_all(&open_store(&app, flavor)?)?;
+        return Ok(reported_entries(&app, loaded));
     }
 
     let mut out = Vec::new();
-    let read_dir =
-        fs::read_dir(&dir).map_err(|e| format!("Unable to read computed store dir: {e}"))?;
-
-    for entry in read_dir.flatten() {
-        let path = entry.path();
-        if !path.is_file() {
-            continue;
-        }
-        if path.extension().and_then(|v| v.to_str()) != Some("json") {
+    for flavor in GameFlavor::ALL {
+        if flavor != GameFlavor::Retail && !store_root(&app, flavor)?.exists() {
             continue;
         }
+        let loaded = computed_store::load_all(&open_store(&app, flavor)?)?;
+        out.extend(reported_entries(&app, loaded));
+    }
+    Ok(out)
+}
 
-        let account = path
-            .file_stem()
-            .and_then(|v| v.to_str())
-            .map(|value| value.trim().to_string())
-            .filter(|value| !value.is_empty())
-            .unwrap_or_else(|| "unknown".to_string());
-
-        let parsed = match read_store(&path, &account, schema_version, persist_normalized) {
-            Ok(value) => value,
-            Err(_) => continue,
-        };
-
-        out.push((account, parsed));
+/// Stores every match that passes validation; the ones that fail are skipped and returned.
+#[tauri::command]
+pub fn upsert_computed_matches(
+    app: AppHandle,
+    account: String,
+    flavor: Option<GameFlavor>,
+    matches: Vec<Value>,
+) -> Result<Vec<MatchValidationError>, String> {
+    let mut rejected = Vec::new();
+    let matches: Vec<Value> = matches
+        .into_iter()
+        .filter(|entry| match match_model::validate_match(entry) {
+            Ok(()) => true,
+            Err(err) => {
+                rejected.push(err);
+                false
+            }
+        })
+        .collect();
+    for err in &rejected {
+        log_command::emit_log(&app, &format!("Computed match skipped: {err}"));
+    }
+    if matches.is_empty() {
+        return Ok(rejected);
     }
 
-    Ok(out)
+    let flavor = flavor.unwrap_or_default();
+    let rows: Vec<(String, Value)> = matches
+        .into_iter()
+        .filter_map(|entry| {
+            let (mut normalized, _) = computed_migrations::normalize_match_entry(entry);
+            local_spell_model::attach_to_entry(&mut normalized);
+            let tagged = tag_flavor(normalized, flavor);
+            match_key_of(&tagged).map(|match_key| (match_key, tagged))
+        })
+        .collect();
+
+    let account = sanitize_account(&account);
+    let root = store_root(&app, flavor)?;
+    let _account_lock = lock_account(&root, &account)?;
+    let mut conn = open_store(&app, flavor)?;
+    rotate_account_backups(&root, &conn, &account)?;
+    computed_store::upsert_entries(
+        &mut conn,
+        &account,
+        &rows,
+        computed_migrations::CURRENT_SCHEMA_VERSION,
+        now_ms() as i64,
+    )?;
+    Ok(rejected)
 }
 
 #[tauri::command]
-pub fn load_computed_matches(app: AppHandle, account: String) -> Result<Vec<Value>, String> {
-    let file = load_account_store_union(&app, &account)?;
-    Ok(file.entries.into_values().collect())
+pub fn query_computed_matches(
+    app: AppHandle,
+    mut filter: ComputedMatchFilter,
+    cursor: Option<String>,
+    limit: Option<usize>,
+) -> Result<ComputedMatchPage, String> {
+    filter.account = filter.account.as_deref().map(sanitize_account);
+    let conn = open_store(&app, filter.flavor.unwrap_or_default())?;
+    computed_query::query_page(&conn, &filter, cursor.as_deref(), limit)
 }
 
+/// Stored matches of `flavor` that look like captures of the same game, across accounts.
+/// Read-only; `tolerance_seconds` bounds how far apart their start times may be.
 #[tauri::command]
-pub fn load_all_computed_matches(app: AppHandle) -> Result<Vec<Value>, String> {
-    let mut by_account: HashMap<String, HashMap<String, Value>> = HashMap::new();
+pub fn find_duplicate_matches(
+    app: AppHandle,
+    flavor: Option<GameFlavor>,
+    tolerance_seconds: Option<u32>,
+) -> Result<Vec<DuplicateMatchSuggestion>, String> {
+    let conn = open_store(&app, flavor.unwrap_or_default())?;
+    let rows = reported_entries(&app, computed_store::load_all_rows(&conn)?);
+    Ok(computed_duplicates::find_duplicates(
+        rows,
+        tolerance_seconds.unwrap_or(computed_duplicates::DEFAULT_TOLERANCE_SECONDS),
+    ))
+}
 
-    for (account, legacy) in read_all_stores(&app, LEGACY_SCHEMA_VERSION, false)? {
-        if !legacy.entries.is_empty() {
-            let _ = promote_legacy_entries(&app, &account, &legacy.entries);
-        }
-        let bucket = by_account.entry(account).or_default();
-        merge_entry_maps(bucket, legacy.entries);
+/// Folds `absorb` into `keep` (see `computed_merge::merge_entries`), stores the result under
+/// `keep` and deletes `absorb`. Both accounts get a backup generation first; returns the merged
+/// entry.
+#[tauri::command]
+pub fn merge_computed_matches(
+    app: AppHandle,
+    flavor: Option<GameFlavor>,
+    mut keep: ComputedMatchRef,
+    mut absorb: ComputedMatchRef,
+) -> Result<Value, String> {
+    keep.account = sanitize_account(&keep.account);
+    absorb.account = sanitize_account(&absorb.account);
+    if keep.account == absorb.account && keep.match_key == absorb.match_key {
+        return Err("Cannot merge a match into itself".to_string());
     }
 
-    for (account, current) in read_all_stores(&app, SCHEMA_VERSION, true)? {
-        let bucket = by_account.entry(account).or_default();
-        merge_entry_maps(bucket, current.entries);
+    let flavor = flavor.unwrap_or_default();
+    let root = store_root(&app, flavor)?;
+    let mut accounts = vec![keep.account.as_str(), absorb.account.as_str()];
+    accounts.sort();
+    accounts.dedup();
+    let _account_locks = accounts
+        .iter()
+        .map(|account| lock_account(&root, account))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut conn = open_store(&app, flavor)?;
+    let load = |target: &ComputedMatchRef| {
+        computed_store::load_entry(&conn, &target.account, &target.match_key)?.ok_or_else(|| {
+            format!(
+                "Computed match {} not found for {}",
+                target.match_key, target.account
+            )
+        })
+    };
+    let keep_entry = load(&keep)?;
+    let absorb_entry = load(&absorb)?;
+
+    let merged =
+        computed_merge::merge_entries((&keep, &keep_entry), (&absorb, &absorb_entry), now_ms());
+    match_model::validate_match(&merged).map_err(|e| e.to_string())?;
+    let (normalized, _) = computed_migrations::normalize_match_entry(merged);
+    let merged = tag_flavor(normalized, flavor);
+
+    for account in &accounts {
+        rotate_account_backups(&root, &conn, account)?;
     }
+    computed_store::write_merged(
+        &mut conn,
+        &keep.account,
+        &(keep.match_key.clone(), merged.clone()),
+        (&absorb.account, &absorb.match_key),
+        computed_migrations::CURRENT_SCHEMA_VERSION,
+        now_ms() as i64,
+    )?;
+
+    log_command::emit_log(&app, "Computed matches merged");
+    Ok(merged)
+}
 
-    Ok(by_account
-        .into_values()
-        .flat_map(|entries| entries.into_values())
-        .collect())
+/// Dry run of the computed store migrations plus the history of applied ones.
+#[tauri::command]
+pub fn plan_computed_store_migrations(
+    app: AppHandle,
+    flavor: Option<GameFlavor>,
+) -> Result<ComputedMigrationReport, String> {
+    let root = store_root(&app, flavor.unwrap_or_default())?;
+    let _store_lock = lock_store(&root)?;
+    let mut conn = open_checked(&app, &root)?;
+    import_legacy_stores(&app, &root, &mut conn)?;
+    computed_migrations::migrate_store(&mut conn, &root.join(BACKUP_DIR), true)
 }
 
+/// Rolls one account back to backup `generation`: 1 is the account as it was before its latest
+/// write, 2 before the one ahead of that, and so on. The account's current rows are copied to
+/// a pre-restore backup first (the newest three are kept), so a restore can itself be undone.
 #[tauri::command]
-pub fn upsert_computed_matches(
+pub fn restore_computed_store(
     app: AppHandle,
     account: String,
-    matches: Vec<Value>,
-) -> Result<(), String> {
-    if matches.is_empty() {
-        return Ok(());
+    flavor: Option<GameFlavor>,
+    generation: u32,
+) -> Result<usize, String> {
+    if generation == 0 || generation > BACKUP_GENERATIONS {
+        return Err(format!(
+            "Backup generation must be between 1 and {BACKUP_GENERATIONS}"
+        ));
     }
 
-    let path = store_path(&app, &account, SCHEMA_VERSION, true)?;
-    let mut file = read_store_or_empty(&path, &account, SCHEMA_VERSION, true);
+    let flavor = flavor.unwrap_or_default();
+    let account = sanitize_account(&account);
+    let root = store_root(&app, flavor)?;
+    let _account_lock = lock_account(&root, &account)?;
+    let snapshot = account_backup_path(&root, &account, generation);
+    if !snapshot.is_file() {
+        return Err(format!("Computed store backup {generation} not found"));
+    }
 
-    matches.into_iter().for_each(|entry| {
-        let (normalized, _) = normalize_match_entry(entry);
-        if let Some(match_key) = match_key_of(&normalized) {
-            file.entries.insert(match_key, normalized);
-        }
-    });
+    let mut conn = open_store(&app, flavor)?;
+    let backup_dir = root.join(BACKUP_DIR);
+    let restore_prefix = format!("pre-restore.{account}.");
+    computed_store::backup_account_to(
+        &conn,
+        &backup_dir.join(format!("{restore_prefix}{}.sqlite3", now_ms())),
+        &account,
+    )?;
+    computed_store::prune_backups(&backup_dir, &restore_prefix, KEPT_RESTORE_BACKUPS)?;
+    let restored = computed_store::restore_account_from(&mut conn, &snapshot, &account)?;
+    {
+        let _store_lock = lock_store(&root)?;
+        computed_migrations::migrate_store(&mut conn, &root.join(BACKUP_DIR), false)?;
+    }
 
-    file.schema_version = SCHEMA_VERSION;
-    file.updated_at_ms = now_ms();
-    write_store_atomic(&path, &file)
-}
\ No newline at end of file
+    log_command::emit_log(
+        &app,
+        &format!("Computed store restored from backup {generation} ({restored} entries)"),
+    );
+    Ok(restored)
+}
@@ -0,0 +1,545 @@
+use crate::computed_duplicates;
+use crate::computed_store;
+use crate::im_command;
+use crate::local_spell_model;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use ts_rs::TS;
+
+const MERGED_FROM: &str = "mergedFrom";
+const INTEGRITY_FLAGS: [&str; 3] = ["scoreboardComplete", "timelineComplete", "roundsComplete"];
+const PLAYER_LISTS: [&str; 2] = ["/players", "/soloShuffle/matchSummary/players"];
+// Fields that describe the game as the recording character saw it. They only carry over from a
+// capture recorded by the same character.
+const VIEWPOINT_FIELDS: [&str; 6] = [
+    "/computed",
+    "/localSpellCapture",
+    "/localLossOfControl",
+    "/timeline",
+    "/winner",
+    "/soloShuffle/outcome",
+];
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/ComputedMatchRef.ts")]
+pub struct ComputedMatchRef {
+    pub account: String,
+    pub match_key: String,
+}
+
+// Recursively adds what `base` lacks from `other`. Objects are unioned key by key; anything
+// else in `base` wins unless it is null.
+fn fill_missing(base: &mut Value, other: &Value) {
+    match (base, other) {
+        (Value::Object(base), Value::Object(other)) => {
+            for (key, value) in other {
+                match base.get_mut(key) {
+                    Some(existing) => fill_missing(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base @ Value::Null, other) => *base = other.clone(),
+        _ => {}
+    }
+}
+
+// Players of both captures keyed like the match fingerprint; `base` keeps its order and, unless
+// `take_stats` is set, its per-player values. The other capture fills gaps and adds players
+// `base` never saw, but never marks a player as the owner.
+fn merge_players(base: &mut Value, other: &Value, take_stats: bool) {
+    let (Some(players), Some(other_players)) = (
+        base.as_array_mut(),
+        other.as_array().filter(|players| !players.is_empty()),
+    ) else {
+        return;
+    };
+
+    for other_player in other_players {
+        let mut incoming = other_player.clone();
+        if let Some(fields) = incoming.as_object_mut() {
+            fields.remove("isOwner");
+        }
+        let key = im_command::player_key(&incoming);
+        match players
+            .iter_mut()
+            .find(|player| im_command::player_key(player) == key)
+        {
+            Some(player) => {
+                if let (true, Some(fields), Some(stats)) =
+                    (take_stats, player.as_object_mut(), incoming.as_object())
+                {
+                    for (field, value) in stats.iter().filter(|(_, value)| !value.is_null()) {
+                        fields.insert(field.clone(), value.clone());
+                    }
+                }
+                fill_missing(player, &incoming);
+            }
+            None => players.push(incoming),
+        }
+    }
+}
+
+// A flag set by either capture stays set; notes are unioned.
+fn merge_integrity(base: &mut Value, other: &Value) {
+    let (Some(integrity), Some(other)) = (base.as_object_mut(), other.as_object()) else {
+        return;
+    };
+
+    for flag in INTEGRITY_FLAGS {
+        if other.get(flag).and_then(|v| v.as_bool()) == Some(true) {
+            integrity.insert(flag.to_string(), Value::Bool(true));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let notes: Vec<Value> = [integrity.get("notes"), other.get("notes")]
+        .into_iter()
+        .flatten()
+        .filter_map(|notes| notes.as_array())
+        .flatten()
+        .filter(|note| seen.insert(note.to_string()))
+        .cloned()
+        .collect();
+    if !notes.is_empty() {
+        integrity.insert("notes".to_string(), Value::Array(notes));
+    }
+}
+
+fn cast_guid(value: &Value) -> Option<&str> {
+    value
+        .get("castGUID")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+fn sort_by_number(items: &mut [Value], field: &str) {
+    let at = |item: &Value| item.get(field).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    items.sort_by(|a, b| at(a).total_cmp(&at(b)));
+}
+
+// Adds the other capture's spell attempts whose castGUID `base` lacks, with their events, then
+// recomputes the outcome counts from the merged attempts. Attempts without a castGUID cannot be
+// matched across captures, so only `base`'s are kept. A model without detail is replaced.
+fn merge_spell_models(base: &mut Value, other: &Value) {
+    let (Some(model), Some(other_model)) = (
+        base.pointer_mut("/computed/localSpellModel"),
+        other.pointer("/computed/localSpellModel"),
+    ) else {
+        return;
+    };
+
+    let detail = |model: &Value| model.get("detailAvailable").and_then(|v| v.as_bool());
+    if detail(model) != Some(true) && detail(other_model) == Some(true) {
+        *model = other_model.clone();
+    } else {
+        let list = |model: &Value, field: &str| {
+            model
+                .get(field)
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default()
+        };
+        let mut known: HashSet<String> = list(model, "attempts")
+            .iter()
+            .filter_map(cast_guid)
+            .map(str::to_string)
+            .collect();
+        let added: Vec<Value> = list(other_model, "attempts")
+            .into_iter()
+            .filter(|attempt| cast_guid(attempt).is_some_and(|guid| known.insert(guid.to_string())))
+            .collect();
+        if added.is_empty() {
+            return;
+        }
+        let added_guids: HashSet<&str> = added.iter().filter_map(cast_guid).collect();
+        let added_events: Vec<Value> = list(other_model, "events")
+            .into_iter()
+            .filter(|event| cast_guid(event).is_some_and(|guid| added_guids.contains(guid)))
+            .collect();
+
+        let Some(fields) = model.as_object_mut() else {
+            return;
+        };
+        for (field, extra, order) in [
+            ("attempts", added, "startTime"),
+            ("events", added_events, "t"),
+        ] {
+            let mut items = match fields.remove(field) {
+                Some(Value::Array(items)) => items,
+                _ => Vec::new(),
+            };
+            items.extend(extra);
+            sort_by_number(&mut items, order);
+            fields.insert(field.to_string(), Value::Array(items));
+        }
+    }
+
+    let Some(model) = local_spell_model::resolve_local_spell_model(base) else {
+        return;
+    };
+    let counts = local_spell_model::build_spell_outcome_counts(&model);
+    if let (Some(computed), Ok(counts)) = (
+        base.get_mut("computed").and_then(|v| v.as_object_mut()),
+        serde_json::to_value(counts),
+    ) {
+        computed.insert("spellOutcomesBySpellId".to_string(), counts);
+    }
+}
+
+fn recorded_by(entry: &Value) -> Option<String> {
+    computed_store::owner_of(entry).map(im_command::player_key)
+}
+
+// `entry` without what only its recording character saw, and without owner flags.
+fn without_viewpoint(entry: &Value) -> Value {
+    let mut stripped = entry.clone();
+    for pointer in VIEWPOINT_FIELDS {
+        let (parent, field) = pointer.rsplit_once('/').unwrap_or_default();
+        if let Some(fields) = stripped.pointer_mut(parent).and_then(|v| v.as_object_mut()) {
+            fields.remove(field);
+        }
+    }
+    for pointer in PLAYER_LISTS {
+        if let Some(players) = stripped.pointer_mut(pointer).and_then(|v| v.as_array_mut()) {
+            players
+                .iter_mut()
+                .filter_map(|player| player.as_object_mut())
+                .for_each(|player| {
+                    player.remove("isOwner");
+                });
+        }
+    }
+    stripped
+}
+
+fn merged_from(entry: &Value) -> Vec<Value> {
+    entry
+        .get(MERGED_FROM)
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Combines two stored captures of one game into the entry stored under `target`.
+///
+/// The target's capture is the base, so the owner, result and spell model stay those of the
+/// character it was recorded by. The absorbed capture only fills fields the base lacks; objects
+/// are filled key by key, arrays are kept whole. Players are merged by GUID or name-realm, taking
+/// their stats from the absorbed capture when only it has a complete scoreboard. When both were
+/// recorded by the same character, the attempts of `computed.localSpellModel` are unioned by
+/// castGUID and `computed.spellOutcomesBySpellId` is recounted from them; otherwise nothing of
+/// the absorbed character's own view carries over. Integrity flags set by either capture stay
+/// set. `mergedFrom` records the absorbed capture, along with whatever either side had already
+/// absorbed.
+pub fn merge_entries(
+    target: (&ComputedMatchRef, &Value),
+    absorbed: (&ComputedMatchRef, &Value),
+    merged_at_ms: u64,
+) -> Value {
+    let (target_ref, target_entry) = target;
+    let (absorbed_ref, absorbed_entry) = absorbed;
+    let absorbed_complete = computed_duplicates::scoreboard_complete(absorbed_entry);
+    let take_stats = absorbed_complete && !computed_duplicates::scoreboard_complete(target_entry);
+    let owner = recorded_by(target_entry);
+    let same_recorder = owner.is_some() && owner == recorded_by(absorbed_entry);
+    let other_view = if same_recorder {
+        absorbed_entry.clone()
+    } else {
+        without_viewpoint(absorbed_entry)
+    };
+    let other = &other_view;
+
+    let mut merged = target_entry.clone();
+    for pointer in PLAYER_LISTS {
+        if let (Some(players), Some(other_players)) =
+            (merged.pointer_mut(pointer), other.pointer(pointer))
+        {
+            merge_players(players, other_players, take_stats);
+        }
+    }
+    for pointer in ["/integrity", "/soloShuffle/integrity"] {
+        if let (Some(integrity), Some(other_integrity)) =
+            (merged.pointer_mut(pointer), other.pointer(pointer))
+        {
+            merge_integrity(integrity, other_integrity);
+        }
+    }
+    fill_missing(&mut merged, other);
+    if same_recorder {
+        merge_spell_models(&mut merged, other);
+    }
+
+    let mut provenance = merged_from(target_entry);
+    provenance.extend(merged_from(absorbed_entry));
+    provenance.push(json!({
+        "account": absorbed_ref.account,
+        "matchKey": absorbed_ref.match_key,
+        "scoreboardComplete": absorbed_complete,
+        "mergedAtMs": merged_at_ms,
+    }));
+
+    if let Some(root) = merged.as_object_mut() {
+        root.insert(
+            "matchKey".to_string(),
+            Value::String(target_ref.match_key.clone()),
+        );
+        root.insert(MERGED_FROM.to_string(), Value::Array(provenance));
+    } else {
+        let mut root = Map::new();
+        root.insert(MERGED_FROM.to_string(), Value::Array(provenance));
+        merged = Value::Object(root);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_entries, ComputedMatchRef};
+    use serde_json::{json, Value};
+
+    fn reference(account: &str, match_key: &str) -> ComputedMatchRef {
+        ComputedMatchRef {
+            account: account.to_string(),
+            match_key: match_key.to_string(),
+        }
+    }
+
+    fn attempt(guid: &str, spell_id: i64, start: f64, outcome: &str) -> Value {
+        json!({
+            "id": guid, "spellId": spell_id, "castGUID": guid, "startTime": start,
+            "grouping": "castGUID", "resolvedOutcome": outcome,
+        })
+    }
+
+    fn capture(match_key: &str, complete: bool, players: Value, attempts: Vec<Value>) -> Value {
+        json!({
+            "matchKey": match_key,
+            "matchDetails": { "timestamp": "2026-03-14 19:42:07", "mapName": "Nagrand Arena" },
+            "players": players,
+            "integrity": { "scoreboardComplete": complete },
+            "computed": {
+                "schemaVersion": 2,
+                "spellOutcomesBySpellId": {},
+                "localSpellModel": {
+                    "schemaVersion": 2, "sourceFormat": "persisted-normalized-v2",
+                    "detailAvailable": true, "attempts": attempts, "events": [], "locEntries": [],
+                },
+            },
+        })
+    }
+
+    #[test]
+    fn complete_capture_supplies_player_stats_under_the_target_key() {
+        let partial = capture(
+            "target",
+            false,
+            json!([{ "guid": "p-1", "damage": 10 }]),
+            vec![],
+        );
+        let complete = capture(
+            "absorbed",
+            true,
+            json!([{ "guid": "p-1", "damage": 99 }]),
+            vec![],
+        );
+
+        let merged = merge_entries(
+            (&reference("Main", "target"), &partial),
+            (&reference("Alt", "absorbed"), &complete),
+            1_000,
+        );
+        assert_eq!(merged["matchKey"], "target");
+        assert_eq!(merged["players"][0]["damage"], 99);
+        assert_eq!(merged["integrity"]["scoreboardComplete"], true);
+
+        // Otherwise the target's values win.
+        let merged = merge_entries(
+            (&reference("Main", "target"), &complete),
+            (&reference("Alt", "absorbed"), &partial),
+            1_000,
+        );
+        assert_eq!(merged["players"][0]["damage"], 99);
+    }
+
+    #[test]
+    fn players_merge_by_key_and_fill_gaps() {
+        let target = capture(
+            "target",
+            true,
+            json!([
+                { "guid": "p-1", "damage": 10, "healing": null },
+                { "name": "Zug", "realm": "Ragnaros", "damage": 5 },
+            ]),
+            vec![],
+        );
+        let absorbed = capture(
+            "absorbed",
+            false,
+            json!([
+                { "guid": "P-1", "damage": 1, "healing": 7, "spec": "Frost" },
+                { "guid": "p-3", "damage": 3 },
+            ]),
+            vec![],
+        );
+
+        let merged = merge_entries(
+            (&reference("Main", "target"), &target),
+            (&reference("Alt", "absorbed"), &absorbed),
+            1_000,
+        );
+        assert_eq!(
+            merged["players"],
+            json!([
+                { "guid": "p-1", "damage": 10, "healing": 7, "spec": "Frost" },
+                { "name": "Zug", "realm": "Ragnaros", "damage": 5 },
+                { "guid": "p-3", "damage": 3 },
+            ])
+        );
+    }
+
+    #[test]
+    fn integrity_flags_stay_set_and_notes_are_unioned() {
+        let mut target = capture("target", false, json!([]), vec![]);
+        target["integrity"] = json!({ "scoreboardComplete": false, "notes": ["a"] });
+        let mut absorbed = capture("absorbed", false, json!([]), vec![]);
+        absorbed["integrity"] =
+            json!({ "timelineComplete": true, "roundsComplete": false, "notes": ["a", "b"] });
+
+        let merged = merge_entries(
+            (&reference("Main", "target"), &target),
+            (&reference("Alt", "absorbed"), &absorbed),
+            1_000,
+        );
+        assert_eq!(
+            merged["integrity"],
+            json!({
+                "scoreboardComplete": false,
+                "timelineComplete": true,
+                "roundsComplete": false,
+                "notes": ["a", "b"],
+            })
+        );
+    }
+
+    #[test]
+    fn spell_attempts_union_by_cast_guid_and_counts_are_recomputed() {
+        let owner = json!([{ "guid": "p-1", "isOwner": true }]);
+        let target = capture(
+            "target",
+            true,
+            owner.clone(),
+            vec![
+                attempt("cast-1", 100, 1.0, "succeeded"),
+                attempt("cast-3", 100, 3.0, "interrupted"),
+            ],
+        );
+        let absorbed = capture(
+            "absorbed",
+            false,
+            owner,
+            vec![
+                attempt("cast-1", 100, 1.0, "failed"),
+                attempt("cast-2", 200, 2.0, "succeeded"),
+            ],
+        );
+
+        let merged = merge_entries(
+            (&reference("Main", "target"), &target),
+            (&reference("Alt", "absorbed"), &absorbed),
+            1_000,
+        );
+        let guids: Vec<&str> = merged["computed"]["localSpellModel"]["attempts"]
+            .as_array()
+            .expect("attempts")
+            .iter()
+            .map(|attempt| attempt["castGUID"].as_str().expect("castGUID"))
+            .collect();
+        assert_eq!(guids, ["cast-1", "cast-2", "cast-3"]);
+        assert_eq!(
+            merged["computed"]["spellOutcomesBySpellId"],
+            json!({
+                "100": { "succeeded": 1, "interrupted": 1, "failed": 0 },
+                "200": { "succeeded": 1, "interrupted": 0, "failed": 0 },
+            })
+        );
+    }
+
+    #[test]
+    fn cross_account_merge_keeps_the_target_characters_view() {
+        let mut target = capture(
+            "target",
+            false,
+            json!([
+                { "guid": "p-1", "isOwner": true, "damage": 10 },
+                { "guid": "p-2", "damage": 20 },
+            ]),
+            vec![attempt("cast-1", 100, 1.0, "succeeded")],
+        );
+        target["winner"] = json!("victory");
+        target["computed"]["spellOutcomesBySpellId"] =
+            json!({ "100": { "succeeded": 1, "interrupted": 0, "failed": 0 } });
+        let mut absorbed = capture(
+            "absorbed",
+            true,
+            json!([
+                { "guid": "p-1", "damage": 11 },
+                { "guid": "p-2", "isOwner": true, "damage": 21 },
+                { "guid": "p-3", "damage": 30 },
+            ]),
+            vec![attempt("cast-9", 900, 2.0, "failed")],
+        );
+        absorbed["winner"] = json!("defeat");
+        absorbed["localSpellCapture"] = json!({ "schema": {} });
+
+        let merged = merge_entries(
+            (&reference("Main", "target"), &target),
+            (&reference("Alt", "absorbed"), &absorbed),
+            1_000,
+        );
+        assert_eq!(
+            merged["players"],
+            json!([
+                { "guid": "p-1", "isOwner": true, "damage": 11 },
+                { "guid": "p-2", "damage": 21 },
+                { "guid": "p-3", "damage": 30 },
+            ])
+        );
+        assert_eq!(merged["winner"], "victory");
+        assert_eq!(merged["computed"], target["computed"]);
+        assert!(merged.get("localSpellCapture").is_none());
+        assert_eq!(merged["integrity"]["scoreboardComplete"], true);
+    }
+
+    #[test]
+    fn merged_from_keeps_earlier_provenance() {
+        let mut target = capture("target", true, json!([]), vec![]);
+        target["mergedFrom"] = json!([{ "account": "Old", "matchKey": "older" }]);
+        let mut absorbed = capture("absorbed", false, json!([]), vec![]);
+        absorbed["mergedFrom"] = json!([{ "account": "Other", "matchKey": "oldest" }]);
+
+        let merged = merge_entries(
+            (&reference("Main", "target"), &target),
+            (&reference("Alt", "absorbed"), &absorbed),
+            1_234,
+        );
+        assert_eq!(
+            merged["mergedFrom"],
+            json!([
+                { "account": "Old", "matchKey": "older" },
+                { "account": "Other", "matchKey": "oldest" },
+                {
+                    "account": "Alt",
+                    "matchKey": "absorbed",
+                    "scoreboardComplete": false,
+                    "mergedAtMs": 1_234,
+                },
+            ])
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use crate::computed_store;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use ts_rs::TS;
+
+/// One step of the computed entry schema: turns a `from` entry into a `from + 1` entry.
+pub struct Migration {
+    pub name: &'static str,
+    pub from: i64,
+    pub apply: fn(Value) -> Value,
+}
+
+// Ordered by `from`; adding a schema means appending a step here.
+const MIGRATIONS: &[Migration] = &[Migration {
+    name: "bracket-ids",
+    from: 1,
+    apply: migrate_bracket_ids,
+}];
+
+pub const CURRENT_SCHEMA_VERSION: i64 = MIGRATIONS[MIGRATIONS.len() - 1].from + 1;
+
+/// Oldest schema the registry migrates from. Entries stamped lower, or never stamped, predate
+/// versioning and already have its shape.
+pub const OLDEST_SCHEMA_VERSION: i64 = MIGRATIONS[0].from;
+
+// Pre-migration copies hold the whole store, so only the newest few are kept.
+const KEPT_MIGRATION_BACKUPS: usize = 3;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/ComputedMigrationStep.ts")]
+pub struct ComputedMigrationStep {
+    pub name: String,
+    pub from_version: i32,
+    pub to_version: i32,
+    pub entries: usize,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/AppliedComputedMigration.ts")]
+pub struct AppliedComputedMigration {
+    pub name: String,
+    pub from_version: i32,
+    pub to_version: i32,
+    pub entries: usize,
+    pub backup_path: Option<String>,
+    #[ts(type = "number")]
+    pub applied_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/ComputedMigrationReport.ts")]
+pub struct ComputedMigrationReport {
+    pub current_version: i32,
+    pub dry_run: bool,
+    /// Steps that would run (dry run) or just ran, with the number of entries each touches.
+    pub pending: Vec<ComputedMigrationStep>,
+    /// Entries whose payload could not be parsed and were left untouched.
+    pub skipped: usize,
+    /// Entries written by a newer app version; kept as-is.
+    pub newer: usize,
+    pub backup_path: Option<String>,
+    pub applied: Vec<AppliedComputedMigration>,
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn bracket_id_from_format(format: &str) -> i64 {
+    match format.trim().to_lowercase().as_str() {
+        "solo shuffle" => 1,
+        "battleground blitz" => 2,
+        "rated arena 2v2" => 3,
+        "rated arena 3v3" => 4,
+        "rated arena" => 5,
+        "rated battleground" => 6,
+        "arena skirmish" => 7,
+        "brawl" => 8,
+        "random battleground" => 9,
+        "random epic battleground" => 10,
+        _ => 0,
+    }
+}
+
+fn count_faction_players(value: &Value) -> (usize, usize) {
+    let mut horde = 0_usize;
+    let mut alliance = 0_usize;
+
+    if let Some(players) = value.get("players").and_then(|v| v.as_array()) {
+        for player in players {
+            let faction = player
+                .get("faction")
+                .and_then(|v| v.as_i64().or_else(|| v.as_u64().map(|raw| raw as i64)));
+            match faction {
+                Some(0) => horde += 1,
+                Some(1) => alliance += 1,
+                _ => {}
+            }
+        }
+    }
+
+    (horde, alliance)
+}
+
+pub fn normalize_match_entry(mut value: Value) -> (Value, bool) {
+    let mut changed = false;
+    let (horde, alliance) = count_faction_players(&value);
+
+    let current_format = value
+        .get("matchDetails")
+        .and_then(|v| v.get("format"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+
+    let mut next_format = current_format.clone();
+    let mut bracket_id = bracket_id_from_format(&current_format);
+
+    if bracket_id == 9 && horde >= 25 && alliance >= 25 {
+        next_format = "Random Epic Battleground".to_string();
+        bracket_id = 10;
+    }
+
+    if let Some(root) = value.as_object_mut() {
+        if let Some(match_details) = root.get_mut("matchDetails").and_then(|v| v.as_object_mut()) {
+            if !next_format.is_empty() && next_format != current_format {
+                match_details.insert("format".to_string(), Value::String(next_format));
+                changed = true;
+            }
+        }
+
+        let existing_bracket_id = root
+            .get("bracketId")
+            .and_then(|v| v.as_i64().or_else(|| v.as_u64().map(|raw| raw as i64)));
+        if existing_bracket_id != Some(bracket_id) {
+            root.insert("bracketId".to_string(), json!(bracket_id));
+            changed = true;
+        }
+    }
+
+    (value, changed)
+}
+
+fn migrate_bracket_ids(entry: Value) -> Value {
+    normalize_match_entry(entry).0
+}
+
+/// Version an entry stamped `version` is migrated from; see [`OLDEST_SCHEMA_VERSION`].
+pub fn normalize_schema_version(version: i64) -> i64 {
+    version.max(OLDEST_SCHEMA_VERSION)
+}
+
+fn step_from(version: i64) -> Option<&'static Migration> {
+    MIGRATIONS.iter().find(|step| step.from == version)
+}
+
+/// Runs every step from `version` up to [`CURRENT_SCHEMA_VERSION`].
+pub fn migrate_entry(
+    mut entry: Value,
+    version: i64,
+) -> Result<(Value, Vec<&'static Migration>), String> {
+    let mut steps = Vec::new();
+    let mut at = normalize_schema_version(version);
+    while at < CURRENT_SCHEMA_VERSION {
+        let step = step_from(at)
+            .ok_or_else(|| format!("No computed store migration from schema v{at}"))?;
+        entry = (step.apply)(entry);
+        steps.push(step);
+        at += 1;
+    }
+    Ok((entry, steps))
+}
+
+fn applied_history(conn: &Connection) -> Result<Vec<AppliedComputedMigration>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name, from_version, to_version, entries, backup_path, applied_at_ms
+             FROM applied_migrations ORDER BY id",
+        )
+        .map_err(|e| format!("Unable to read computed store migrations: {e}"))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(AppliedComputedMigration {
+                name: row.get(0)?,
+                from_version: row.get(1)?,
+                to_version: row.get(2)?,
+                entries: row.get::<_, i64>(3)? as usize,
+                backup_path: row.get(4)?,
+                applied_at_ms: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Unable to read computed store migrations: {e}"))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Unable to read computed store migrations: {e}"))
+}
+
+/// Migrates every stored entry below [`CURRENT_SCHEMA_VERSION`]. With `dry_run` nothing is
+/// written; otherwise the database is copied into `backup_dir` before the first change, and
+/// older copies beyond the newest three are removed.
+pub fn migrate_store(
+    conn: &mut Connection,
+    backup_dir: &Path,
+    dry_run: bool,
+) -> Result<ComputedMigrationReport, String> {
+    let rows = computed_store::rows_below_version(conn, CURRENT_SCHEMA_VERSION)?;
+    let newer = computed_store::count_above_version(conn, CURRENT_SCHEMA_VERSION)?;
+
+    let mut skipped = 0_usize;
+    let mut step_counts: BTreeMap<(i64, &'static str), usize> = BTreeMap::new();
+    let mut by_account: HashMap<String, Vec<(String, Value)>> = HashMap::new();
+
+    for row in rows {
+        let migrated = serde_json::from_str::<Value>(&row.payload)
+            .map_err(|e| e.to_string())
+            .and_then(|entry| migrate_entry(entry, row.schema_version));
+        let Ok((entry, steps)) = migrated else {
+            skipped += 1;
+            continue;
+        };
+        steps.iter().for_each(|step| {
+            *step_counts.entry((step.from, step.name)).or_default() += 1;
+        });
+        by_account
+            .entry(row.account)
+            .or_default()
+            .push((row.match_key, entry));
+    }
+
+    let pending: Vec<ComputedMigrationStep> = step_counts
+        .iter()
+        .map(|((from, name), entries)| ComputedMigrationStep {
+            name: name.to_string(),
+            from_version: *from as i32,
+            to_version: (*from + 1) as i32,
+            entries: *entries,
+        })
+        .collect();
+
+    let mut report = ComputedMigrationReport {
+        current_version: CURRENT_SCHEMA_VERSION as i32,
+        dry_run,
+        pending,
+        skipped,
+        newer,
+        backup_path: None,
+        applied: Vec::new(),
+    };
+
+    if dry_run {
+        report.applied = applied_history(conn)?;
+        return Ok(report);
+    }
+    if by_account.is_empty() {
+        return Ok(report);
+    }
+
+    let applied_at_ms = now_ms();
+    let backup_path: PathBuf = backup_dir.join(format!(
+        "pre-migration-v{CURRENT_SCHEMA_VERSION}-{applied_at_ms}.sqlite3"
+    ));
+    computed_store::backup_to(conn, &backup_path)?;
+    computed_store::prune_backups(backup_dir, "pre-migration-", KEPT_MIGRATION_BACKUPS)?;
+    let backup_label = backup_path.to_string_lossy().to_string();
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Unable to begin computed store transaction: {e}"))?;
+    for (account, entries) in &by_account {
+        computed_store::write_rows(&tx, account, entries, CURRENT_SCHEMA_VERSION, applied_at_ms)?;
+    }
+    for step in &report.pending {
+        tx.execute(
+            "INSERT INTO applied_migrations
+                (name, from_version, to_version, entries, backup_path, applied_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                step.name,
+                step.from_version,
+                step.to_version,
+                step.entries as i64,
+                backup_label,
+                applied_at_ms
+            ],
+        )
+        .map_err(|e| format!("Unable to record computed store migration: {e}"))?;
+    }
+    tx.commit()
+        .map_err(|e| format!("Unable to commit computed store migration: {e}"))?;
+
+    report.backup_path = Some(backup_label);
+    report.applied = applied_history(conn)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_below_the_oldest_schema_are_migrated_from_it() {
+        let entry = json!({ "matchDetails": { "format": "Rated Arena 3v3" } });
+        for version in [-1, 0, OLDEST_SCHEMA_VERSION] {
+            let (migrated, steps) = migrate_entry(entry.clone(), version).unwrap();
+            assert_eq!(migrated["bracketId"], json!(4));
+            assert_eq!(
+                steps.iter().map(|step| step.from).collect::<Vec<_>>(),
+                (OLDEST_SCHEMA_VERSION..CURRENT_SCHEMA_VERSION).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn current_entries_need_no_steps() {
+        let (_, steps) = migrate_entry(json!({}), CURRENT_SCHEMA_VERSION).unwrap();
+        assert!(steps.is_empty());
+    }
+}
@@ -0,0 +1,669 @@
+use crate::computed_store;
+use crate::game_monitor::GameFlavor;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ts_rs::TS;
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+// Rows one page may read while the payload filters (result, opponent comps) reject them. A
+// page that runs out stops early, possibly short, with a cursor to continue from.
+const MAX_SCANNED_ROWS: usize = 2_000;
+
+// Bracket ids 1..=6 are the rated ones (see `matchBrackets.ts`).
+const RATED_BRACKET_MIN: i64 = 1;
+const RATED_BRACKET_MAX: i64 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../../src/Interfaces/ComputedMatchResult.ts")]
+pub enum ComputedMatchResult {
+    Win,
+    Loss,
+    Neutral,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+#[serde(rename_all = "camelCase", default)]
+#[ts(export, export_to = "../../src/Interfaces/ComputedMatchFilter.ts")]
+pub struct ComputedMatchFilter {
+    /// Store partition to page through; retail when omitted.
+    #[ts(optional)]
+    pub flavor: Option<GameFlavor>,
+    #[ts(optional)]
+    pub account: Option<String>,
+    /// Owner character key as built by `buildCharacterKey` (`char:<realm>:<name>`).
+    #[ts(optional)]
+    pub character: Option<String>,
+    #[ts(optional)]
+    pub bracket_ids: Option<Vec<i32>>,
+    #[ts(optional)]
+    pub rated: Option<bool>,
+    /// Inclusive bounds compared against `matchDetails.timestamp`; a date-only `to` covers the whole day.
+    #[ts(optional)]
+    pub from: Option<String>,
+    #[ts(optional)]
+    pub to: Option<String>,
+    #[ts(optional)]
+    pub map: Option<String>,
+    #[ts(optional)]
+    pub result: Option<ComputedMatchResult>,
+    /// Enemy spec line-ups; a match passes when its opponents equal any of them, in any order.
+    #[ts(optional)]
+    pub opponent_comps: Option<Vec<Vec<String>>>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/ComputedMatchPage.ts")]
+pub struct ComputedMatchPage {
+    #[ts(type = "unknown[]")]
+    pub matches: Vec<Value>,
+    /// Pass back as `cursor` to fetch the next page; `null` once the history is exhausted.
+    pub next_cursor: Option<String>,
+}
+
+// Keyset position of the last row on a page, serialized into the opaque cursor string.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PageCursor {
+    played_at: String,
+    account: String,
+    match_key: String,
+}
+
+fn number_of(value: Option<&Value>) -> Option<f64> {
+    value.and_then(|v| {
+        v.as_f64()
+            .or_else(|| v.as_str().and_then(|raw| raw.trim().parse::<f64>().ok()))
+    })
+}
+
+fn rating_delta(entry: &Value) -> Option<f64> {
+    if let Some(owner) = computed_store::owner_of(entry) {
+        let prematch = number_of(owner.get("prematchMMR"));
+        let postmatch = number_of(owner.get("postmatchMMR"));
+        if let (Some(pre), Some(post)) = (prematch, postmatch) {
+            return Some(post - pre);
+        }
+        if let Some(change) = number_of(owner.get("ratingChange")) {
+            return Some(change);
+        }
+    }
+
+    number_of(
+        entry
+            .get("soloShuffle")
+            .and_then(|v| v.get("matchSummary"))
+            .and_then(|v| v.get("ratingChange")),
+    )
+}
+
+/// Same precedence as `getResult` in the match history: winner, rating delta, shuffle outcome.
+pub fn match_result(entry: &Value) -> ComputedMatchResult {
+    let winner = entry
+        .get("winner")
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_lowercase());
+    match winner.as_deref() {
+        Some("victory") => return ComputedMatchResult::Win,
+        Some("defeat") => return ComputedMatchResult::Loss,
+        Some("draw") => return ComputedMatchResult::Neutral,
+        _ => {}
+    }
+
+    match rating_delta(entry) {
+        Some(delta) if delta > 0.0 => return ComputedMatchResult::Win,
+        Some(delta) if delta < 0.0 => return ComputedMatchResult::Loss,
+        _ => {}
+    }
+
+    let outcome = entry
+        .get("soloShuffle")
+        .and_then(|v| v.get("outcome"))
+        .and_then(|v| v.get("result"))
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_lowercase())
+        .unwrap_or_default();
+    if outcome.contains("win") {
+        ComputedMatchResult::Win
+    } else if outcome.contains("loss") {
+        ComputedMatchResult::Loss
+    } else {
+        ComputedMatchResult::Neutral
+    }
+}
+
+fn normalized_comp<'a>(specs: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut comp: Vec<String> = specs
+        .map(|spec| spec.trim().to_lowercase())
+        .filter(|spec| !spec.is_empty())
+        .collect();
+    comp.sort();
+    comp
+}
+
+/// Specs of the players on the other team (faction) from the owner, sorted and lowercased.
+pub fn opponent_comp(entry: &Value) -> Vec<String> {
+    let owner_faction = computed_store::owner_of(entry).and_then(|owner| owner.get("faction"));
+    let Some(owner_faction) = owner_faction.filter(|v| !v.is_null()) else {
+        return Vec::new();
+    };
+
+    let players = entry
+        .get("players")
+        .and_then(|v| v.as_array())
+        .map(|players| players.as_slice())
+        .unwrap_or_default();
+    normalized_comp(
+        players
+            .iter()
+            .filter(|player| {
+                player
+                    .get("faction")
+                    .is_some_and(|faction| !faction.is_null() && faction != owner_faction)
+            })
+            .filter_map(|player| player.get("spec").and_then(|v| v.as_str())),
+    )
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn build_where(
+    filter: &ComputedMatchFilter,
+    cursor: Option<&PageCursor>,
+) -> (String, Vec<SqlValue>) {
+    let mut clauses: Vec<String> = Vec::new();
+    let mut args: Vec<SqlValue> = Vec::new();
+
+    if let Some(account) = non_empty(&filter.account) {
+        clauses.push("account = ?".to_string());
+        args.push(SqlValue::Text(account.to_string()));
+    }
+    if let Some(character) = non_empty(&filter.character) {
+        clauses.push("owner_key = ?".to_string());
+        args.push(SqlValue::Text(character.to_lowercase()));
+    }
+    if let Some(bracket_ids) = filter.bracket_ids.as_ref().filter(|ids| !ids.is_empty()) {
+        let placeholders = vec!["?"; bracket_ids.len()].join(", ");
+        clauses.push(format!("bracket_id IN ({placeholders})"));
+        args.extend(
+            bracket_ids
+                .iter()
+                .map(|id| SqlValue::Integer(i64::from(*id))),
+        );
+    }
+    match filter.rated {
+        Some(true) => clauses.push(format!(
+            "bracket_id BETWEEN {RATED_BRACKET_MIN} AND {RATED_BRACKET_MAX}"
+        )),
+        Some(false) => clauses.push(format!(
+            "bracket_id NOT BETWEEN {RATED_BRACKET_MIN} AND {RATED_BRACKET_MAX}"
+        )),
+        None => {}
+    }
+    if let Some(from) = non_empty(&filter.from) {
+        clauses.push("played_at >= ?".to_string());
+        args.push(SqlValue::Text(from.to_string()));
+    }
+    if let Some(to) = non_empty(&filter.to) {
+        clauses.push("substr(played_at, 1, length(?)) <= ?".to_string());
+        args.push(SqlValue::Text(to.to_string()));
+        args.push(SqlValue::Text(to.to_string()));
+    }
+    if let Some(map) = non_empty(&filter.map) {
+        clauses.push("map_name = ? COLLATE NOCASE".to_string());
+        args.push(SqlValue::Text(map.to_string()));
+    }
+    if let Some(cursor) = cursor {
+        clauses.push("(played_at, account, match_key) < (?, ?, ?)".to_string());
+        args.push(SqlValue::Text(cursor.played_at.clone()));
+        args.push(SqlValue::Text(cursor.account.clone()));
+        args.push(SqlValue::Text(cursor.match_key.clone()));
+    }
+
+    let sql = if clauses.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", clauses.join(" AND "))
+    };
+    (sql, args)
+}
+
+// Result and opponent comp live inside the payload, so they are checked after the SQL prefilter.
+fn payload_matches(filter: &ComputedMatchFilter, entry: &Value) -> bool {
+    if let Some(result) = filter.result {
+        if match_result(entry) != result {
+            return false;
+        }
+    }
+
+    if let Some(comps) = filter
+        .opponent_comps
+        .as_ref()
+        .filter(|comps| !comps.is_empty())
+    {
+        let actual = opponent_comp(entry);
+        return comps
+            .iter()
+            .any(|comp| normalized_comp(comp.iter().map(String::as_str)) == actual);
+    }
+
+    true
+}
+
+/// Returns up to `limit` matches, newest first, starting after `cursor`. A page can come back
+/// short, even empty, with a `next_cursor` when the payload filters rejected too many rows.
+pub fn query_page(
+    conn: &Connection,
+    filter: &ComputedMatchFilter,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> Result<ComputedMatchPage, String> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let cursor = cursor
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(serde_json::from_str::<PageCursor>)
+        .transpose()
+        .map_err(|e| format!("Invalid computed match cursor: {e}"))?;
+
+    let (where_sql, args) = build_where(filter, cursor.as_ref());
+    let sql = format!(
+        "SELECT played_at, account, match_key, payload FROM computed_matches {where_sql}
+         ORDER BY played_at DESC, account DESC, match_key DESC"
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Unable to query computed store: {e}"))?;
+    let mut rows = stmt
+        .query(params_from_iter(args))
+        .map_err(|e| format!("Unable to query computed store: {e}"))?;
+
+    let mut matches = Vec::new();
+    // Last row read, matching or not; the next page starts after it.
+    let mut last: Option<PageCursor> = None;
+    let mut has_more = false;
+    let mut scanned = 0_usize;
+
+    while let Some(row) = rows
+        .next()
+        .map_err(|e| format!("Unable to read computed match row: {e}"))?
+    {
+        if scanned == MAX_SCANNED_ROWS {
+            has_more = true;
+            break;
+        }
+        let payload: String = row
+            .get(3)
+            .map_err(|e| format!("Unable to read computed match row: {e}"))?;
+        let entry = serde_json::from_str::<Value>(&payload)
+            .ok()
+            .filter(|entry| payload_matches(filter, entry));
+        if entry.is_some() && matches.len() == limit {
+            has_more = true;
+            break;
+        }
+
+        scanned += 1;
+        last = Some(PageCursor {
+            played_at: row
+                .get::<_, Option<String>>(0)
+                .ok()
+                .flatten()
+                .unwrap_or_default(),
+            account: row.get(1).unwrap_or_default(),
+            match_key: row.get(2).unwrap_or_default(),
+        });
+        matches.extend(entry);
+    }
+
+    let next_cursor = match last.filter(|_| has_more) {
+        Some(cursor) => Some(
+            serde_json::to_string(&cursor)
+                .map_err(|e| format!("Unable to encode computed match cursor: {e}"))?,
+        ),
+        None => None,
+    };
+
+    Ok(ComputedMatchPage {
+        matches,
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    struct TestStore {
+        dir: PathBuf,
+        conn: Connection,
+    }
+
+    impl Drop for TestStore {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn store(name: &str) -> TestStore {
+        let dir =
+            std::env::temp_dir().join(format!("pvp-scalpel-query-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let (conn, _) = computed_store::open(&dir).unwrap();
+        TestStore { dir, conn }
+    }
+
+    fn entry(timestamp: &str, map: &str, winner: &str, opponents: &[&str]) -> Value {
+        let mut players = vec![json!({
+            "name": "Owner", "realm": "Realm", "isOwner": true, "faction": 0, "spec": "Arms"
+        })];
+        players.extend(
+            opponents
+                .iter()
+                .map(|spec| json!({ "name": "Enemy", "faction": 1, "spec": spec })),
+        );
+        json!({
+            "bracketId": 1,
+            "winner": winner,
+            "matchDetails": { "timestamp": timestamp, "mapName": map },
+            "players": players,
+        })
+    }
+
+    fn insert(store: &mut TestStore, account: &str, rows: &[(&str, Value)]) {
+        let rows: Vec<(String, Value)> = rows
+            .iter()
+            .map(|(key, entry)| (key.to_string(), entry.clone()))
+            .collect();
+        computed_store::upsert_entries(&mut store.conn, account, &rows, 1, 0).unwrap();
+    }
+
+    fn keys(page: &ComputedMatchPage) -> Vec<String> {
+        page.matches
+            .iter()
+            .map(|entry| {
+                entry["matchDetails"]["mapName"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    // Walks every page and returns the map names in order (maps double as row labels here).
+    fn all_pages(conn: &Connection, filter: &ComputedMatchFilter, limit: usize) -> Vec<String> {
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = query_page(conn, filter, cursor.as_deref(), Some(limit)).unwrap();
+            seen.extend(keys(&page));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return seen,
+            }
+        }
+    }
+
+    #[test]
+    fn cursor_breaks_played_at_ties_without_skipping_or_repeating() {
+        let mut store = store("ties");
+        let same = "2024-05-01 20:00:00";
+        insert(
+            &mut store,
+            "A",
+            &[
+                ("k1", entry(same, "a-k1", "victory", &[])),
+                ("k2", entry(same, "a-k2", "victory", &[])),
+            ],
+        );
+        insert(
+            &mut store,
+            "B",
+            &[
+                ("k1", entry(same, "b-k1", "victory", &[])),
+                ("k0", entry("2024-04-01 20:00:00", "b-k0", "victory", &[])),
+            ],
+        );
+
+        let seen = all_pages(&store.conn, &ComputedMatchFilter::default(), 1);
+        assert_eq!(seen, vec!["b-k1", "a-k2", "a-k1", "b-k0"]);
+    }
+
+    #[test]
+    fn last_page_has_no_cursor() {
+        let mut store = store("last-page");
+        insert(
+            &mut store,
+            "A",
+            &[
+                ("k1", entry("2024-05-01", "one", "victory", &[])),
+                ("k2", entry("2024-05-02", "two", "victory", &[])),
+            ],
+        );
+
+        let full = query_page(&store.conn, &ComputedMatchFilter::default(), None, Some(2)).unwrap();
+        assert_eq!(keys(&full), vec!["two", "one"]);
+        assert!(full.next_cursor.is_none());
+
+        let first =
+            query_page(&store.conn, &ComputedMatchFilter::default(), None, Some(1)).unwrap();
+        let second = query_page(
+            &store.conn,
+            &ComputedMatchFilter::default(),
+            first.next_cursor.as_deref(),
+            Some(1),
+        )
+        .unwrap();
+        assert_eq!(keys(&second), vec!["one"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    fn filtered_pages_only_return_matching_rows() {
+        let mut store = store("filtered");
+        let rows: Vec<(String, Value)> = (0..10)
+            .map(|i| {
+                let winner = if i % 3 == 0 { "victory" } else { "defeat" };
+                (
+                    format!("k{i}"),
+                    entry(
+                        &format!("2024-05-{:02}", i + 1),
+                        &format!("m{i}"),
+                        winner,
+                        &[],
+                    ),
+                )
+            })
+            .collect();
+        computed_store::upsert_entries(&mut store.conn, "A", &rows, 1, 0).unwrap();
+
+        let filter = ComputedMatchFilter {
+            result: Some(ComputedMatchResult::Win),
+            ..Default::default()
+        };
+        assert_eq!(
+            all_pages(&store.conn, &filter, 1),
+            vec!["m9", "m6", "m3", "m0"]
+        );
+    }
+
+    #[test]
+    fn scan_budget_ends_a_page_early_with_a_cursor() {
+        let mut store = store("budget");
+        let mut rows: Vec<(String, Value)> = (0..MAX_SCANNED_ROWS + 5)
+            .map(|i| {
+                (
+                    format!("k{i:05}"),
+                    entry(&format!("2024-06-01 {i:05}"), "loss", "defeat", &[]),
+                )
+            })
+            .collect();
+        rows.push((
+            "old".to_string(),
+            entry("2024-01-01", "win", "victory", &[]),
+        ));
+        computed_store::upsert_entries(&mut store.conn, "A", &rows, 1, 0).unwrap();
+
+        let filter = ComputedMatchFilter {
+            result: Some(ComputedMatchResult::Win),
+            ..Default::default()
+        };
+        let first = query_page(&store.conn, &filter, None, Some(10)).unwrap();
+        assert!(first.matches.is_empty());
+        assert!(first.next_cursor.is_some());
+        assert_eq!(all_pages(&store.conn, &filter, 10), vec!["win"]);
+    }
+
+    #[test]
+    fn column_filters_narrow_the_page() {
+        let mut store = store("columns");
+        insert(
+            &mut store,
+            "A",
+            &[
+                (
+                    "k1",
+                    entry("2024-05-01 10:00", "Nagrand Arena", "victory", &[]),
+                ),
+                (
+                    "k2",
+                    entry("2024-05-02 10:00", "Dalaran Sewers", "victory", &[]),
+                ),
+                (
+                    "k3",
+                    entry("2024-05-03 10:00", "Nagrand Arena", "defeat", &[]),
+                ),
+            ],
+        );
+        insert(
+            &mut store,
+            "B",
+            &[(
+                "k4",
+                entry("2024-05-04 10:00", "Nagrand Arena", "victory", &[]),
+            )],
+        );
+
+        let by_map = ComputedMatchFilter {
+            account: Some("A".to_string()),
+            map: Some("nagrand arena".to_string()),
+            ..Default::default()
+        };
+        let page = query_page(&store.conn, &by_map, None, None).unwrap();
+        assert_eq!(page.matches.len(), 2);
+
+        let by_date = ComputedMatchFilter {
+            from: Some("2024-05-02".to_string()),
+            to: Some("2024-05-03".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            all_pages(&store.conn, &by_date, 10),
+            vec!["Nagrand Arena", "Dalaran Sewers"]
+        );
+
+        let by_character = ComputedMatchFilter {
+            character: Some("char:realm:owner".to_string()),
+            rated: Some(false),
+            ..Default::default()
+        };
+        assert!(query_page(&store.conn, &by_character, None, None)
+            .unwrap()
+            .matches
+            .is_empty());
+    }
+
+    #[test]
+    fn opponent_comps_match_in_any_order() {
+        let mut store = store("comps");
+        insert(
+            &mut store,
+            "A",
+            &[
+                (
+                    "k1",
+                    entry(
+                        "2024-05-01",
+                        "rmp",
+                        "victory",
+                        &["Subtlety", "Frost", "Discipline"],
+                    ),
+                ),
+                (
+                    "k2",
+                    entry(
+                        "2024-05-02",
+                        "jungle",
+                        "victory",
+                        &["Beast Mastery", "Feral", "Restoration"],
+                    ),
+                ),
+            ],
+        );
+
+        let filter = ComputedMatchFilter {
+            opponent_comps: Some(vec![vec![
+                "frost".to_string(),
+                "discipline".to_string(),
+                "subtlety".to_string(),
+            ]]),
+            ..Default::default()
+        };
+        assert_eq!(all_pages(&store.conn, &filter, 10), vec!["rmp"]);
+    }
+
+    #[test]
+    fn matches_without_an_owner_have_no_opponent_comp() {
+        let mut entry = entry("2024-05-01", "rmp", "", &["Subtlety", "Frost"]);
+        entry["players"][0]["isOwner"] = json!(false);
+        assert!(opponent_comp(&entry).is_empty());
+        assert_eq!(match_result(&entry), ComputedMatchResult::Neutral);
+    }
+
+    #[test]
+    fn keyset_order_and_map_filter_use_indexes() {
+        let store = store("plan");
+        let plan = |filter: &ComputedMatchFilter| -> String {
+            let cursor = PageCursor {
+                played_at: "2024".to_string(),
+                account: "A".to_string(),
+                match_key: "k".to_string(),
+            };
+            let (where_sql, args) = build_where(filter, Some(&cursor));
+            let sql = format!(
+                "EXPLAIN QUERY PLAN SELECT payload FROM computed_matches {where_sql}
+                 ORDER BY played_at DESC, account DESC, match_key DESC"
+            );
+            let mut stmt = store.conn.prepare(&sql).unwrap();
+            let details: Vec<String> = stmt
+                .query_map(params_from_iter(args), |row| row.get::<_, String>(3))
+                .unwrap()
+                .map(Result::unwrap)
+                .collect();
+            details.join("\n")
+        };
+
+        let unfiltered = plan(&ComputedMatchFilter::default());
+        assert!(
+            unfiltered.contains("idx_computed_matches_page"),
+            "{unfiltered}"
+        );
+        assert!(!unfiltered.contains("TEMP B-TREE"), "{unfiltered}");
+
+        let by_map = plan(&ComputedMatchFilter {
+            map: Some("Nagrand Arena".to_string()),
+            ..Default::default()
+        });
+        assert!(
+            by_map.contains("idx_computed_matches_map_nocase"),
+            "{by_map}"
+        );
+    }
+}
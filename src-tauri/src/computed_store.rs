@@ -0,0 +1,665 @@
+use rusqlite::{params, Connection, ErrorCode, OpenFlags, OptionalExtension, Transaction};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DB_FILE_NAME: &str = "computed_matches.sqlite3";
+const QUARANTINE_DIR: &str = "quarantine";
+
+// Databases that already passed `quick_check` in this process.
+static CHECKED_STORES: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS computed_matches (
+    account TEXT NOT NULL,
+    match_key TEXT NOT NULL,
+    schema_version INTEGER NOT NULL,
+    bracket_id INTEGER NOT NULL,
+    played_at TEXT,
+    map_name TEXT,
+    owner_key TEXT,
+    owner_spec TEXT,
+    updated_at_ms INTEGER NOT NULL,
+    payload TEXT NOT NULL,
+    PRIMARY KEY (account, match_key)
+);
+CREATE INDEX IF NOT EXISTS idx_computed_matches_bracket
+    ON computed_matches (account, bracket_id, played_at);
+DROP INDEX IF EXISTS idx_computed_matches_played_at;
+DROP INDEX IF EXISTS idx_computed_matches_map;
+-- Serves the keyset order of `computed_query::query_page`.
+CREATE INDEX IF NOT EXISTS idx_computed_matches_page
+    ON computed_matches (played_at, account, match_key);
+-- Map filters compare case-insensitively.
+CREATE INDEX IF NOT EXISTS idx_computed_matches_map_nocase
+    ON computed_matches (map_name COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS idx_computed_matches_owner
+    ON computed_matches (owner_key, owner_spec);
+CREATE INDEX IF NOT EXISTS idx_computed_matches_schema
+    ON computed_matches (schema_version);
+CREATE TABLE IF NOT EXISTS store_meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS applied_migrations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    from_version INTEGER NOT NULL,
+    to_version INTEGER NOT NULL,
+    entries INTEGER NOT NULL,
+    backup_path TEXT,
+    applied_at_ms INTEGER NOT NULL
+);
+";
+
+pub struct StoreRecovery {
+    pub quarantined_path: PathBuf,
+    pub salvaged: usize,
+}
+
+/// Stored entries plus the `account/match_key` of rows whose payload no longer parses; those
+/// rows stay in the table untouched so a later fix can still recover them.
+#[derive(Default)]
+pub struct LoadedEntries<T> {
+    pub entries: Vec<T>,
+    pub unreadable: Vec<String>,
+}
+
+// Values lifted out of the payload so SQLite can filter without parsing JSON.
+struct IndexedColumns {
+    bracket_id: i64,
+    played_at: String,
+    map_name: Option<String>,
+    owner_key: Option<String>,
+    owner_spec: Option<String>,
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn non_empty_str(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(|v| v.as_str())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+// Mirrors `buildCharacterKey` in the frontend so stored keys match the scope picker.
+fn character_key(name: &str, realm: Option<&str>) -> Option<String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    let realm = realm.map(|v| v.trim().to_lowercase()).unwrap_or_default();
+    Some(format!("char:{realm}:{name}"))
+}
+
+pub fn owner_of(entry: &Value) -> Option<&Value> {
+    let players = entry.get("players").and_then(|v| v.as_array())?;
+    players
+        .iter()
+        .find(|player| player.get("isOwner").and_then(|v| v.as_bool()) == Some(true))
+}
+
+fn indexed_columns(entry: &Value) -> IndexedColumns {
+    let details = entry.get("matchDetails");
+    let owner = owner_of(entry);
+
+    IndexedColumns {
+        bracket_id: entry.get("bracketId").and_then(|v| v.as_i64()).unwrap_or(0),
+        // Kept non-null so keyset pagination can compare it directly.
+        played_at: non_empty_str(details.and_then(|d| d.get("timestamp"))).unwrap_or_default(),
+        map_name: non_empty_str(details.and_then(|d| d.get("mapName"))),
+        owner_key: owner.and_then(|player| {
+            let name = non_empty_str(player.get("name"))?;
+            character_key(&name, non_empty_str(player.get("realm")).as_deref())
+        }),
+        owner_spec: owner.and_then(|player| non_empty_str(player.get("spec"))),
+    }
+}
+
+fn open_at(path: &Path) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(Duration::from_secs(5))?;
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = FULL;")?;
+    conn.execute_batch(SCHEMA)?;
+    Ok(conn)
+}
+
+fn is_corruption(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+    )
+}
+
+fn passes_quick_check(conn: &Connection) -> bool {
+    conn.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0))
+        .map(|result| result == "ok")
+        .unwrap_or(false)
+}
+
+/// Moves `path` into `<root>/quarantine/` under a timestamped name; never deletes it.
+pub fn quarantine_file(root: &Path, path: &Path, label: &str) -> Result<PathBuf, String> {
+    let dir = root.join(QUARANTINE_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("Unable to create quarantine directory: {e}"))?;
+
+    let file_name = path
+        .file_name()
+        .and_then(|v| v.to_str())
+        .unwrap_or("unknown");
+    let target = dir.join(format!("{}-{label}-{file_name}", now_ms()));
+    fs::rename(path, &target).map_err(|e| format!("Unable to quarantine {file_name}: {e}"))?;
+    Ok(target)
+}
+
+// Reads whatever rows the damaged database still yields, stopping at the first bad page.
+fn salvage_rows(path: &Path) -> Vec<(StoredRow, i64)> {
+    let Ok(conn) = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+        return Vec::new();
+    };
+    let Ok(mut stmt) = conn.prepare(
+        "SELECT account, match_key, schema_version, payload, updated_at_ms FROM computed_matches",
+    ) else {
+        return Vec::new();
+    };
+    let Ok(mut rows) = stmt.query([]) else {
+        return Vec::new();
+    };
+
+    let mut out = Vec::new();
+    while let Ok(Some(row)) = rows.next() {
+        let read = || -> rusqlite::Result<(StoredRow, i64)> {
+            Ok((
+                StoredRow {
+                    account: row.get(0)?,
+                    match_key: row.get(1)?,
+                    schema_version: row.get(2)?,
+                    payload: row.get(3)?,
+                },
+                row.get(4)?,
+            ))
+        };
+        if let Ok(salvaged) = read() {
+            out.push(salvaged);
+        }
+    }
+    out
+}
+
+fn salvage_meta(path: &Path) -> Vec<(String, String)> {
+    let Ok(conn) = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+        return Vec::new();
+    };
+    let Ok(mut stmt) = conn.prepare("SELECT key, value FROM store_meta") else {
+        return Vec::new();
+    };
+    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map(|rows| rows.flatten().collect())
+        .unwrap_or_default()
+}
+
+fn recover(dir: &Path, path: &Path) -> Result<(Connection, StoreRecovery), String> {
+    let quarantined_path = quarantine_file(dir, path, "db")?;
+    for suffix in ["-wal", "-shm"] {
+        let sidecar = PathBuf::from(format!("{}{suffix}", path.to_string_lossy()));
+        if sidecar.exists() {
+            let _ = quarantine_file(dir, &sidecar, "db");
+        }
+    }
+
+    let salvaged = salvage_rows(&quarantined_path);
+    let salvaged_meta = salvage_meta(&quarantined_path);
+    let mut conn =
+        open_at(path).map_err(|e| format!("Unable to open computed store database: {e}"))?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Unable to begin computed store transaction: {e}"))?;
+    let mut restored = 0_usize;
+    for (row, updated_at_ms) in salvaged {
+        let Ok(entry) = serde_json::from_str::<Value>(&row.payload) else {
+            continue;
+        };
+        restored += write_rows(
+            &tx,
+            &row.account,
+            &[(row.match_key, entry)],
+            row.schema_version,
+            updated_at_ms,
+        )?;
+    }
+    tx.commit()
+        .map_err(|e| format!("Unable to commit computed store transaction: {e}"))?;
+    for (key, value) in salvaged_meta {
+        set_meta_value(&conn, &key, &value)?;
+    }
+
+    Ok((
+        conn,
+        StoreRecovery {
+            quarantined_path,
+            salvaged: restored,
+        },
+    ))
+}
+
+/// Opens (creating if needed) the computed match database inside `dir`. A database that is
+/// corrupt is moved to quarantine and rebuilt from the rows that can still be read.
+pub fn open(dir: &Path) -> Result<(Connection, Option<StoreRecovery>), String> {
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Unable to create computed store directory: {e}"))?;
+    let path = dir.join(DB_FILE_NAME);
+
+    let checked = CHECKED_STORES.get_or_init(|| Mutex::new(HashSet::new()));
+    let needs_check = checked
+        .lock()
+        .map(|guard| !guard.contains(&path))
+        .unwrap_or(true);
+
+    let opened = match open_at(&path) {
+        Ok(conn) if !needs_check || passes_quick_check(&conn) => Ok(conn),
+        Ok(conn) => {
+            drop(conn);
+            Err(None)
+        }
+        Err(err) if is_corruption(&err) => Err(None),
+        Err(err) => Err(Some(format!(
+            "Unable to open computed store database: {err}"
+        ))),
+    };
+
+    let result = match opened {
+        Ok(conn) => (conn, None),
+        Err(Some(message)) => return Err(message),
+        Err(None) => {
+            let (conn, recovery) = recover(dir, &path)?;
+            (conn, Some(recovery))
+        }
+    };
+
+    if let Ok(mut guard) = checked.lock() {
+        guard.insert(path);
+    }
+    Ok(result)
+}
+
+pub fn meta_value(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT value FROM store_meta WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("Unable to read computed store metadata: {e}"))
+}
+
+pub fn set_meta_value(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO store_meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("Unable to write computed store metadata: {e}"))
+}
+
+/// Inserts or replaces `(match_key, entry)` rows for `account` inside an open transaction.
+pub fn write_rows(
+    tx: &Transaction,
+    account: &str,
+    entries: &[(String, Value)],
+    schema_version: i64,
+    updated_at_ms: i64,
+) -> Result<usize, String> {
+    let mut stmt = tx
+        .prepare_cached(
+            "INSERT INTO computed_matches (
+                account, match_key, schema_version, bracket_id, played_at,
+                map_name, owner_key, owner_spec, updated_at_ms, payload
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(account, match_key) DO UPDATE SET
+                schema_version = excluded.schema_version,
+                bracket_id = excluded.bracket_id,
+                played_at = excluded.played_at,
+                map_name = excluded.map_name,
+                owner_key = excluded.owner_key,
+                owner_spec = excluded.owner_spec,
+                updated_at_ms = excluded.updated_at_ms,
+                payload = excluded.payload",
+        )
+        .map_err(|e| format!("Unable to prepare computed store upsert: {e}"))?;
+
+    for (match_key, entry) in entries {
+        let columns = indexed_columns(entry);
+        let payload = serde_json::to_string(entry)
+            .map_err(|e| format!("Unable to serialize computed match: {e}"))?;
+        stmt.execute(params![
+            account,
+            match_key,
+            schema_version,
+            columns.bracket_id,
+            columns.played_at,
+            columns.map_name,
+            columns.owner_key,
+            columns.owner_spec,
+            updated_at_ms,
+            payload,
+        ])
+        .map_err(|e| format!("Unable to write computed match: {e}"))?;
+    }
+    Ok(entries.len())
+}
+
+/// Same as [`write_rows`] but in its own transaction.
+pub fn upsert_entries(
+    conn: &mut Connection,
+    account: &str,
+    entries: &[(String, Value)],
+    schema_version: i64,
+    updated_at_ms: i64,
+) -> Result<usize, String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Unable to begin computed store transaction: {e}"))?;
+    let written = write_rows(&tx, account, entries, schema_version, updated_at_ms)?;
+    tx.commit()
+        .map_err(|e| format!("Unable to commit computed store transaction: {e}"))?;
+    Ok(written)
+}
+
+/// Writes `entry` for `account` and deletes the row it was merged from, in one transaction.
+pub fn write_merged(
+    conn: &mut Connection,
+    account: &str,
+    entry: &(String, Value),
+    merged_from: (&str, &str),
+    schema_version: i64,
+    updated_at_ms: i64,
+) -> Result<(), String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Unable to begin computed store transaction: {e}"))?;
+    tx.execute(
+        "DELETE FROM computed_matches WHERE account = ?1 AND match_key = ?2",
+        params![merged_from.0, merged_from.1],
+    )
+    .map_err(|e| format!("Unable to delete merged computed match: {e}"))?;
+    write_rows(
+        &tx,
+        account,
+        std::slice::from_ref(entry),
+        schema_version,
+        updated_at_ms,
+    )?;
+    tx.commit()
+        .map_err(|e| format!("Unable to commit computed store transaction: {e}"))
+}
+
+pub struct StoredRow {
+    pub account: String,
+    pub match_key: String,
+    pub schema_version: i64,
+    pub payload: String,
+}
+
+/// Rows still stored under an older entry schema than `version`.
+pub fn rows_below_version(conn: &Connection, version: i64) -> Result<Vec<StoredRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT account, match_key, schema_version, payload FROM computed_matches
+             WHERE schema_version < ?1",
+        )
+        .map_err(|e| format!("Unable to query computed store: {e}"))?;
+    let rows = stmt
+        .query_map(params![version], |row| {
+            Ok(StoredRow {
+                account: row.get(0)?,
+                match_key: row.get(1)?,
+                schema_version: row.get(2)?,
+                payload: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Unable to query computed store: {e}"))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Unable to read computed match row: {e}"))
+}
+
+pub fn count_above_version(conn: &Connection, version: i64) -> Result<usize, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM computed_matches WHERE schema_version > ?1",
+        params![version],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count as usize)
+    .map_err(|e| format!("Unable to query computed store: {e}"))
+}
+
+/// Writes a consistent copy of the whole database to `path`.
+pub fn backup_to(conn: &Connection, path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Unable to create computed store backup directory: {e}"))?;
+    }
+    conn.execute("VACUUM INTO ?1", params![path.to_string_lossy()])
+        .map(|_| ())
+        .map_err(|e| format!("Unable to back up computed store: {e}"))
+}
+
+/// Writes only the rows of `account` to a fresh database at `path`, in the store's layout, so
+/// [`restore_account_from`] can read it back.
+pub fn backup_account_to(conn: &Connection, path: &Path, account: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Unable to create computed store backup directory: {e}"))?;
+    }
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(format!("Unable to replace computed store backup: {e}"));
+        }
+        _ => {}
+    }
+    conn.execute(
+        "ATTACH DATABASE ?1 AS snapshot",
+        params![path.to_string_lossy()],
+    )
+    .map_err(|e| format!("Unable to create computed store backup: {e}"))?;
+
+    let copied = conn
+        .execute_batch(
+            "CREATE TABLE snapshot.computed_matches AS
+             SELECT * FROM main.computed_matches WHERE 0",
+        )
+        .and_then(|_| {
+            conn.execute(
+                "INSERT INTO snapshot.computed_matches
+                 SELECT * FROM main.computed_matches WHERE account = ?1",
+                params![account],
+            )
+        })
+        .map(|_| ())
+        .map_err(|e| format!("Unable to back up computed matches: {e}"));
+
+    conn.execute("DETACH DATABASE snapshot", [])
+        .map_err(|e| format!("Unable to close computed store backup: {e}"))?;
+    copied
+}
+
+/// Deletes all but the newest `keep` files in `dir` named `<prefix><...>-<ms>.sqlite3` or
+/// `<prefix><...>.<ms>.sqlite3`, judged by that trailing millisecond timestamp.
+pub fn prune_backups(dir: &Path, prefix: &str, keep: usize) -> Result<(), String> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    let mut backups: Vec<(u64, PathBuf)> = read_dir
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stem = name.strip_prefix(prefix)?.strip_suffix(".sqlite3")?;
+            let at_ms = stem.rsplit(['-', '.']).next()?.parse::<u64>().ok()?;
+            Some((at_ms, entry.path()))
+        })
+        .collect();
+    backups.sort_by_key(|(at_ms, _)| std::cmp::Reverse(*at_ms));
+
+    for (_, path) in backups.into_iter().skip(keep) {
+        fs::remove_file(&path)
+            .map_err(|e| format!("Unable to remove old computed store backup: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Swaps every row of `account` for the ones in `snapshot`, a database written by
+/// [`backup_to`] or [`backup_account_to`]. Rows keep the schema version they were snapshotted at.
+pub fn restore_account_from(
+    conn: &mut Connection,
+    snapshot: &Path,
+    account: &str,
+) -> Result<usize, String> {
+    conn.execute(
+        "ATTACH DATABASE ?1 AS snapshot",
+        params![snapshot.to_string_lossy()],
+    )
+    .map_err(|e| format!("Unable to open computed store backup: {e}"))?;
+
+    let restored = (|| {
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Unable to begin computed store transaction: {e}"))?;
+        tx.execute(
+            "DELETE FROM computed_matches WHERE account = ?1",
+            params![account],
+        )
+        .map_err(|e| format!("Unable to clear computed matches: {e}"))?;
+        let restored = tx
+            .execute(
+                "INSERT INTO computed_matches (
+                    account, match_key, schema_version, bracket_id, played_at,
+                    map_name, owner_key, owner_spec, updated_at_ms, payload
+                 )
+                 SELECT account, match_key, schema_version, bracket_id, played_at,
+                    map_name, owner_key, owner_spec, updated_at_ms, payload
+                 FROM snapshot.computed_matches WHERE account = ?1",
+                params![account],
+            )
+            .map_err(|e| format!("Unable to restore computed matches: {e}"))?;
+        tx.commit()
+            .map_err(|e| format!("Unable to commit computed store transaction: {e}"))?;
+        Ok(restored)
+    })();
+
+    conn.execute("DETACH DATABASE snapshot", [])
+        .map_err(|e| format!("Unable to close computed store backup: {e}"))?;
+    restored
+}
+
+// `sql` selects `account, match_key, payload`.
+fn collect_rows(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> Result<LoadedEntries<(String, String, Value)>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Unable to query computed store: {e}"))?;
+    let rows = stmt
+        .query_map(params, |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| format!("Unable to query computed store: {e}"))?;
+
+    let mut out = LoadedEntries::default();
+    for row in rows {
+        let (account, match_key, payload) =
+            row.map_err(|e| format!("Unable to read computed match row: {e}"))?;
+        match serde_json::from_str::<Value>(&payload) {
+            Ok(entry) => out.entries.push((account, match_key, entry)),
+            Err(_) => out.unreadable.push(format!("{account}/{match_key}")),
+        }
+    }
+    Ok(out)
+}
+
+fn collect_payloads(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> Result<LoadedEntries<Value>, String> {
+    let rows = collect_rows(conn, sql, params)?;
+    Ok(LoadedEntries {
+        entries: rows
+            .entries
+            .into_iter()
+            .map(|(_, _, entry)| entry)
+            .collect(),
+        unreadable: rows.unreadable,
+    })
+}
+
+pub fn load_account(conn: &Connection, account: &str) -> Result<LoadedEntries<Value>, String> {
+    collect_payloads(
+        conn,
+        "SELECT account, match_key, payload FROM computed_matches WHERE account = ?1",
+        params![account],
+    )
+}
+
+pub fn load_all(conn: &Connection) -> Result<LoadedEntries<Value>, String> {
+    collect_payloads(
+        conn,
+        "SELECT account, match_key, payload FROM computed_matches",
+        [],
+    )
+}
+
+/// `(account, match_key, entry)` for every stored row.
+pub fn load_all_rows(conn: &Connection) -> Result<LoadedEntries<(String, String, Value)>, String> {
+    collect_rows(
+        conn,
+        "SELECT account, match_key, payload FROM computed_matches",
+        [],
+    )
+}
+
+/// The stored entry, `None` when there is no such row; an unreadable payload is an error.
+pub fn load_entry(
+    conn: &Connection,
+    account: &str,
+    match_key: &str,
+) -> Result<Option<Value>, String> {
+    let mut loaded = collect_payloads(
+        conn,
+        "SELECT account, match_key, payload FROM computed_matches
+         WHERE account = ?1 AND match_key = ?2",
+        params![account, match_key],
+    )?;
+    if !loaded.unreadable.is_empty() {
+        return Err(format!(
+            "Computed match {match_key} for {account} is unreadable"
+        ));
+    }
+    Ok(loaded.entries.pop())
+}
+
+/// Match keys stored for `account`.
+pub fn match_keys(conn: &Connection, account: &str) -> Result<HashSet<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT match_key FROM computed_matches WHERE account = ?1")
+        .map_err(|e| format!("Unable to query computed store: {e}"))?;
+    let rows = stmt
+        .query_map(params![account], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Unable to query computed store: {e}"))?;
+    rows.collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| format!("Unable to read computed match row: {e}"))
+}
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use xxhash_rust::xxh64::xxh64;
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_RETRY: Duration = Duration::from_millis(50);
+
+// Where the sidecars live, set once at startup; the temp dir until then.
+static LOCK_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+// Targets currently locked by this process; the condvar wakes waiters on release.
+static HELD: OnceLock<(Mutex<HashSet<PathBuf>>, Condvar)> = OnceLock::new();
+
+fn held() -> &'static (Mutex<HashSet<PathBuf>>, Condvar) {
+    HELD.get_or_init(|| (Mutex::new(HashSet::new()), Condvar::new()))
+}
+
+/// Exclusive lock on a target path, released on drop. Holds both the in-process entry and an
+/// OS advisory lock on the target's sidecar, so threads and other app instances serialize.
+pub struct FileLockGuard {
+    target: PathBuf,
+    file: Option<File>,
+}
+
+impl Drop for FileLockGuard {
+    fn drop(&mut self) {
+        if let Some(file) = self.file.take() {
+            let _ = file.unlock();
+        }
+        let (set, released) = held();
+        if let Ok(mut guard) = set.lock() {
+            guard.remove(&self.target);
+        }
+        released.notify_all();
+    }
+}
+
+/// Keeps every sidecar in `dir` (under app data) rather than next to its target, so locking
+/// a SavedVariables file leaves nothing behind in the WoW folder.
+pub fn set_lock_dir(dir: PathBuf) {
+    let _ = LOCK_DIR.set(dir);
+}
+
+// Sidecars are named after a hash of the target path, so targets never collide by file name.
+fn sidecar_path(target: &Path) -> PathBuf {
+    let dir = LOCK_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join("pvp-scalpel-locks"));
+    let hash = xxh64(target.to_string_lossy().as_bytes(), 0);
+    dir.join(format!("{hash:016x}.lock"))
+}
+
+fn acquire_in_process(target: &Path, deadline: Instant) -> Result<(), String> {
+    let (set, released) = held();
+    let mut guard = set
+        .lock()
+        .map_err(|_| "File lock registry poisoned".to_string())?;
+    while guard.contains(target) {
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(format!(
+                "Timed out waiting for lock on {}",
+                target.display()
+            ));
+        }
+        guard = released
+            .wait_timeout(guard, deadline - now)
+            .map_err(|_| "File lock registry poisoned".to_string())?
+            .0;
+    }
+    guard.insert(target.to_path_buf());
+    Ok(())
+}
+
+fn acquire_os_lock(target: &Path, deadline: Instant) -> Result<File, String> {
+    let sidecar = sidecar_path(target);
+    if let Some(parent) = sidecar.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Unable to create lock directory: {e}"))?;
+    }
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&sidecar)
+        .map_err(|e| format!("Unable to open lock file: {e}"))?;
+
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(file),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => thread::sleep(LOCK_RETRY),
+            Err(TryLockError::WouldBlock) => {
+                return Err(format!("{} is locked by another process", target.display()))
+            }
+            Err(TryLockError::Error(e)) => return Err(format!("Unable to lock file: {e}")),
+        }
+    }
+}
+
+/// Blocks (up to a timeout) until `target` can be locked exclusively.
+pub fn lock(target: &Path) -> Result<FileLockGuard, String> {
+    let deadline = Instant::now() + LOCK_TIMEOUT;
+    acquire_in_process(target, deadline)?;
+
+    // From here on the guard owns the in-process entry and releases it on any early return.
+    let mut guard = FileLockGuard {
+        target: target.to_path_buf(),
+        file: None,
+    };
+    guard.file = Some(acquire_os_lock(target, deadline)?);
+    Ok(guard)
+}
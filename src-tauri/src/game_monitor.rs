@@ -0,0 +1,281 @@
+use crate::gc_state_command;
+use crate::log_command;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Client process names without `.exe`; `world of warcraft` is the macOS app.
+const GAME_PROCESS_NAMES: [&str; 7] = [
+    "wow",
+    "wowt",
+    "wowb",
+    "wowclassic",
+    "wowclassict",
+    "wowclassicb",
+    "world of warcraft",
+];
+
+// Last poll of the monitor thread; `None` until it has run once.
+static GAME_STATE: OnceLock<Mutex<Option<GameState>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../src/Interfaces/GameFlavor.ts")]
+pub enum GameFlavor {
+    #[default]
+    Retail,
+    Classic,
+    ClassicEra,
+    Ptr,
+    Beta,
+}
+
+impl GameFlavor {
+    pub const ALL: [GameFlavor; 5] = [
+        GameFlavor::Retail,
+        GameFlavor::Classic,
+        GameFlavor::ClassicEra,
+        GameFlavor::Ptr,
+        GameFlavor::Beta,
+    ];
+
+    pub fn folder_name(self) -> &'static str {
+        match self {
+            GameFlavor::Retail => "_retail_",
+            GameFlavor::Classic => "_classic_",
+            GameFlavor::ClassicEra => "_classic_era_",
+            GameFlavor::Ptr => "_ptr_",
+            GameFlavor::Beta => "_beta_",
+        }
+    }
+
+    /// This flavor's part of an app data directory. Retail keeps the directory itself, so data
+    /// written before flavors were told apart stays where it is.
+    pub fn partition(self, root: PathBuf) -> PathBuf {
+        match self {
+            GameFlavor::Retail => root,
+            other => root.join(other.folder_name()),
+        }
+    }
+
+    /// Flavor of an install folder such as `_retail_` or `_classic_era_`.
+    pub fn from_folder(name: &str) -> Option<GameFlavor> {
+        match name.to_ascii_lowercase().as_str() {
+            "_retail_" => Some(GameFlavor::Retail),
+            "_classic_" => Some(GameFlavor::Classic),
+            "_classic_era_" => Some(GameFlavor::ClassicEra),
+            "_ptr_" | "_xptr_" | "_classic_ptr_" => Some(GameFlavor::Ptr),
+            "_beta_" | "_classic_beta_" => Some(GameFlavor::Beta),
+            _ => None,
+        }
+    }
+
+    // The install folder is the more precise signal (classic vs. era share an executable
+    // name), so the executable name is only the fallback.
+    fn from_executable(exe: Option<&Path>, process_name: &str) -> Option<GameFlavor> {
+        if let Some(flavor) = exe
+            .and_then(|exe| exe.parent())
+            .and_then(|dir| dir.file_name())
+            .and_then(|name| name.to_str())
+            .and_then(GameFlavor::from_folder)
+        {
+            return Some(flavor);
+        }
+
+        match process_name {
+            "wow" | "world of warcraft" => Some(GameFlavor::Retail),
+            "wowclassic" => Some(GameFlavor::Classic),
+            "wowt" | "wowclassict" => Some(GameFlavor::Ptr),
+            "wowb" | "wowclassicb" => Some(GameFlavor::Beta),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/GameProcess.ts")]
+pub struct GameProcess {
+    pub pid: u32,
+    pub exe_path: Option<String>,
+    pub flavor: Option<GameFlavor>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/GameState.ts")]
+pub struct GameState {
+    pub running: bool,
+    pub processes: Vec<GameProcess>,
+    /// When `running` last changed (or the monitor first polled), in ms since the epoch;
+    /// `null` for a one-off scan made before the monitor started.
+    #[ts(type = "number | null")]
+    pub changed_at_ms: Option<u64>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn process_name(raw: &str) -> String {
+    let name = raw.to_ascii_lowercase();
+    name.strip_suffix(".exe").unwrap_or(&name).to_string()
+}
+
+/// Refreshes the process list only (names always, executable paths once per process) and
+/// returns the running game clients.
+fn scan(system: &mut System) -> Vec<GameProcess> {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+    );
+
+    let mut processes: Vec<GameProcess> = system
+        .processes()
+        .values()
+        .filter_map(|process| {
+            let name = process_name(&process.name().to_string_lossy());
+            if !GAME_PROCESS_NAMES.contains(&name.as_str()) {
+                return None;
+            }
+            Some(GameProcess {
+                pid: process.pid().as_u32(),
+                exe_path: process.exe().map(|exe| exe.to_string_lossy().to_string()),
+                flavor: GameFlavor::from_executable(process.exe(), &name),
+            })
+        })
+        .collect();
+    processes.sort_by_key(|process| process.pid);
+    processes
+}
+
+fn emit_changes(app: &AppHandle, previous: &[GameProcess], current: &[GameProcess]) {
+    for process in current
+        .iter()
+        .filter(|p| !previous.iter().any(|o| o.pid == p.pid))
+    {
+        log_command::emit_log(app, "Game started");
+        let _ = app.emit("game-started", process.clone());
+    }
+    for process in previous
+        .iter()
+        .filter(|p| !current.iter().any(|n| n.pid == p.pid))
+    {
+        log_command::emit_log(app, "Game stopped");
+        let _ = app.emit("game-stopped", process.clone());
+    }
+}
+
+// Same rule as `is_flavor_running`, against one poll's processes.
+fn runs_flavor(processes: &[GameProcess], flavor: Option<GameFlavor>) -> bool {
+    processes
+        .iter()
+        .any(|process| flavor.is_none() || process.flavor.is_none() || process.flavor == flavor)
+}
+
+// Flavors (and `None`, for installs outside a flavor folder) that ran before this poll and no
+// longer do. On the first poll every flavor not running counts, so writes queued by an earlier
+// session are applied at startup.
+fn stopped_flavors(
+    previous: Option<&[GameProcess]>,
+    current: &[GameProcess],
+) -> Vec<Option<GameFlavor>> {
+    GameFlavor::ALL
+        .into_iter()
+        .map(Some)
+        .chain([None])
+        .filter(|flavor| {
+            previous.is_none_or(|previous| runs_flavor(previous, *flavor))
+                && !runs_flavor(current, *flavor)
+        })
+        .collect()
+}
+
+fn run_monitor(app: AppHandle, state: &Mutex<Option<GameState>>) {
+    let mut system = System::new();
+    loop {
+        let processes = scan(&mut system);
+        let mut stopped = Vec::new();
+        if let Ok(mut guard) = state.lock() {
+            match guard.as_mut() {
+                Some(current) if current.processes == processes => {}
+                Some(current) => {
+                    emit_changes(&app, &current.processes, &processes);
+                    stopped = stopped_flavors(Some(&current.processes), &processes);
+                    let running = !processes.is_empty();
+                    if running != current.running {
+                        current.running = running;
+                        current.changed_at_ms = Some(now_ms());
+                    }
+                    current.processes = processes;
+                }
+                None => {
+                    emit_changes(&app, &[], &processes);
+                    stopped = stopped_flavors(None, &processes);
+                    *guard = Some(GameState {
+                        running: !processes.is_empty(),
+                        processes,
+                        changed_at_ms: Some(now_ms()),
+                    });
+                }
+            }
+        }
+        // The client has written SavedVariables on its way out; queued edits are safe now.
+        for flavor in stopped {
+            gc_state_command::flush_pending_writes(&app, flavor);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Starts the monitor thread once; later calls do nothing.
+pub fn start(app: AppHandle) {
+    let mut started = false;
+    let state = GAME_STATE.get_or_init(|| {
+        started = true;
+        Mutex::new(None)
+    });
+    if started {
+        thread::spawn(move || run_monitor(app, state));
+    }
+}
+
+/// The monitor's last poll, or a one-off scan when it has not polled yet.
+fn current_state() -> GameState {
+    if let Some(state) = GAME_STATE
+        .get()
+        .and_then(|state| state.lock().ok())
+        .and_then(|guard| guard.clone())
+    {
+        return state;
+    }
+
+    let processes = scan(&mut System::new());
+    GameState {
+        running: !processes.is_empty(),
+        processes,
+        changed_at_ms: None,
+    }
+}
+
+/// Whether a client of `flavor` is running. Clients whose flavor could not be told count for
+/// every flavor, and `None` asks about any client.
+pub fn is_flavor_running(flavor: Option<GameFlavor>) -> bool {
+    runs_flavor(&current_state().processes, flavor)
+}
+
+#[tauri::command]
+pub fn get_game_state() -> GameState {
+    current_state()
+}
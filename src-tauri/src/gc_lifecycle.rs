@@ -0,0 +1,237 @@
+use crate::lua_parser::{LuaKey, LuaValue};
+use serde::{Deserialize, Serialize};
+use serde_json::Number;
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+pub const GC_ROOT: &str = "PvP_Scalpel_GC";
+
+// Reserved GC field holding per-key timestamps and retry counts. It lives inside the GC table
+// because the client only writes back the SavedVariables the addon declares, so a separate root
+// would be dropped on the next logout. Its value is a table, so readers that only look at string
+// states (the addon, `toPendingMatchKeys`) skip it.
+pub const LIFECYCLE_KEY: &str = "__lifecycle";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../../src/Interfaces/GcState.ts")]
+pub enum GcState {
+    Pending,
+    Computed,
+    Uploaded,
+    Synced,
+    Failed,
+    Expired,
+}
+
+impl GcState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GcState::Pending => "pending",
+            GcState::Computed => "computed",
+            GcState::Uploaded => "uploaded",
+            GcState::Synced => "synced",
+            GcState::Failed => "failed",
+            GcState::Expired => "expired",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<GcState> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "pending" => Some(GcState::Pending),
+            "computed" => Some(GcState::Computed),
+            "uploaded" => Some(GcState::Uploaded),
+            "synced" => Some(GcState::Synced),
+            "failed" => Some(GcState::Failed),
+            "expired" => Some(GcState::Expired),
+            _ => None,
+        }
+    }
+
+    /// States reachable in one step. `Failed -> Pending` is a retry, `Failed -> Synced` a later
+    /// sync that went through anyway; `Expired` is terminal.
+    fn next_states(self) -> &'static [GcState] {
+        match self {
+            GcState::Pending => &[
+                GcState::Computed,
+                GcState::Synced,
+                GcState::Failed,
+                GcState::Expired,
+            ],
+            GcState::Computed => &[
+                GcState::Uploaded,
+                GcState::Synced,
+                GcState::Failed,
+                GcState::Expired,
+            ],
+            GcState::Uploaded => &[GcState::Synced, GcState::Failed, GcState::Expired],
+            GcState::Synced => &[GcState::Expired],
+            GcState::Failed => &[GcState::Pending, GcState::Synced, GcState::Expired],
+            GcState::Expired => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/GcEntry.ts")]
+pub struct GcEntry {
+    pub key: String,
+    /// `None` when the stored value is not a known state; `raw` then holds what is there.
+    pub state: Option<GcState>,
+    pub raw: String,
+    pub retries: u32,
+    #[ts(type = "number | null")]
+    pub updated_at: Option<i64>,
+    /// Unix seconds at which the key last entered each state.
+    #[ts(type = "Partial<Record<GcState, number>>")]
+    pub entered_at: BTreeMap<GcState, i64>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/GcRejectedTransition.ts")]
+pub struct GcRejectedTransition {
+    pub key: String,
+    /// Current stored value, `null` when the key is not in the table.
+    pub from: Option<String>,
+    pub to: GcState,
+}
+
+fn number_of(value: Option<&LuaValue>) -> Option<i64> {
+    match value {
+        Some(LuaValue::Number(number)) => number
+            .as_i64()
+            .or_else(|| number.as_f64().map(|v| v as i64)),
+        _ => None,
+    }
+}
+
+fn raw_state(table: &LuaValue, key: &str) -> Option<String> {
+    match table.field(key) {
+        Some(LuaValue::String(raw)) => Some(raw.clone()),
+        _ => None,
+    }
+}
+
+fn entry_of(table: &LuaValue, key: &str, raw: String) -> GcEntry {
+    let meta = table.field(LIFECYCLE_KEY).and_then(|meta| meta.field(key));
+    let entered_at = match meta.and_then(|meta| meta.field("enteredAt")) {
+        Some(LuaValue::Table(fields)) => fields
+            .iter()
+            .filter_map(|(state, at)| match state {
+                LuaKey::Name(state) => Some((GcState::parse(state)?, number_of(Some(at))?)),
+                _ => None,
+            })
+            .collect(),
+        _ => BTreeMap::new(),
+    };
+
+    GcEntry {
+        key: key.to_string(),
+        state: GcState::parse(&raw),
+        raw,
+        retries: number_of(meta.and_then(|meta| meta.field("retries")))
+            .map(|v| v.max(0) as u32)
+            .unwrap_or(0),
+        updated_at: number_of(meta.and_then(|meta| meta.field("updatedAt"))),
+        entered_at,
+    }
+}
+
+/// Every string-valued key of the GC table with its bookkeeping.
+pub fn read_entries(table: &LuaValue) -> Vec<GcEntry> {
+    let LuaValue::Table(fields) = table else {
+        return Vec::new();
+    };
+    fields
+        .iter()
+        .filter_map(|(key, value)| match (key, value) {
+            (LuaKey::Name(key), LuaValue::String(raw)) => Some(entry_of(table, key, raw.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether `key` may move from its current value to `to`. Missing keys may start as pending, or
+/// as synced for matches stored without the addon tracking them; re-entering the current state
+/// is allowed and changes nothing.
+pub fn check_transition(
+    table: &LuaValue,
+    key: &str,
+    to: GcState,
+) -> Result<bool, GcRejectedTransition> {
+    let raw = raw_state(table, key);
+    let allowed = match raw.as_deref().map(GcState::parse) {
+        None => matches!(to, GcState::Pending | GcState::Synced),
+        Some(Some(from)) if from == to => return Ok(false),
+        Some(Some(from)) => from.next_states().contains(&to),
+        Some(None) => false,
+    };
+    if allowed {
+        Ok(true)
+    } else {
+        Err(GcRejectedTransition {
+            key: key.to_string(),
+            from: raw,
+            to,
+        })
+    }
+}
+
+// The sub-table `name`, replacing whatever non-table value was there.
+fn table_field<'a>(table: &'a mut LuaValue, name: &str) -> Option<&'a mut LuaValue> {
+    if !matches!(table.field(name), Some(LuaValue::Table(_))) {
+        table.set_field(LuaKey::Name(name.to_string()), LuaValue::Table(Vec::new()));
+    }
+    table.field_mut(name)
+}
+
+/// Moves `key` to `to`, recording the time it entered the state and counting retries.
+/// Returns `Ok(false)` when the key already was in `to`.
+pub fn apply_transition(
+    table: &mut LuaValue,
+    key: &str,
+    to: GcState,
+    now_s: i64,
+) -> Result<bool, GcRejectedTransition> {
+    if !check_transition(table, key, to)? {
+        return Ok(false);
+    }
+    let retry = raw_state(table, key).and_then(|raw| GcState::parse(&raw)) == Some(GcState::Failed)
+        && to == GcState::Pending;
+
+    table.set_field(
+        LuaKey::Name(key.to_string()),
+        LuaValue::String(to.as_str().to_string()),
+    );
+
+    let Some(meta) = table_field(table, LIFECYCLE_KEY).and_then(|meta| table_field(meta, key))
+    else {
+        return Ok(true);
+    };
+    if retry {
+        let retries = number_of(meta.field("retries")).unwrap_or(0) + 1;
+        meta.set_field(
+            LuaKey::Name("retries".to_string()),
+            LuaValue::Number(Number::from(retries)),
+        );
+    } else if meta.field("retries").is_none() {
+        meta.set_field(
+            LuaKey::Name("retries".to_string()),
+            LuaValue::Number(Number::from(0)),
+        );
+    }
+    meta.set_field(
+        LuaKey::Name("updatedAt".to_string()),
+        LuaValue::Number(Number::from(now_s)),
+    );
+    if let Some(entered_at) = table_field(meta, "enteredAt") {
+        entered_at.set_field(
+            LuaKey::Name(to.as_str().to_string()),
+            LuaValue::Number(Number::from(now_s)),
+        );
+    }
+
+    Ok(true)
+}
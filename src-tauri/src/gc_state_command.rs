@@ -1,184 +1,423 @@
-use std::collections::BTreeSet;
+use crate::file_lock;
+use crate::fs_atomic;
+use crate::game_monitor::GameFlavor;
+use crate::gc_lifecycle::{self, GcEntry, GcRejectedTransition, GcState, GC_ROOT};
+use crate::log_command;
+use crate::lua_parser::{self, LuaValue};
+use crate::lua_writer;
+use crate::watcher;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
 
-fn find_gc_table_range(content: &str) -> Option<(usize, usize)> {
-    let start_idx = content.find("PvP_Scalpel_GC")?;
-    let bytes = content.as_bytes();
-    let mut eq_idx = start_idx;
-    while eq_idx < bytes.len() && bytes[eq_idx] != b'=' {
-        eq_idx += 1;
+const PENDING_WRITES_FILE: &str = "pending_savedvars_writes.json";
+
+// GC transitions waiting for the game to close, per SavedVariables path, in request order.
+// The client rewrites the file on logout, so anything written while it runs would be lost.
+// Mirrored to app data so a restart before the game closes keeps them.
+static PENDING_WRITES: OnceLock<Mutex<BTreeMap<String, PendingWrite>>> = OnceLock::new();
+
+struct PendingWrite {
+    transitions: Vec<PendingGcTransition>,
+    queued_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/PendingGcTransition.ts")]
+pub struct PendingGcTransition {
+    pub key: String,
+    pub to: GcState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/PendingSavedVarsWrite.ts")]
+pub struct PendingSavedVarsWrite {
+    pub path: String,
+    pub transitions: Vec<PendingGcTransition>,
+    #[ts(type = "number")]
+    pub queued_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/GcTransitionReport.ts")]
+pub struct GcTransitionReport {
+    pub changed: usize,
+    /// Keys that already were in the target state.
+    pub unchanged: usize,
+    /// True when the game is running and the write waits for it to close.
+    pub queued: bool,
+    /// Keys left as they were because the transition is illegal. Only
+    /// `mark_gc_matches_synced` skips keys; the bulk command rejects the whole batch.
+    pub skipped: Vec<GcRejectedTransition>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/GcStateError.ts")]
+pub enum GcStateError {
+    Io {
+        message: String,
+    },
+    Validation {
+        message: String,
+        rejected: Vec<GcRejectedTransition>,
+    },
+}
+
+impl From<String> for GcStateError {
+    fn from(message: String) -> Self {
+        GcStateError::Io { message }
     }
-    if eq_idx >= bytes.len() {
-        return None;
+}
+
+impl fmt::Display for GcStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GcStateError::Io { message } | GcStateError::Validation { message, .. } => {
+                f.write_str(message)
+            }
+        }
     }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
 
-    let mut i = eq_idx + 1;
-    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
-        i += 1;
+fn pending_writes_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Unable to resolve app data directory: {e}"))?;
+    Ok(dir.join(PENDING_WRITES_FILE))
+}
+
+fn load_pending_writes(app: &AppHandle) -> BTreeMap<String, PendingWrite> {
+    let stored: Vec<PendingSavedVarsWrite> = pending_writes_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    stored
+        .into_iter()
+        .map(|write| {
+            (
+                write.path,
+                PendingWrite {
+                    transitions: write.transitions,
+                    queued_at_ms: write.queued_at_ms,
+                },
+            )
+        })
+        .collect()
+}
+
+fn to_reported(pending: &BTreeMap<String, PendingWrite>) -> Vec<PendingSavedVarsWrite> {
+    pending
+        .iter()
+        .map(|(path, write)| PendingSavedVarsWrite {
+            path: path.clone(),
+            transitions: write.transitions.clone(),
+            queued_at_ms: write.queued_at_ms,
+        })
+        .collect()
+}
+
+fn save_pending_writes(
+    app: &AppHandle,
+    pending: &BTreeMap<String, PendingWrite>,
+) -> Result<(), String> {
+    let path = pending_writes_path(app)?;
+    if pending.is_empty() {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!(
+                "Unable to clear pending SavedVariables writes: {e}"
+            )),
+            _ => Ok(()),
+        };
     }
-    if i >= bytes.len() || bytes[i] != b'{' {
-        return None;
+
+    let bytes = serde_json::to_vec(&to_reported(pending))
+        .map_err(|e| format!("Unable to serialize pending SavedVariables writes: {e}"))?;
+    fs_atomic::write_atomic(&path, &bytes)
+        .map_err(|e| format!("Unable to store pending SavedVariables writes: {e}"))
+}
+
+fn pending_writes(app: &AppHandle) -> &'static Mutex<BTreeMap<String, PendingWrite>> {
+    PENDING_WRITES.get_or_init(|| Mutex::new(load_pending_writes(app)))
+}
+
+fn queued_transitions(app: &AppHandle, path: &str) -> Result<Vec<PendingGcTransition>, String> {
+    pending_writes(app)
+        .lock()
+        .map(|guard| {
+            guard
+                .get(path)
+                .map(|write| write.transitions.clone())
+                .unwrap_or_default()
+        })
+        .map_err(|_| "Pending SavedVariables writes poisoned".to_string())
+}
+
+fn queue_write(
+    app: &AppHandle,
+    path: &str,
+    transitions: Vec<PendingGcTransition>,
+    queued_at_ms: u64,
+) -> Result<(), String> {
+    let mut guard = pending_writes(app)
+        .lock()
+        .map_err(|_| "Pending SavedVariables writes poisoned".to_string())?;
+    let entry = guard
+        .entry(path.to_string())
+        .or_insert_with(|| PendingWrite {
+            transitions: Vec::new(),
+            queued_at_ms,
+        });
+    entry.transitions.extend(transitions);
+    entry.queued_at_ms = entry.queued_at_ms.min(queued_at_ms);
+    save_pending_writes(app, &guard)
+}
+
+fn take_pending(app: &AppHandle, path: &str) -> Result<Option<PendingWrite>, String> {
+    let mut guard = pending_writes(app)
+        .lock()
+        .map_err(|_| "Pending SavedVariables writes poisoned".to_string())?;
+    let taken = guard.remove(path);
+    if taken.is_some() {
+        save_pending_writes(app, &guard)?;
     }
+    Ok(taken)
+}
 
-    let table_start = i;
-    let mut depth: i32 = 0;
-    let mut in_string = false;
-    let mut quote = b'"';
-    let mut escaped = false;
-    let mut in_comment = false;
-
-    let mut j = i;
-    while j < bytes.len() {
-        let ch = bytes[j];
-        let next = if j + 1 < bytes.len() { bytes[j + 1] } else { 0 };
-
-        if in_comment {
-            if ch == b'\n' {
-                in_comment = false;
-            }
-            j += 1;
-            continue;
-        }
+fn read_gc_table(content: &str) -> Result<LuaValue, GcStateError> {
+    lua_parser::parse_roots(content, &[GC_ROOT])
+        .map_err(|e| format!("Failed parsing SavedVariables: {e}"))?
+        .pop()
+        .map(|root| root.value)
+        .filter(|value| matches!(value, LuaValue::Table(_)))
+        .ok_or_else(|| "Failed locating PvP_Scalpel_GC table".to_string().into())
+}
 
-        if in_string {
-            if escaped {
-                escaped = false;
-                j += 1;
-                continue;
-            }
-            if ch == b'\\' {
-                escaped = true;
-                j += 1;
-                continue;
-            }
-            if ch == quote {
-                in_string = false;
-            }
-            j += 1;
-            continue;
+// Applies the transitions in order. Any illegal one rejects the whole batch, unless
+// `skip_illegal` is set; those keys are then left alone and returned.
+fn apply_transitions(
+    table: &mut LuaValue,
+    transitions: &[PendingGcTransition],
+    skip_illegal: bool,
+) -> Result<(usize, Vec<GcRejectedTransition>), GcStateError> {
+    let now_s = (now_ms() / 1000) as i64;
+    let mut changed = 0;
+    let mut rejected = Vec::new();
+    for transition in transitions {
+        match gc_lifecycle::apply_transition(table, &transition.key, transition.to, now_s) {
+            Ok(true) => changed += 1,
+            Ok(false) => {}
+            Err(rejection) => rejected.push(rejection),
         }
+    }
 
-        if ch == b'-' && next == b'-' {
-            in_comment = true;
-            j += 2;
-            continue;
-        }
+    if rejected.is_empty() || skip_illegal {
+        return Ok((changed, rejected));
+    }
+    let message = rejected
+        .iter()
+        .map(|r| {
+            format!(
+                "{}: {} -> {}",
+                r.key,
+                r.from.as_deref().unwrap_or("missing"),
+                r.to.as_str()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(GcStateError::Validation {
+        message: format!("Illegal GC transition ({message})"),
+        rejected,
+    })
+}
 
-        if ch == b'"' || ch == b'\'' {
-            in_string = true;
-            quote = ch;
-            j += 1;
-            continue;
-        }
+// Runs the transitions against the file; only writes when `commit` is set and something changed.
+fn run_transitions(
+    path: &str,
+    transitions: &[PendingGcTransition],
+    commit: bool,
+    skip_illegal: bool,
+) -> Result<(usize, Vec<GcRejectedTransition>), GcStateError> {
+    let _lock = file_lock::lock(Path::new(path))?;
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed reading SavedVariables: {e}"))?;
+    let mut table = read_gc_table(&content)?;
+    let (changed, skipped) = apply_transitions(&mut table, transitions, skip_illegal)?;
+    if !commit || changed == 0 {
+        return Ok((changed, skipped));
+    }
 
-        if ch == b'{' {
-            depth += 1;
-        } else if ch == b'}' {
-            depth -= 1;
-            if depth == 0 {
-                return Some((table_start, j + 1));
-            }
-        }
+    let updated = lua_writer::replace_roots(&content, &[(GC_ROOT, table)])?;
+    fs_atomic::write_atomic(Path::new(path), updated.as_bytes())
+        .map_err(|e| format!("Failed writing SavedVariables: {e}"))?;
+    Ok((changed, skipped))
+}
 
-        j += 1;
+// Illegal queued transitions are dropped since they will not become legal; I/O failures stay
+// queued for the next close.
+fn apply_queued(app: &AppHandle, path: &str, write: PendingWrite) -> usize {
+    match run_transitions(path, &write.transitions, true, false) {
+        Ok((changed, _)) => changed,
+        Err(e @ GcStateError::Validation { .. }) => {
+            log_command::emit_log(app, &format!("Queued GC write dropped: {e}"));
+            0
+        }
+        Err(e) => {
+            log_command::emit_log(app, &format!("Queued GC write failed: {e}"));
+            let _ = queue_write(app, path, write.transitions, write.queued_at_ms);
+            0
+        }
     }
+}
 
-    None
-}
-
-fn replace_gc_value(table: &mut String, key: &str, value: &str) {
-    let variants = [format!("[\"{}\"]", key), format!("['{}']", key)];
-    for marker in variants {
-        if let Some(pos) = table.find(&marker) {
-            let after = pos + marker.len();
-            if let Some(eq_rel) = table[after..].find('=') {
-                let eq_pos = after + eq_rel;
-                let mut val_start = eq_pos + 1;
-                let bytes = table.as_bytes();
-                while val_start < bytes.len() && bytes[val_start].is_ascii_whitespace() {
-                    val_start += 1;
-                }
-                if val_start >= bytes.len() {
-                    break;
-                }
-
-                let quoted = bytes[val_start] == b'"' || bytes[val_start] == b'\'';
-                if quoted {
-                    let quote = bytes[val_start];
-                    let mut k = val_start + 1;
-                    let mut escaped = false;
-                    while k < bytes.len() {
-                        let ch = bytes[k];
-                        if escaped {
-                            escaped = false;
-                            k += 1;
-                            continue;
-                        }
-                        if ch == b'\\' {
-                            escaped = true;
-                            k += 1;
-                            continue;
-                        }
-                        if ch == quote {
-                            let replacement = format!("\"{}\"", value);
-                            table.replace_range(val_start..=k, &replacement);
-                            return;
-                        }
-                        k += 1;
-                    }
-                } else {
-                    let mut k = val_start;
-                    while k < bytes.len()
-                        && bytes[k] != b','
-                        && bytes[k] != b'\n'
-                        && bytes[k] != b'\r'
-                        && bytes[k] != b'}'
-                    {
-                        k += 1;
-                    }
-                    let replacement = format!("\"{}\"", value);
-                    table.replace_range(val_start..k, &replacement);
-                    return;
-                }
+/// Applies the queued writes of one flavor's installs. Called by the game monitor once that
+/// client has exited, and again by the watcher when its logout write settles.
+pub fn flush_pending_writes(app: &AppHandle, flavor: Option<GameFlavor>) {
+    let pending: Vec<(String, PendingWrite)> = match pending_writes(app).lock() {
+        Ok(mut guard) => {
+            let paths: Vec<String> = guard
+                .keys()
+                .filter(|path| watcher::extract_flavor(Path::new(path)) == flavor)
+                .cloned()
+                .collect();
+            let taken: Vec<(String, PendingWrite)> = paths
+                .into_iter()
+                .filter_map(|path| guard.remove(&path).map(|write| (path, write)))
+                .collect();
+            if !taken.is_empty() {
+                let _ = save_pending_writes(app, &guard);
             }
+            taken
         }
+        Err(_) => return,
+    };
+    if pending.is_empty() {
+        return;
     }
 
-    if let Some(end) = table.rfind('}') {
-        let line = format!("  [\"{}\"] = \"{}\",\n", key, value);
-        table.insert_str(end, &line);
+    let applied: usize = pending
+        .into_iter()
+        .map(|(path, write)| apply_queued(app, &path, write))
+        .sum();
+    if applied > 0 {
+        log_command::emit_log(app, &format!("Queued GC states applied ({applied})"));
     }
 }
 
-#[tauri::command]
-pub fn mark_gc_matches_synced(path: String, keys: Vec<String>) -> Result<usize, String> {
-    if keys.is_empty() {
-        return Ok(0);
-    }
-
-    let mut unique = BTreeSet::new();
-    keys.into_iter()
+fn transition_keys(
+    app: &AppHandle,
+    path: &str,
+    keys: Vec<String>,
+    to: GcState,
+    skip_illegal: bool,
+) -> Result<GcTransitionReport, GcStateError> {
+    let unique: BTreeSet<String> = keys
+        .into_iter()
         .map(|v| v.trim().to_string())
         .filter(|v| !v.is_empty())
-        .for_each(|v| {
-            unique.insert(v);
+        .collect();
+    let mut requested: Vec<PendingGcTransition> = unique
+        .into_iter()
+        .map(|key| PendingGcTransition { key, to })
+        .collect();
+    if requested.is_empty() {
+        return Ok(GcTransitionReport {
+            changed: 0,
+            unchanged: 0,
+            queued: false,
+            skipped: Vec::new(),
         });
-    if unique.is_empty() {
-        return Ok(0);
     }
 
-    let mut content =
+    if watcher::is_wow_running(Path::new(path)) {
+        // Validate against the file as it will look once the earlier queued writes land.
+        let mut planned = queued_transitions(app, path)?;
+        let (before, _) = run_transitions(path, &planned, false, false)?;
+        planned.extend(requested.iter().cloned());
+        let (after, skipped) = run_transitions(path, &planned, false, skip_illegal)?;
+        let changed = after - before;
+
+        requested.retain(|transition| !skipped.iter().any(|r| r.key == transition.key));
+        if !requested.is_empty() {
+            queue_write(app, path, requested.clone(), now_ms())?;
+            log_command::emit_log(app, "GC write queued until WoW closes");
+        }
+        return Ok(GcTransitionReport {
+            changed,
+            unchanged: requested.len() - changed,
+            queued: !requested.is_empty(),
+            skipped,
+        });
+    }
+
+    if let Some(write) = take_pending(app, path)? {
+        apply_queued(app, path, write);
+    }
+    let (changed, skipped) = run_transitions(path, &requested, true, skip_illegal)?;
+    Ok(GcTransitionReport {
+        changed,
+        unchanged: requested.len() - skipped.len() - changed,
+        queued: false,
+        skipped,
+    })
+}
+
+/// Current state and bookkeeping of every key in the file's GC table.
+#[tauri::command]
+pub fn get_gc_states(path: String) -> Result<Vec<GcEntry>, GcStateError> {
+    let content =
         fs::read_to_string(&path).map_err(|e| format!("Failed reading SavedVariables: {e}"))?;
-    let (start, end) = find_gc_table_range(&content)
-        .ok_or_else(|| "Failed locating PvP_Scalpel_GC table".to_string())?;
+    Ok(gc_lifecycle::read_entries(&read_gc_table(&content)?))
+}
 
-    let mut table = content[start..end].to_string();
-    unique
-        .iter()
-        .for_each(|key| replace_gc_value(&mut table, key, "synced"));
+/// Moves `keys` to `to` in one write; an illegal transition for any key rejects the batch.
+#[tauri::command]
+pub fn transition_gc_states(
+    app: AppHandle,
+    path: String,
+    keys: Vec<String>,
+    to: GcState,
+    skip_illegal: bool,
+) -> Result<GcTransitionReport, GcStateError> {
+    transition_keys(&app, &path, keys, to, false)
+}
 
-    content.replace_range(start..end, &table);
-    fs::write(&path, content).map_err(|e| format!("Failed writing SavedVariables: {e}"))?;
+/// Marks `keys` synced. Keys that cannot move to synced (unknown or expired states) are skipped
+/// and reported rather than failing the rest; `queued` tells a write deferred until the game
+/// closes from one that landed.
+#[tauri::command]
+pub fn mark_gc_matches_synced(
+    app: AppHandle,
+    path: String,
+    keys: Vec<String>,
+) -> Result<GcTransitionReport, String> {
+    transition_keys(&app, &path, keys, GcState::Synced, true).map_err(|e| e.to_string())
+}
 
-    Ok(unique.len())
+/// SavedVariables writes waiting for the game to close.
+#[tauri::command]
+pub fn get_pending_savedvars_writes(app: AppHandle) -> Result<Vec<PendingSavedVarsWrite>, String> {
+    let guard = pending_writes(&app)
+        .lock()
+        .map_err(|_| "Pending SavedVariables writes poisoned".to_string())?;
+    Ok(to_reported(&guard))
 }
@@ -1,26 +1,437 @@
-use registry::{Hive, Security, Data};
+use crate::app_config;
+use crate::game_monitor::GameFlavor;
+use crate::log_command;
+use crate::watcher;
+use crate::watcher_service;
+#[cfg(windows)]
+use registry::{Data, Hive, Security};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::AppHandle;
+use ts_rs::TS;
 
-#[tauri::command]
-pub fn get_wow_path() -> Option<String> {
-    let main_key = r"SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall\Battle.net\Game\wow";
+#[cfg(windows)]
+const REGISTRY_KEYS: [(&str, &str); 2] = [
+    (
+        r"SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall\Battle.net\Game\wow",
+        "InstallLocation",
+    ),
+    (
+        r"SOFTWARE\WOW6432Node\Blizzard Entertainment\World of Warcraft",
+        "InstallPath",
+    ),
+];
+
+// Where Battle.net puts the game inside a Wine prefix.
+#[cfg(not(windows))]
+const PREFIX_INSTALL_DIRS: [&str; 2] = [
+    "drive_c/Program Files (x86)/World of Warcraft",
+    "drive_c/Program Files/World of Warcraft",
+];
+
+// Folder chosen with `set_wow_path`, loaded from the app config at startup.
+static WOW_PATH_OVERRIDE: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../src/Interfaces/WowInstallSource.ts")]
+pub enum WowInstallSource {
+    #[cfg_attr(not(windows), allow(dead_code))]
+    Registry,
+    #[cfg_attr(windows, allow(dead_code))]
+    WinePrefix,
+    #[cfg_attr(windows, allow(dead_code))]
+    Lutris,
+    #[cfg_attr(windows, allow(dead_code))]
+    Bottles,
+    #[cfg_attr(windows, allow(dead_code))]
+    SteamProton,
+    /// Folder chosen by the user.
+    Manual,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/WowInstallCandidate.ts")]
+pub struct WowInstallCandidate {
+    /// `<install>/<flavor>/WTF/Account`, the folder the watcher scans.
+    pub account_path: String,
+    pub flavor: Option<GameFlavor>,
+    pub source: WowInstallSource,
+    /// What pointed at it: registry key, Wine prefix, Lutris/Bottles config, compatdata folder or
+    /// the folder the user chose.
+    pub origin: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/WowPathSettings.ts")]
+pub struct WowPathSettings {
+    /// Folder set with `set_wow_path`; `null` when the installs are detected.
+    pub override_path: Option<String>,
+    /// Installs being watched.
+    pub installs: Vec<WowInstallCandidate>,
+    /// What detection finds, whether or not an override is set.
+    pub detected: Vec<WowInstallCandidate>,
+}
+
+fn candidate(flavor_dir: &Path, source: WowInstallSource, origin: String) -> WowInstallCandidate {
+    WowInstallCandidate {
+        account_path: flavor_dir
+            .join("WTF")
+            .join("Account")
+            .to_string_lossy()
+            .to_string(),
+        flavor: flavor_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(GameFlavor::from_folder),
+        source,
+        origin,
+    }
+}
+
+fn is_flavor_folder(dir: &Path) -> bool {
+    dir.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.len() > 2 && name.starts_with('_') && name.ends_with('_'))
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = read_dir
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
 
-    if let Ok(key) = Hive::LocalMachine.open(main_key, Security::Read) {
-        if let Ok(Data::String(path)) = key.value("InstallLocation") {
-            let wow = path.to_string_lossy();
-            let result = format!("{}\\WTF\\Account", wow.trim_end_matches(['\\','/']));
-            return Some(result);
+// Every `_<flavor>_` folder of a WoW install that has an account folder.
+fn flavors_in_install(
+    install: &Path,
+    source: WowInstallSource,
+    origin: &str,
+    out: &mut Vec<WowInstallCandidate>,
+) {
+    for flavor_dir in subdirs(install) {
+        if is_flavor_folder(&flavor_dir) && flavor_dir.join("WTF").join("Account").is_dir() {
+            out.push(candidate(&flavor_dir, source, origin.to_string()));
         }
     }
+}
 
-    let fallback = r"SOFTWARE\WOW6432Node\Blizzard Entertainment\World of Warcraft";
+// The registry points at the retail folder; its siblings are the other flavors.
+#[cfg(windows)]
+fn registry_candidates() -> Vec<WowInstallCandidate> {
+    let mut out = Vec::new();
+    for (key_path, value) in REGISTRY_KEYS.iter() {
+        let Ok(key) = Hive::LocalMachine.open(*key_path, Security::Read) else {
+            continue;
+        };
+        let Ok(Data::String(path)) = key.value(*value) else {
+            continue;
+        };
+        let wow = path.to_string_lossy();
+        let flavor_dir = PathBuf::from(wow.trim_end_matches(['\\', '/']));
+        let origin = format!(r"HKLM\{key_path}");
+        let install = match flavor_dir.parent() {
+            Some(parent) if is_flavor_folder(&flavor_dir) => parent.to_path_buf(),
+            _ => flavor_dir.clone(),
+        };
 
-    if let Ok(key) = Hive::LocalMachine.open(fallback, Security::Read) {
-        if let Ok(Data::String(path)) = key.value("InstallPath") {
-            let wow = path.to_string_lossy();
-            let result = format!("{}\\WTF\\Account", wow.trim_end_matches(['\\','/']));
-            return Some(result);
+        let before = out.len();
+        flavors_in_install(&install, WowInstallSource::Registry, &origin, &mut out);
+        if out.len() == before {
+            out.push(candidate(&flavor_dir, WowInstallSource::Registry, origin));
         }
     }
+    out
+}
 
-    None
+#[cfg(not(windows))]
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(not(windows))]
+fn installs_in_prefix(
+    prefix: &Path,
+    source: WowInstallSource,
+    origin: &Path,
+    out: &mut Vec<WowInstallCandidate>,
+) {
+    let origin = origin.to_string_lossy();
+    for install in PREFIX_INSTALL_DIRS.iter().map(|dir| prefix.join(dir)) {
+        flavors_in_install(&install, source, &origin, out);
+    }
+}
+
+// `key: value` from a flat YAML line, quotes stripped.
+#[cfg(not(windows))]
+fn yaml_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let value = line.trim().strip_prefix(key)?.strip_prefix(':')?.trim();
+    Some(value.trim_matches(['"', '\'']).trim()).filter(|v| !v.is_empty())
+}
+
+#[cfg(not(windows))]
+fn wine_prefixes(home: &Path) -> Vec<PathBuf> {
+    let mut prefixes: Vec<PathBuf> = std::env::var_os("WINEPREFIX")
+        .filter(|prefix| !prefix.is_empty())
+        .map(PathBuf::from)
+        .into_iter()
+        .collect();
+    prefixes.push(home.join(".wine"));
+    // Lutris installs into ~/Games/<game> by default, even when its config is gone.
+    prefixes.extend(subdirs(&home.join("Games")));
+    prefixes
+}
+
+// Lutris game configs name the prefix directly, or at least the executable inside it.
+#[cfg(not(windows))]
+fn lutris_prefixes(home: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let config_dirs = [
+        home.join(".config/lutris/games"),
+        home.join(".local/share/lutris/games"),
+        home.join(".var/app/net.lutris.Lutris/config/lutris/games"),
+    ];
+
+    let mut prefixes = Vec::new();
+    for config in config_dirs.iter().filter_map(|dir| fs::read_dir(dir).ok()) {
+        for path in config.flatten().map(|entry| entry.path()) {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("yml") {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let prefix = content.lines().find_map(|line| yaml_value(line, "prefix"));
+            let from_exe = || {
+                content
+                    .lines()
+                    .find_map(|line| yaml_value(line, "exe"))
+                    .and_then(|exe| {
+                        Path::new(exe)
+                            .ancestors()
+                            .find(|dir| dir.file_name().is_some_and(|name| name == "drive_c"))
+                            .and_then(|drive_c| drive_c.parent())
+                            .map(|prefix| prefix.to_string_lossy().to_string())
+                    })
+            };
+            if let Some(prefix) = prefix.map(str::to_string).or_else(from_exe) {
+                prefixes.push((PathBuf::from(prefix), path));
+            }
+        }
+    }
+    prefixes
+}
+
+// Each bottle folder is a prefix; `bottle.yml` marks it as one.
+#[cfg(not(windows))]
+fn bottles_prefixes(home: &Path) -> Vec<PathBuf> {
+    [
+        home.join(".local/share/bottles/bottles"),
+        home.join(".var/app/com.usebottles.bottles/data/bottles/bottles"),
+    ]
+    .iter()
+    .flat_map(|dir| subdirs(dir))
+    .filter(|bottle| bottle.join("bottle.yml").is_file())
+    .collect()
+}
+
+// Proton keeps one prefix per app under `steamapps/compatdata/<appid>/pfx`, in every library.
+#[cfg(not(windows))]
+fn steam_prefixes(home: &Path) -> Vec<PathBuf> {
+    let roots = [
+        home.join(".steam/steam"),
+        home.join(".local/share/Steam"),
+        home.join(".var/app/com.valvesoftware.Steam/data/Steam"),
+    ];
+
+    let mut libraries: Vec<PathBuf> = Vec::new();
+    for root in &roots {
+        libraries.push(root.clone());
+        let Ok(vdf) = fs::read_to_string(root.join("steamapps/libraryfolders.vdf")) else {
+            continue;
+        };
+        for line in vdf.lines() {
+            let tokens: Vec<&str> = line.split('"').filter(|t| !t.trim().is_empty()).collect();
+            if let ["path", library] = tokens.as_slice() {
+                libraries.push(PathBuf::from(library.replace("\\\\", "\\")));
+            }
+        }
+    }
+
+    libraries
+        .iter()
+        .flat_map(|library| subdirs(&library.join("steamapps/compatdata")))
+        .map(|app| app.join("pfx"))
+        .filter(|prefix| prefix.is_dir())
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn wine_candidates() -> Vec<WowInstallCandidate> {
+    let Some(home) = home_dir() else {
+        return Vec::new();
+    };
+
+    let mut out = Vec::new();
+    for prefix in wine_prefixes(&home) {
+        installs_in_prefix(&prefix, WowInstallSource::WinePrefix, &prefix, &mut out);
+    }
+    for (prefix, config) in lutris_prefixes(&home) {
+        installs_in_prefix(&prefix, WowInstallSource::Lutris, &config, &mut out);
+    }
+    for prefix in bottles_prefixes(&home) {
+        installs_in_prefix(&prefix, WowInstallSource::Bottles, &prefix, &mut out);
+    }
+    for prefix in steam_prefixes(&home) {
+        let compatdata = prefix.parent().unwrap_or(&prefix).to_path_buf();
+        installs_in_prefix(
+            &prefix,
+            WowInstallSource::SteamProton,
+            &compatdata,
+            &mut out,
+        );
+    }
+    out
+}
+
+/// Every WoW install this machine appears to have, in lookup order. The same folder reached
+/// through several routes (e.g. the `~/.steam/steam` symlink) is listed once, under the first.
+pub fn detect_installs() -> Vec<WowInstallCandidate> {
+    #[cfg(windows)]
+    let candidates = registry_candidates();
+    #[cfg(not(windows))]
+    let candidates = wine_candidates();
+
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|candidate| {
+            let path = Path::new(&candidate.account_path);
+            seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()))
+        })
+        .collect()
+}
+
+// A user-chosen folder may be the `World of Warcraft` install, one `_<flavor>_` folder in it,
+// or that flavor's `WTF/Account` folder.
+fn installs_at(folder: &Path) -> Vec<WowInstallCandidate> {
+    let origin = folder.to_string_lossy().to_string();
+    if folder.ends_with(Path::new("WTF").join("Account")) && folder.is_dir() {
+        if let Some(flavor_dir) = folder.parent().and_then(Path::parent) {
+            return vec![candidate(flavor_dir, WowInstallSource::Manual, origin)];
+        }
+    }
+    if folder.join("WTF").join("Account").is_dir() {
+        return vec![candidate(folder, WowInstallSource::Manual, origin)];
+    }
+
+    let mut out = Vec::new();
+    flavors_in_install(folder, WowInstallSource::Manual, &origin, &mut out);
+    out
+}
+
+fn wow_path_override() -> &'static Mutex<Option<String>> {
+    WOW_PATH_OVERRIDE.get_or_init(|| Mutex::new(None))
+}
+
+fn current_override() -> Option<String> {
+    wow_path_override()
+        .lock()
+        .ok()
+        .and_then(|guard| guard.clone())
+}
+
+/// Picks up the folder stored by `set_wow_path`. Called once during setup.
+pub fn load_wow_path_override(app: &AppHandle) {
+    if let Ok(mut guard) = wow_path_override().lock() {
+        *guard = app_config::load(app).wow_path;
+    }
+}
+
+/// The installs the app works with: those in the user's folder when one is set, otherwise the
+/// detected ones. An override that no longer holds an install falls back to detection.
+pub fn find_installs() -> Vec<WowInstallCandidate> {
+    if let Some(folder) = current_override() {
+        let installs = installs_at(Path::new(&folder));
+        if !installs.is_empty() {
+            return installs;
+        }
+    }
+    detect_installs()
+}
+
+#[tauri::command]
+pub fn find_wow_installs() -> Vec<WowInstallCandidate> {
+    detect_installs()
+}
+
+#[tauri::command]
+pub fn get_wow_path_settings() -> WowPathSettings {
+    WowPathSettings {
+        override_path: current_override(),
+        installs: find_installs(),
+        detected: detect_installs(),
+    }
+}
+
+/// Uses `path` instead of the detected installs (`null` goes back to detection), persists the
+/// choice and moves the watchers over without a restart.
+#[tauri::command]
+pub fn set_wow_path(app: AppHandle, path: Option<String>) -> Result<WowPathSettings, String> {
+    let path = path.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    if let Some(folder) = path.as_deref() {
+        if !Path::new(folder).is_dir() {
+            return Err(format!("Folder not found: {folder}"));
+        }
+        if installs_at(Path::new(folder)).is_empty() {
+            return Err(format!("No WoW install found in {folder}"));
+        }
+    }
+
+    let mut config = app_config::load(&app);
+    config.wow_path = path.clone();
+    app_config::save(&app, &config)?;
+    *wow_path_override()
+        .lock()
+        .map_err(|_| "WoW path override poisoned".to_string())? = path;
+
+    let settings = get_wow_path_settings();
+    log_command::emit_log(
+        &app,
+        if settings.override_path.is_some() {
+            "WoW path set"
+        } else {
+            "WoW path reset to detected"
+        },
+    );
+    watcher_service::watch_installs(&app, &settings.installs);
+    if watcher::rescan(&app) {
+        log_command::emit_log(&app, "SavedVariables discovered");
+    } else {
+        log_command::emit_log(&app, "SavedVariables not found");
+    }
+    Ok(settings)
+}
+
+/// Account folder of the preferred install: the first retail one, else the first found. The
+/// watcher covers every install; this is for what only needs one, like the addon version.
+pub fn get_wow_path() -> Option<String> {
+    let candidates = find_installs();
+    candidates
+        .iter()
+        .find(|candidate| candidate.flavor == Some(GameFlavor::Retail))
+        .or_else(|| candidates.first())
+        .map(|candidate| candidate.account_path.clone())
 }
@@ -1,10 +1,28 @@
-use serde_json::Value;
+use serde::Serialize;
+use serde_json::{json, Value};
+use ts_rs::TS;
 use xxhash_rust::xxh64::xxh64;
 
+/// Version tag of the current fingerprint; bump it whenever the encoding below changes.
+pub const MATCH_ID_VERSION: &str = "v2";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/MatchIdentity.ts")]
+pub struct MatchIdentity {
+    /// Current fingerprint, `v2:` followed by 16 hex digits.
+    pub id: String,
+    /// Unversioned v1 fingerprint that older stored matches and server records were keyed by.
+    pub legacy_id: String,
+}
+
 #[tauri::command]
-pub fn identify_match(obj: Value) -> Result<String, String> {
-    let id = hash_match_from_full_match(&obj);
-    Ok(id)
+pub fn identify_match(obj: Value) -> Result<MatchIdentity, String> {
+    let fields = IdentityFields::collect(&obj);
+    Ok(MatchIdentity {
+        id: fields.canonical_id(),
+        legacy_id: fields.legacy_id(),
+    })
 }
 
 fn as_i64_lossy(value: &Value) -> Option<i64> {
@@ -17,7 +35,8 @@ fn as_i64_lossy(value: &Value) -> Option<i64> {
     value.as_f64().map(|v| v.round() as i64)
 }
 
-fn player_key(player: &Value) -> String {
+/// Lowercased GUID, or `name-realm` for players captured without one.
+pub fn player_key(player: &Value) -> String {
     if let Some(guid) = player.get("guid").and_then(|v| v.as_str()) {
         let trimmed = guid.trim();
         if !trimmed.is_empty() {
@@ -63,88 +82,236 @@ fn player_killing_blows_deaths(player: &Value) -> (Option<i64>, Option<i64>) {
         .and_then(as_i64_lossy)
         .or_else(|| player.get("kills").and_then(as_i64_lossy))
         .map(|v| v.max(0));
-    let deaths = player.get("deaths").and_then(as_i64_lossy).map(|v| v.max(0));
+    let deaths = player
+        .get("deaths")
+        .and_then(as_i64_lossy)
+        .map(|v| v.max(0));
 
     (killing_blows, deaths)
 }
 
-fn hash_match_from_full_match(obj: &Value) -> String {
-    let map = obj["matchDetails"]["mapName"].as_str().unwrap_or("");
-    let format = obj["matchDetails"]["format"].as_str().unwrap_or("");
-
-    let empty: Vec<Value> = Vec::new();
-    let players = obj["players"]
-        .as_array()
-        .or_else(|| obj["soloShuffle"]["matchSummary"]["players"].as_array())
-        .unwrap_or(&empty);
-
-    // Gather stable identity fields and per-player totals in a single pass.
-    // (We still sort afterward for order-independence.)
-    let mut realms: Vec<String> = Vec::with_capacity(players.len());
-    let mut names: Vec<String> = Vec::with_capacity(players.len());
-    let mut servers: Vec<String> = Vec::with_capacity(players.len());
-    let mut stats: Vec<(String, Option<i64>, Option<i64>, Option<i64>, Option<i64>)> =
-        Vec::with_capacity(players.len());
-
-    for p in players {
-        if let Some(realm) = p.get("realm").and_then(|v| v.as_str()) {
-            realms.push(realm.to_lowercase());
-        }
-        if let Some(name) = p.get("name").and_then(|v| v.as_str()) {
-            names.push(name.to_lowercase());
-        }
-        if let Some(server) = p.get("server").and_then(|v| v.as_str()) {
-            servers.push(server.to_lowercase());
+type PlayerTotals = (String, Option<i64>, Option<i64>, Option<i64>, Option<i64>);
+
+/// Identity-relevant fields of a match, lowercased but otherwise as captured.
+struct IdentityFields {
+    map: String,
+    format: String,
+    realms: Vec<String>,
+    names: Vec<String>,
+    servers: Vec<String>,
+    /// Player key, damage, healing, killing blows and deaths.
+    stats: Vec<PlayerTotals>,
+}
+
+impl IdentityFields {
+    fn collect(obj: &Value) -> Self {
+        let empty: Vec<Value> = Vec::new();
+        let players = obj["players"]
+            .as_array()
+            .or_else(|| obj["soloShuffle"]["matchSummary"]["players"].as_array())
+            .unwrap_or(&empty);
+
+        let mut fields = IdentityFields {
+            map: obj["matchDetails"]["mapName"]
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            format: obj["matchDetails"]["format"]
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            realms: Vec::with_capacity(players.len()),
+            names: Vec::with_capacity(players.len()),
+            servers: Vec::with_capacity(players.len()),
+            stats: Vec::with_capacity(players.len()),
+        };
+
+        for p in players {
+            if let Some(realm) = p.get("realm").and_then(|v| v.as_str()) {
+                fields.realms.push(realm.to_lowercase());
+            }
+            if let Some(name) = p.get("name").and_then(|v| v.as_str()) {
+                fields.names.push(name.to_lowercase());
+            }
+            if let Some(server) = p.get("server").and_then(|v| v.as_str()) {
+                fields.servers.push(server.to_lowercase());
+            }
+
+            // Include per-player totals to detect tampering across uploads.
+            // Missing values remain missing (not coerced to 0) so incomplete captures differ.
+            let (dmg, heal) = player_damage_healing(p);
+            let (kb, deaths) = player_killing_blows_deaths(p);
+            fields.stats.push((player_key(p), dmg, heal, kb, deaths));
         }
 
-        // Include per-player totals to detect tampering across uploads.
-        // Missing values remain missing (not coerced to 0) so incomplete captures differ.
-        let key = player_key(p);
-        let (dmg, heal) = player_damage_healing(p);
-        let (kb, deaths) = player_killing_blows_deaths(p);
-        stats.push((key, dmg, heal, kb, deaths));
+        fields
     }
 
-    realms.sort();
-    names.sort();
-    servers.sort();
-    stats.sort_by(|a, b| a.0.cmp(&b.0));
+    /// The v2 fingerprint: `v2:` plus the xxHash64 (seed 0, lowercase hex, 16 digits) of the
+    /// UTF-8 bytes of this compact JSON array, as serialized by serde_json:
+    ///
+    /// ```text
+    /// ["pvp-scalpel/match", 2, map, format, [realm..], [name..], [server..],
+    ///  [[playerKey, damage, healing, killingBlows, deaths]..]]
+    /// ```
+    ///
+    /// Strings are trimmed and lowercased, missing map/format are `""`, and missing totals are
+    /// `null`. The realm, name and server lists are sorted; player rows are sorted by key and then
+    /// by totals, with `null` before any number. Player keys are the GUID when present, otherwise
+    /// `name-realm` (each `unknown` when absent).
+    fn canonical_id(&self) -> String {
+        let normalize = |values: &[String]| {
+            let mut values: Vec<String> = values.iter().map(|v| v.trim().to_string()).collect();
+            values.sort();
+            values
+        };
+        let mut stats = self.stats.clone();
+        stats.sort();
+        let players: Vec<Value> = stats
+            .into_iter()
+            .map(|(key, dmg, heal, kb, deaths)| json!([key, dmg, heal, kb, deaths]))
+            .collect();
 
-    let mut stats_str = String::new();
-    for (idx, (key, dmg, heal, kb, deaths)) in stats.iter().enumerate() {
-        if idx > 0 {
-            stats_str.push('|');
-        }
-        stats_str.push_str(key);
-        stats_str.push(':');
-        match dmg {
-            Some(v) => stats_str.push_str(&v.to_string()),
-            None => stats_str.push_str("null"),
-        }
-        stats_str.push(',');
-        match heal {
-            Some(v) => stats_str.push_str(&v.to_string()),
-            None => stats_str.push_str("null"),
-        }
-        stats_str.push(',');
-        match kb {
-            Some(v) => stats_str.push_str(&v.to_string()),
-            None => stats_str.push_str("null"),
+        let encoded = json!([
+            "pvp-scalpel/match",
+            2,
+            self.map.trim().to_lowercase(),
+            self.format.trim().to_lowercase(),
+            normalize(&self.realms),
+            normalize(&self.names),
+            normalize(&self.servers),
+            players,
+        ])
+        .to_string();
+
+        format!("{MATCH_ID_VERSION}:{:016x}", xxh64(encoded.as_bytes(), 0))
+    }
+
+    /// The original fingerprint. Its input depends on Rust's `Debug` output for string lists,
+    /// so it is frozen here only to keep older IDs linkable; do not change it.
+    fn legacy_id(&self) -> String {
+        let mut realms = self.realms.clone();
+        let mut names = self.names.clone();
+        let mut servers = self.servers.clone();
+        let mut stats = self.stats.clone();
+        realms.sort();
+        names.sort();
+        servers.sort();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut stats_str = String::new();
+        for (idx, (key, dmg, heal, kb, deaths)) in stats.iter().enumerate() {
+            if idx > 0 {
+                stats_str.push('|');
+            }
+            stats_str.push_str(key);
+            stats_str.push(':');
+            match dmg {
+                Some(v) => stats_str.push_str(&v.to_string()),
+                None => stats_str.push_str("null"),
+            }
+            stats_str.push(',');
+            match heal {
+                Some(v) => stats_str.push_str(&v.to_string()),
+                None => stats_str.push_str("null"),
+            }
+            stats_str.push(',');
+            match kb {
+                Some(v) => stats_str.push_str(&v.to_string()),
+                None => stats_str.push_str("null"),
+            }
+            stats_str.push(',');
+            match deaths {
+                Some(v) => stats_str.push_str(&v.to_string()),
+                None => stats_str.push_str("null"),
+            }
         }
-        stats_str.push(',');
-        match deaths {
-            Some(v) => stats_str.push_str(&v.to_string()),
-            None => stats_str.push_str("null"),
+
+        // build stable string representation
+        let combined = format!(
+            "{}|{}|{:?}|{:?}|{:?}|{}",
+            self.map, self.format, realms, names, servers, stats_str
+        );
+
+        // xxHash64 → always 8 bytes → 16 hex chars
+        let h = xxh64(combined.as_bytes(), 0);
+        format!("{:016x}", h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdentityFields;
+    use serde_json::{json, Value};
+
+    fn arena() -> Value {
+        json!({
+            "matchDetails": { "mapName": "Nagrand Arena", "format": "Rated Arena 3v3" },
+            "players": [
+                {
+                    "name": "Zugzug", "realm": "Ragnaros", "server": "EU", "guid": "Player-1-0A",
+                    "damage": 1200345, "healing": 40500.6, "killingBlows": 2, "deaths": 0
+                },
+                {
+                    "name": "Élune", "realm": "Kazzak", "server": "EU",
+                    "damageDone": 980000, "healingDone": 2500000, "kills": 0, "deaths": 1
+                },
+                { "name": "Stabby", "realm": "Ragnaros", "server": "EU", "damage": -5 }
+            ]
+        })
+    }
+
+    fn shuffle() -> Value {
+        json!({
+            "matchDetails": { "mapName": "Dalaran Sewers", "format": "Solo Shuffle" },
+            "soloShuffle": { "matchSummary": { "players": [
+                { "name": "B", "realm": "r" },
+                { "name": "A", "realm": "r", "damage": 10 }
+            ] } }
+        })
+    }
+
+    // Fixture, current ID and legacy ID.
+    type Golden = (fn() -> Value, &'static str, &'static str);
+
+    // Golden vectors: a change to either value means stored IDs would stop matching.
+    const GOLDEN: [Golden; 3] = [
+        (arena, "v2:9fafeb0d6d16d457", "b267e446d19feff3"),
+        (shuffle, "v2:b6904327b0066d7e", "9bc16f8b2d454674"),
+        (|| json!({}), "v2:1cc3abfb376baf0e", "47dda3644dbb8dd5"),
+    ];
+
+    #[test]
+    fn golden_vectors() {
+        for (fixture, id, legacy_id) in GOLDEN {
+            let fields = IdentityFields::collect(&fixture());
+            assert_eq!(fields.canonical_id(), id);
+            assert_eq!(fields.legacy_id(), legacy_id);
         }
     }
 
-    // build stable string representation
-    let combined = format!(
-        "{}|{}|{:?}|{:?}|{:?}|{}",
-        map, format, realms, names, servers, stats_str
-    );
+    #[test]
+    fn canonical_id_ignores_player_order_and_padding() {
+        let mut reordered = arena();
+        let players = reordered["players"].as_array_mut().unwrap();
+        players.reverse();
+        players[0]["name"] = json!("  Stabby ");
+        reordered["matchDetails"]["mapName"] = json!("NAGRAND ARENA ");
 
-    // xxHash64 → always 8 bytes → 16 hex chars
-    let h = xxh64(combined.as_bytes(), 0);
-    format!("{:016x}", h)
+        assert_eq!(
+            IdentityFields::collect(&reordered).canonical_id(),
+            IdentityFields::collect(&arena()).canonical_id()
+        );
+    }
+
+    #[test]
+    fn canonical_id_tracks_totals() {
+        let mut tampered = arena();
+        tampered["players"][0]["damage"] = json!(1200346);
+
+        assert_ne!(
+            IdentityFields::collect(&tampered).canonical_id(),
+            IdentityFields::collect(&arena()).canonical_id()
+        );
+    }
 }
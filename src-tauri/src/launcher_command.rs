@@ -1,3 +1,4 @@
+#[cfg(windows)]
 use registry::{Data, Hive, RegKey, Security};
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -7,12 +8,14 @@ use std::os::windows::process::CommandExt;
 
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
+#[cfg(windows)]
 const LAUNCHER_UPGRADE_CODE: &str = "A3F9C2B4-6E1A-4F3C-9D6A-8C4E0E9A2B17";
 
 fn launcher_exe_candidates() -> [&'static str; 2] {
     ["PVP-S Launcher.exe", "PvP-S Launcher.exe"]
 }
 
+#[cfg(windows)]
 fn launcher_display_names() -> [&'static str; 2] {
     ["PVP-S Launcher", "PvP-S Launcher"]
 }
@@ -25,6 +28,7 @@ fn launcher_dir_candidates() -> [&'static str; 3] {
     ["PVP-S Launcher", "PvP-S Launcher", "PvP Scalpel Launcher"]
 }
 
+#[cfg(windows)]
 fn extract_exe_path(value: &str) -> Option<PathBuf> {
     let trimmed = value.trim_matches('"');
     let lower = trimmed.to_lowercase();
@@ -47,6 +51,7 @@ fn matches_launcher_exe_name(path: &Path) -> bool {
     false
 }
 
+#[cfg(windows)]
 fn matches_launcher_display_name(value: &str) -> bool {
     let trimmed = value.trim();
     launcher_display_names()
@@ -54,6 +59,7 @@ fn matches_launcher_display_name(value: &str) -> bool {
         .any(|candidate| candidate.eq_ignore_ascii_case(trimmed))
 }
 
+#[cfg(windows)]
 fn normalize_guid(value: &str) -> String {
     value
         .trim()
@@ -65,6 +71,7 @@ fn normalize_guid(value: &str) -> String {
         .collect()
 }
 
+#[cfg(windows)]
 fn matches_upgrade_code(value: &str) -> bool {
     normalize_guid(value) == normalize_guid(LAUNCHER_UPGRADE_CODE)
 }
@@ -167,6 +174,7 @@ fn find_launcher_shortcut() -> Option<PathBuf> {
     None
 }
 
+#[cfg(windows)]
 fn resolve_launcher_from_key(key: &RegKey, current_exe: Option<&Path>) -> Option<PathBuf> {
     if let Ok(Data::String(path)) = key.value("DisplayIcon") {
         let raw = path.to_string_lossy();
@@ -201,6 +209,7 @@ fn resolve_launcher_from_key(key: &RegKey, current_exe: Option<&Path>) -> Option
     None
 }
 
+#[cfg(windows)]
 fn scan_uninstall_roots_for_launcher(hive: Hive, current_exe: Option<&Path>) -> Option<PathBuf> {
     let roots = [
         r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
@@ -240,8 +249,8 @@ fn scan_uninstall_roots_for_launcher(hive: Hive, current_exe: Option<&Path>) ->
     None
 }
 
-fn find_launcher_exe() -> Option<PathBuf> {
-    let current_exe = std::env::current_exe().ok();
+#[cfg(windows)]
+fn find_registered_launcher(current_exe: Option<&Path>) -> Option<PathBuf> {
     // Detect the launcher install path via uninstall registry entries.
     let keys = [
         r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\PVP-S Launcher",
@@ -260,18 +269,28 @@ fn find_launcher_exe() -> Option<PathBuf> {
                 Err(_) => continue,
             };
 
-            if let Some(candidate) = resolve_launcher_from_key(&key, current_exe.as_deref()) {
+            if let Some(candidate) = resolve_launcher_from_key(&key, current_exe) {
                 return Some(candidate);
             }
         }
     }
 
     for hive in hives {
-        if let Some(candidate) = scan_uninstall_roots_for_launcher(hive, current_exe.as_deref()) {
+        if let Some(candidate) = scan_uninstall_roots_for_launcher(hive, current_exe) {
             return Some(candidate);
         }
     }
 
+    None
+}
+
+fn find_launcher_exe() -> Option<PathBuf> {
+    let current_exe = std::env::current_exe().ok();
+    #[cfg(windows)]
+    if let Some(candidate) = find_registered_launcher(current_exe.as_deref()) {
+        return Some(candidate);
+    }
+
     for root in possible_install_roots() {
         for dir in launcher_dir_candidates() {
             for exe in launcher_exe_candidates() {
@@ -0,0 +1,1486 @@
+//! Rust port of `resolveLocalSpellModel` and `buildSpellOutcomeCounts` from
+//! `src/Domain/localSpellModel.ts` (and the legacy grouping in `spellCastResolver.ts`). The two
+//! must produce the same model; the parity tests below pin that down.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use ts_rs::TS;
+
+pub const MODEL_SCHEMA_VERSION: u8 = 2;
+
+// Legacy timeline grouping. Timeline times are seconds; the windows keep the frontend's values.
+const GUID_WINDOW_MS: f64 = 800.0;
+const FALLBACK_WINDOW_MS: f64 = 250.0;
+const START_TIMEOUT_MS: f64 = 1500.0;
+
+const UNSUPPORTED_V4_SCHEMA: &str = "unsupported-v4-schema";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../../src/Interfaces/SpellOutcomeResult.ts")]
+pub enum SpellOutcomeResult {
+    Succeeded,
+    Failed,
+    Interrupted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "../../src/Interfaces/LocalSpellSourceFormat.ts")]
+pub enum LocalSpellSourceFormat {
+    LegacyTimeline,
+    V4LocalSpellCapture,
+    PersistedNormalizedV2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[ts(
+    export,
+    export_to = "../../src/Interfaces/LocalSpellAttemptGrouping.ts"
+)]
+pub enum LocalSpellAttemptGrouping {
+    #[serde(rename = "castGUID")]
+    CastGuid,
+    #[serde(rename = "fallback")]
+    Fallback,
+    #[serde(rename = "normalized")]
+    Normalized,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(
+    export,
+    export_to = "../../src/Interfaces/NormalizedLocalSpellEvent.ts"
+)]
+pub struct NormalizedLocalSpellEvent {
+    #[ts(type = "number")]
+    pub id: i64,
+    pub t: f64,
+    #[ts(type = "number")]
+    pub spell_id: i64,
+    pub event: String,
+    #[serde(rename = "castGUID", skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cast_guid: Option<String>,
+    #[ts(type = "number")]
+    pub index: i64,
+    /// Synthesized from a v4 cast row rather than observed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub pseudo: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(
+    export,
+    export_to = "../../src/Interfaces/NormalizedLocalSpellAttempt.ts"
+)]
+pub struct NormalizedLocalSpellAttempt {
+    pub id: String,
+    #[ts(type = "number")]
+    pub spell_id: i64,
+    #[serde(rename = "castGUID", skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cast_guid: Option<String>,
+    pub start_time: f64,
+    pub end_time: f64,
+    pub window_ms: f64,
+    pub grouping: LocalSpellAttemptGrouping,
+    pub events: Vec<NormalizedLocalSpellEvent>,
+    pub outcomes: Vec<SpellOutcomeResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub resolved_outcome: Option<SpellOutcomeResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number | null")]
+    pub round_index: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
+    pub interruptible: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "unknown")]
+    pub interrupted_by: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number[]")]
+    pub linked_loc: Option<Vec<i64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
+    pub fake_cast_stop_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "unknown")]
+    pub target_info: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub provenance: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(
+    export,
+    export_to = "../../src/Interfaces/NormalizedLocalLossOfControlEntry.ts"
+)]
+pub struct NormalizedLocalLossOfControlEntry {
+    #[ts(type = "number")]
+    pub id: i64,
+    pub t: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
+    pub duration: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
+    pub end_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number | null")]
+    pub round_index: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
+    pub loc_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
+    pub display_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
+    pub issued_by_guid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number | null")]
+    pub aura_instance_id: Option<i64>,
+    /// The raw school value, a string or a number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "string | number | null")]
+    pub school: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "unknown")]
+    pub raw: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(
+    export,
+    export_to = "../../src/Interfaces/NormalizedLocalSpellModel.ts"
+)]
+pub struct NormalizedLocalSpellModel {
+    #[ts(type = "2")]
+    pub schema_version: u8,
+    pub source_format: LocalSpellSourceFormat,
+    pub detail_available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
+    pub failure_reason: Option<String>,
+    pub attempts: Vec<NormalizedLocalSpellAttempt>,
+    pub events: Vec<NormalizedLocalSpellEvent>,
+    pub loc_entries: Vec<NormalizedLocalLossOfControlEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
+    pub duration_seconds_hint: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+#[ts(
+    export,
+    export_to = "../../src/Interfaces/ComputedSpellOutcomeCounts.ts"
+)]
+pub struct ComputedSpellOutcomeCounts {
+    pub succeeded: u32,
+    pub interrupted: u32,
+    pub failed: u32,
+}
+
+/// Owner interrupt totals computed by the webview; older entries carry only some of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/ComputedOwnerKickSummary.ts")]
+pub struct ComputedOwnerKickSummary {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub total: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub intent_attempts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub landed: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub confirmed_interrupts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub missed: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub succeeded: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub failed: Option<u32>,
+}
+
+/// The `computed` block stored on a match.
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/ComputedAnalyticsV2.ts")]
+pub struct ComputedAnalyticsV2 {
+    #[ts(type = "2")]
+    pub schema_version: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, as = "Option<HashMap<String, ComputedSpellOutcomeCounts>>")]
+    pub spell_outcomes_by_spell_id: Option<BTreeMap<i64, ComputedSpellOutcomeCounts>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub owner_kicks: Option<ComputedOwnerKickSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub local_spell_model: Option<NormalizedLocalSpellModel>,
+}
+
+type SchemaIndex = HashMap<String, i64>;
+
+struct CastFields;
+
+impl CastFields {
+    const SPELL_ID: &[&str] = &["spellID", "spellId", "spell", "abilityId"];
+    const CAST_GUID: &[&str] = &["castGUID", "castGuid", "castId", "castKey"];
+    const TIME: &[&str] = &["t", "time", "timestamp", "at", "attemptTimeCs"];
+    const START_TIME: &[&str] = &[
+        "startTime",
+        "firstObservedTime",
+        "firstTime",
+        "startedAt",
+        "attemptTimeCs",
+    ];
+    const END_TIME: &[&str] = &[
+        "endTime",
+        "lastTime",
+        "completedAt",
+        "resolvedAt",
+        "resolveTimeCs",
+    ];
+    const DURATION: &[&str] = &["duration", "elapsed", "castDuration", "durationCs"];
+    const OUTCOME: &[&str] = &["outcome", "result", "status", "resolvedOutcome"];
+    const FIRST_EVENT: &[&str] = &[
+        "firstEvent",
+        "firstObservedEvent",
+        "startEvent",
+        "openEvent",
+    ];
+    const TERMINAL_EVENT: &[&str] = &["terminalEvent", "outcomeEvent", "lastEvent", "endEvent"];
+    const INTERRUPTIBLE: &[&str] = &[
+        "interruptible",
+        "isInterruptible",
+        "canBeInterrupted",
+        "interruptibleState",
+    ];
+    const INTERRUPTED_BY: &[&str] = &["interruptedBy", "interruptSource", "interruptSpell"];
+    const LINKED_LOC: &[&str] = &["linkedLoc", "locLink", "locIndex", "linkedLocIndex"];
+    const ROUND_INDEX: &[&str] = &["roundIndex", "round"];
+    const FAKE_CAST_STOP_REASON: &[&str] = &[
+        "fakeCastStopReason",
+        "stopReason",
+        "fakeStopReason",
+        "cancelReason",
+        "manualStopReason",
+    ];
+    const TARGET_INFO: &[&str] = &[
+        "targetInfo",
+        "targetSnapshot",
+        "target",
+        "targetName",
+        "targetDisposition",
+        "targetIsPlayer",
+        "targetCanAttack",
+        "targetIsFriend",
+        "targetReaction",
+    ];
+    const PROVENANCE: &[&str] = &["provenance", "provenanceFlags", "sourceFlags", "flags"];
+}
+
+struct LocFields;
+
+impl LocFields {
+    const TIME: &[&str] = &["t", "time", "timestamp", "at", "startTime", "timeCs"];
+    const DURATION: &[&str] = &["duration", "elapsed", "durationCs"];
+    const ROUND_INDEX: &[&str] = &["roundIndex", "round"];
+    const LOC_TYPE: &[&str] = &["locType", "type"];
+    const DISPLAY_TEXT: &[&str] = &["displayText", "text", "label"];
+    const ISSUED_BY_GUID: &[&str] = &["issuedByGuid", "sourceGuid"];
+    const AURA_INSTANCE_ID: &[&str] = &["auraInstanceId", "auraId"];
+    const SCHOOL: &[&str] = &["school", "lockoutSchool", "schoolMask", "lockoutSchoolText"];
+}
+
+fn to_finite_number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(number) => number.as_f64().filter(|v| v.is_finite()),
+        Value::String(text) => {
+            let trimmed = text.trim();
+            if trimmed.is_empty() {
+                return None;
+            }
+            trimmed.parse::<f64>().ok().filter(|v| v.is_finite())
+        }
+        _ => None,
+    }
+}
+
+fn to_integer(value: Option<&Value>) -> Option<i64> {
+    to_finite_number(value).map(|v| v.trunc() as i64)
+}
+
+fn to_trimmed_string(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(|v| v.as_str())
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+fn to_boolean(value: Option<&Value>) -> Option<bool> {
+    match value? {
+        Value::Bool(flag) => Some(*flag),
+        Value::Number(number) => number.as_f64().map(|v| v != 0.0),
+        Value::String(text) => match text.trim().to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Some(true),
+            "false" | "no" | "n" | "0" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn seconds_from_centiseconds(value: Option<&Value>) -> Option<f64> {
+    to_finite_number(value).map(|v| v / 100.0)
+}
+
+fn normalize_token(value: &str) -> String {
+    value
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit())
+        .collect()
+}
+
+// `a ?? b ?? ...`: the first field that is neither missing nor null.
+fn first_present<'a>(map: &'a Map<String, Value>, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter()
+        .filter_map(|key| map.get(*key))
+        .find(|value| !value.is_null())
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(flag) => *flag,
+        Value::Number(number) => number.as_f64().is_some_and(|v| v != 0.0),
+        Value::String(text) => !text.is_empty(),
+        _ => true,
+    }
+}
+
+fn is_digits(key: &str) -> bool {
+    !key.is_empty() && key.bytes().all(|b| b.is_ascii_digit())
+}
+
+// Keys JS treats as array indices, which `Object.entries` lists first in ascending order.
+fn is_array_index(key: &str) -> bool {
+    is_digits(key)
+        && (key == "0" || !key.starts_with('0'))
+        && key.parse::<u64>().is_ok_and(|v| v < u32::MAX as u64)
+}
+
+// `Object.entries` order. JS keeps the other keys in insertion order and serde_json keeps them
+// sorted; matches reach the frontend from this backend, so it sees them sorted too.
+fn js_entries(map: &Map<String, Value>) -> Vec<(&String, &Value)> {
+    let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+    entries.sort_by_key(|(key, _)| match is_array_index(key) {
+        true => (0, key.parse::<u64>().unwrap_or(0)),
+        false => (1, 0),
+    });
+    entries
+}
+
+// JS `Number#toString` for the values used in ids: integral values print without a fraction.
+fn js_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e21 {
+        format!("{}", value as i64)
+    } else {
+        format!("{value}")
+    }
+}
+
+// `localeCompare` for the ASCII ids attempts carry: punctuation before digits before letters,
+// letters case-insensitively, and lowercase before uppercase only to break ties.
+fn locale_compare(a: &str, b: &str) -> Ordering {
+    let primary = |ch: char| {
+        let class = if ch.is_ascii_digit() {
+            1
+        } else if ch.is_alphabetic() {
+            2
+        } else {
+            0
+        };
+        (class, ch.to_lowercase().next().unwrap_or(ch))
+    };
+    a.chars()
+        .map(primary)
+        .cmp(b.chars().map(primary))
+        .then_with(|| {
+            a.chars()
+                .map(|ch| ch.is_uppercase())
+                .cmp(b.chars().map(|ch| ch.is_uppercase()))
+        })
+}
+
+// Ascending by time, ties (and NaN) broken by `tie`, like the frontend's `a.t - b.t || ...`.
+fn by_time_then(a: f64, b: f64, tie: impl FnOnce() -> Ordering) -> Ordering {
+    match a.partial_cmp(&b) {
+        Some(Ordering::Equal) | None => tie(),
+        Some(order) => order,
+    }
+}
+
+fn to_ordered_indexed_rows(value: Option<&Value>) -> Vec<(i64, &Value)> {
+    match value {
+        Some(Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (index as i64, item))
+            .collect(),
+        Some(Value::Object(map)) => {
+            let mut rows: Vec<(i64, &Value)> = js_entries(map)
+                .into_iter()
+                .filter(|(key, _)| is_digits(key))
+                .filter_map(|(key, item)| {
+                    let index = key.parse::<f64>().ok().filter(|v| v.is_finite())?;
+                    Some((index as i64, item))
+                })
+                .collect();
+            rows.sort_by_key(|(index, _)| *index);
+            rows
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn set_field(out: &mut SchemaIndex, name: Option<&str>, index: Option<i64>) {
+    let name = name.map(normalize_token).unwrap_or_default();
+    match index {
+        Some(index) if !name.is_empty() && index >= 0 => {
+            out.insert(name, index);
+        }
+        _ => {}
+    }
+}
+
+// `raw ?? fallback`, coerced to an integer.
+fn index_or(raw: Option<&Value>, fallback: i64) -> Option<i64> {
+    match raw.filter(|v| !v.is_null()) {
+        Some(raw) => to_integer(Some(raw)),
+        None => Some(fallback),
+    }
+}
+
+fn build_schema_index(schema_row: &Value) -> Option<SchemaIndex> {
+    const NAME_KEYS: &[&str] = &["name", "key", "field", "label", "id", "column"];
+    const INDEX_KEYS: &[&str] = &["index", "idx", "slot", "position", "columnIndex"];
+    let mut out = SchemaIndex::new();
+
+    match schema_row {
+        Value::Array(entries) => {
+            for (index, entry) in entries.iter().enumerate() {
+                let index = index as i64;
+                match entry {
+                    Value::String(name) => set_field(&mut out, Some(name), Some(index)),
+                    Value::Array(pair) if pair.len() >= 2 => {
+                        if let Some(first) = pair[0].as_str() {
+                            let at = to_integer(Some(&pair[1])).unwrap_or(index);
+                            set_field(&mut out, Some(first), Some(at));
+                        } else if let Some(second) = pair[1].as_str() {
+                            let at = to_integer(Some(&pair[0])).unwrap_or(index);
+                            set_field(&mut out, Some(second), Some(at));
+                        }
+                    }
+                    Value::Object(entry) => {
+                        if let Some(name) = first_present(entry, NAME_KEYS) {
+                            let at = index_or(first_present(entry, INDEX_KEYS), index);
+                            set_field(&mut out, name.as_str(), at);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in js_entries(map) {
+                let digits = is_digits(key);
+                if digits && value.is_string() {
+                    let at = key.parse::<f64>().ok().map(|v| v as i64);
+                    set_field(&mut out, value.as_str(), at);
+                } else if !digits && value.is_number() {
+                    set_field(&mut out, Some(key), to_integer(Some(value)));
+                } else if let Value::Object(entry) = value {
+                    let name = first_present(entry, &NAME_KEYS[..3])
+                        .map(|name| name.as_str())
+                        .unwrap_or(Some(key.as_str()));
+                    set_field(&mut out, name, to_integer(first_present(entry, INDEX_KEYS)));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    (!out.is_empty()).then_some(out)
+}
+
+fn build_schema_index_from_row(row: &Value) -> Option<SchemaIndex> {
+    build_schema_index(row).or_else(|| {
+        row.as_array()
+            .and_then(|items| items.iter().find_map(build_schema_index))
+    })
+}
+
+struct SchemaRows<'a> {
+    schema: Option<SchemaIndex>,
+    data_rows: Vec<(i64, &'a Value)>,
+}
+
+// The first row that reads as a schema describes the others; rows are used as-is without one.
+fn resolve_schema_rows<'a>(rows: &[(i64, &'a Value)]) -> SchemaRows<'a> {
+    for (raw_index, item) in rows {
+        if let Some(schema) = build_schema_index_from_row(item) {
+            return SchemaRows {
+                schema: Some(schema),
+                data_rows: rows
+                    .iter()
+                    .filter(|(candidate, _)| candidate != raw_index)
+                    .copied()
+                    .collect(),
+            };
+        }
+    }
+    SchemaRows {
+        schema: None,
+        data_rows: rows.to_vec(),
+    }
+}
+
+fn read_field<'a>(
+    row: &'a Value,
+    schema: Option<&SchemaIndex>,
+    aliases: &[&str],
+) -> Option<&'a Value> {
+    let aliases: Vec<String> = aliases.iter().map(|alias| normalize_token(alias)).collect();
+
+    match row {
+        Value::Array(items) => {
+            let schema = schema?;
+            aliases.iter().find_map(|alias| {
+                let index = *schema.get(alias)?;
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|index| items.get(index))
+            })
+        }
+        Value::Object(map) => {
+            if let Some(schema) = schema {
+                let indexed = aliases.iter().find_map(|alias| {
+                    let index = schema.get(alias)?;
+                    map.get(&index.to_string())
+                });
+                if indexed.is_some() {
+                    return indexed;
+                }
+            }
+            let entries = js_entries(map);
+            aliases.iter().find_map(|alias| {
+                entries
+                    .iter()
+                    .find(|(key, _)| normalize_token(key) == *alias)
+                    .map(|(_, value)| *value)
+            })
+        }
+        _ => None,
+    }
+}
+
+fn coerce_string_array(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| to_trimmed_string(Some(item)))
+            .collect(),
+        Some(Value::Object(map)) => js_entries(map)
+            .into_iter()
+            .filter(|(_, entry)| {
+                matches!(entry, Value::Bool(true))
+                    || entry.as_f64() == Some(1.0)
+                    || entry.as_str() == Some("1")
+            })
+            .map(|(key, _)| key.clone())
+            .collect(),
+        other => to_trimmed_string(other).into_iter().collect(),
+    }
+}
+
+fn coerce_linked_loc(value: Option<&Value>) -> Vec<i64> {
+    match value {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| to_integer(Some(item)))
+            .filter(|entry| *entry >= 0)
+            .collect(),
+        Some(Value::Object(map)) => {
+            const DIRECT_KEYS: &[&str] = &["id", "index", "entry", "locIndex", "linkedLoc"];
+            if let Some(direct) = to_integer(first_present(map, DIRECT_KEYS)) {
+                if direct >= 0 {
+                    return vec![direct];
+                }
+            }
+            to_ordered_indexed_rows(value)
+                .into_iter()
+                .filter_map(|(_, item)| to_integer(Some(item)))
+                .filter(|entry| *entry >= 0)
+                .collect()
+        }
+        other => to_integer(other).filter(|v| *v >= 0).into_iter().collect(),
+    }
+}
+
+fn normalize_event_name(value: Option<&Value>) -> Option<String> {
+    if let Some(number) = value.and_then(|v| v.as_f64()) {
+        return match number.trunc() as i64 {
+            1 => Some("START"),
+            2 => Some("SUCCEEDED"),
+            3 => Some("FAILED"),
+            4 => Some("INTERRUPTED"),
+            5 => Some("STOP"),
+            _ => None,
+        }
+        .map(str::to_string);
+    }
+
+    let raw = to_trimmed_string(value)?;
+    let mut out = String::with_capacity(raw.len());
+    let mut in_separator = false;
+    for ch in raw.chars() {
+        if ch.is_whitespace() || ch == '-' {
+            if !in_separator {
+                out.push('_');
+            }
+            in_separator = true;
+        } else {
+            out.push(ch);
+            in_separator = false;
+        }
+    }
+    Some(out.to_uppercase())
+}
+
+fn normalize_outcome_str(value: &str) -> Option<SpellOutcomeResult> {
+    let token = normalize_token(value);
+    if token.is_empty() {
+        None
+    } else if token.contains("succeed") || token == "success" || token == "ok" {
+        Some(SpellOutcomeResult::Succeeded)
+    } else if token.contains("interrupt") {
+        Some(SpellOutcomeResult::Interrupted)
+    } else if ["fail", "quiet", "cancel", "stop"]
+        .iter()
+        .any(|part| token.contains(part))
+    {
+        Some(SpellOutcomeResult::Failed)
+    } else {
+        None
+    }
+}
+
+fn normalize_outcome(value: Option<&Value>) -> Option<SpellOutcomeResult> {
+    match value? {
+        Value::Number(number) => match number.as_f64()?.trunc() as i64 {
+            0 => Some(SpellOutcomeResult::Succeeded),
+            1 => Some(SpellOutcomeResult::Failed),
+            2 => Some(SpellOutcomeResult::Interrupted),
+            _ => None,
+        },
+        Value::String(text) => normalize_outcome_str(text),
+        _ => None,
+    }
+}
+
+fn sort_events(events: &mut [NormalizedLocalSpellEvent]) {
+    events.sort_by(|a, b| by_time_then(a.t, b.t, || a.index.cmp(&b.index)));
+}
+
+fn outcome_priority(outcome: SpellOutcomeResult) -> u8 {
+    match outcome {
+        SpellOutcomeResult::Succeeded => 3,
+        SpellOutcomeResult::Interrupted => 2,
+        SpellOutcomeResult::Failed => 1,
+    }
+}
+
+fn classify_outcome(event: &str) -> Option<SpellOutcomeResult> {
+    match event {
+        "SUCCEEDED" => Some(SpellOutcomeResult::Succeeded),
+        "FAILED" | "FAILED_QUIET" => Some(SpellOutcomeResult::Failed),
+        "INTERRUPTED" => Some(SpellOutcomeResult::Interrupted),
+        _ => None,
+    }
+}
+
+fn is_intent_event(event: &str) -> bool {
+    matches!(
+        event,
+        "SENT" | "START" | "SUCCEEDED" | "FAILED" | "FAILED_QUIET" | "INTERRUPTED"
+    )
+}
+
+fn normalize_timeline_event(entry: &Value, index: usize) -> Option<NormalizedLocalSpellEvent> {
+    let spell_id = entry.get("spellID").and_then(|v| v.as_f64())?;
+    let event = entry
+        .get("event")
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_uppercase())
+        .filter(|v| !v.is_empty())?;
+    let t = entry.get("t").and_then(|v| v.as_f64())?;
+    Some(NormalizedLocalSpellEvent {
+        id: index as i64,
+        t,
+        spell_id: spell_id as i64,
+        event,
+        cast_guid: entry
+            .get("castGUID")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        index: index as i64,
+        pseudo: None,
+    })
+}
+
+// `resolveIntentAttempts`: groups intent events into attempts by castGUID, or by spell within a
+// short window when the GUID is missing.
+fn normalize_legacy(raw_match: &Map<String, Value>) -> Option<NormalizedLocalSpellModel> {
+    let timeline = raw_match
+        .get("timeline")
+        .and_then(|v| v.as_array())
+        .filter(|timeline| !timeline.is_empty())?;
+
+    let mut raw_events: Vec<NormalizedLocalSpellEvent> = timeline
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| normalize_timeline_event(entry, index))
+        .filter(|event| is_intent_event(&event.event))
+        .collect();
+    sort_events(&mut raw_events);
+
+    let mut attempts: Vec<NormalizedLocalSpellAttempt> = Vec::new();
+    let mut open_by_guid: HashMap<String, usize> = HashMap::new();
+    let mut open_by_spell: HashMap<i64, usize> = HashMap::new();
+
+    for (idx, event) in raw_events.iter().enumerate() {
+        let guid = event.cast_guid.as_deref().filter(|guid| !guid.is_empty());
+        let existing = match guid {
+            Some(guid) => open_by_guid.get(guid),
+            None => open_by_spell.get(&event.spell_id),
+        };
+        let reusable = existing
+            .copied()
+            .filter(|at| event.t - attempts[*at].start_time <= attempts[*at].window_ms);
+
+        let at = match reusable {
+            Some(at) => at,
+            None => {
+                attempts.push(NormalizedLocalSpellAttempt {
+                    id: format!(
+                        "{}-{}-{}-{}",
+                        event.cast_guid.as_deref().unwrap_or("spell"),
+                        event.spell_id,
+                        js_number(event.t),
+                        idx
+                    ),
+                    spell_id: event.spell_id,
+                    cast_guid: event.cast_guid.clone(),
+                    start_time: event.t,
+                    end_time: event.t,
+                    window_ms: if guid.is_some() {
+                        GUID_WINDOW_MS
+                    } else {
+                        FALLBACK_WINDOW_MS
+                    },
+                    grouping: if guid.is_some() {
+                        LocalSpellAttemptGrouping::CastGuid
+                    } else {
+                        LocalSpellAttemptGrouping::Fallback
+                    },
+                    events: Vec::new(),
+                    outcomes: Vec::new(),
+                    resolved_outcome: None,
+                    round_index: None,
+                    interruptible: None,
+                    interrupted_by: None,
+                    linked_loc: None,
+                    fake_cast_stop_reason: None,
+                    target_info: None,
+                    provenance: None,
+                });
+                let at = attempts.len() - 1;
+                match guid {
+                    Some(guid) => open_by_guid.insert(guid.to_string(), at),
+                    None => open_by_spell.insert(event.spell_id, at),
+                };
+                at
+            }
+        };
+
+        let attempt = &mut attempts[at];
+        attempt.events.push(event.clone());
+        attempt.end_time = attempt.end_time.max(event.t);
+        if let Some(outcome) = classify_outcome(&event.event) {
+            if !attempt.outcomes.contains(&outcome) {
+                attempt.outcomes.push(outcome);
+            }
+            let stronger = attempt
+                .resolved_outcome
+                .is_none_or(|current| outcome_priority(outcome) > outcome_priority(current));
+            if stronger {
+                attempt.resolved_outcome = Some(outcome);
+            }
+        }
+    }
+
+    for attempt in attempts
+        .iter_mut()
+        .filter(|attempt| attempt.resolved_outcome.is_none())
+    {
+        attempt.end_time = attempt.end_time.max(attempt.start_time + START_TIMEOUT_MS);
+    }
+
+    let duration_seconds_hint = attempts
+        .iter()
+        .map(|attempt| attempt.end_time)
+        .reduce(f64::max);
+    Some(NormalizedLocalSpellModel {
+        schema_version: MODEL_SCHEMA_VERSION,
+        source_format: LocalSpellSourceFormat::LegacyTimeline,
+        detail_available: true,
+        failure_reason: None,
+        attempts,
+        events: raw_events,
+        loc_entries: Vec::new(),
+        duration_seconds_hint,
+    })
+}
+
+fn normalize_loc_entries(raw_match: &Map<String, Value>) -> Vec<NormalizedLocalLossOfControlEntry> {
+    let Some(local_loss_of_control) = raw_match.get("localLossOfControl").filter(|v| is_truthy(v))
+    else {
+        return Vec::new();
+    };
+
+    let entries = local_loss_of_control
+        .get("entries")
+        .filter(|v| !v.is_null() && local_loss_of_control.is_object());
+    let rows = to_ordered_indexed_rows(entries.or(Some(local_loss_of_control)));
+    if rows.is_empty() {
+        return Vec::new();
+    }
+
+    let SchemaRows { schema, data_rows } = resolve_schema_rows(&rows);
+    if schema.is_none() && rows.iter().any(|(_, item)| item.is_array()) {
+        return Vec::new();
+    }
+    let schema = schema.as_ref();
+
+    let mut out: Vec<NormalizedLocalLossOfControlEntry> = data_rows
+        .into_iter()
+        .map(|(raw_index, item)| {
+            let t =
+                seconds_from_centiseconds(read_field(item, schema, LocFields::TIME)).unwrap_or(0.0);
+            let duration = seconds_from_centiseconds(read_field(item, schema, LocFields::DURATION));
+            let school = read_field(item, schema, LocFields::SCHOOL)
+                .filter(|v| v.is_string() || v.is_number())
+                .cloned();
+            NormalizedLocalLossOfControlEntry {
+                id: raw_index,
+                t,
+                duration,
+                end_time: Some(duration.map_or(t, |duration| t + duration)),
+                round_index: to_integer(read_field(item, schema, LocFields::ROUND_INDEX)),
+                loc_type: to_trimmed_string(read_field(item, schema, LocFields::LOC_TYPE)),
+                display_text: to_trimmed_string(read_field(item, schema, LocFields::DISPLAY_TEXT)),
+                issued_by_guid: to_trimmed_string(read_field(
+                    item,
+                    schema,
+                    LocFields::ISSUED_BY_GUID,
+                )),
+                aura_instance_id: to_integer(read_field(item, schema, LocFields::AURA_INSTANCE_ID)),
+                school,
+                raw: Some(item.clone()),
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| by_time_then(a.t, b.t, || a.id.cmp(&b.id)));
+    out
+}
+
+struct PseudoEvents<'a> {
+    cursor: &'a mut i64,
+    spell_id: i64,
+    cast_guid: Option<&'a str>,
+}
+
+impl PseudoEvents<'_> {
+    // Stand-in events for a cast row: its first event at the start, then the terminal event (or
+    // one implied by the outcome) at the end.
+    fn build(
+        &mut self,
+        start_time: f64,
+        end_time: f64,
+        first_event: Option<&str>,
+        terminal_event: Option<&str>,
+        outcome: Option<SpellOutcomeResult>,
+    ) -> Vec<NormalizedLocalSpellEvent> {
+        let mut events: Vec<NormalizedLocalSpellEvent> = Vec::new();
+        let mut create = |events: &mut Vec<NormalizedLocalSpellEvent>, t: f64, event: &str| {
+            let id = *self.cursor;
+            *self.cursor += 1;
+            events.push(NormalizedLocalSpellEvent {
+                id,
+                t,
+                spell_id: self.spell_id,
+                event: event.to_string(),
+                cast_guid: self.cast_guid.map(str::to_string),
+                index: id,
+                pseudo: Some(true),
+            });
+        };
+
+        if let Some(first_event) = first_event {
+            create(&mut events, start_time, first_event);
+        }
+
+        let resolved_event = terminal_event.or(match outcome {
+            Some(SpellOutcomeResult::Succeeded) => Some("SUCCEEDED"),
+            Some(SpellOutcomeResult::Interrupted) => Some("INTERRUPTED"),
+            Some(SpellOutcomeResult::Failed) => Some("FAILED"),
+            None => None,
+        });
+        if let Some(resolved_event) = resolved_event {
+            let repeated = events
+                .last()
+                .is_some_and(|last| last.event == resolved_event && last.t == end_time);
+            if !repeated {
+                create(&mut events, end_time, resolved_event);
+            }
+        }
+        events
+    }
+}
+
+fn target_info(item: &Value, schema: Option<&SchemaIndex>) -> Option<Value> {
+    let target_name = to_trimmed_string(read_field(item, schema, &["targetName"]));
+    let target_disposition = read_field(item, schema, &["targetDisposition"]);
+    let target_is_player = to_boolean(read_field(item, schema, &["targetIsPlayer"]));
+    let target_can_attack = to_boolean(read_field(item, schema, &["targetCanAttack"]));
+    let target_is_friend = to_boolean(read_field(item, schema, &["targetIsFriend"]));
+    let target_reaction = to_integer(read_field(item, schema, &["targetReaction"]));
+
+    let has_snapshot = target_name.is_some()
+        || target_disposition.is_some()
+        || target_is_player.is_some()
+        || target_can_attack.is_some()
+        || target_is_friend.is_some()
+        || target_reaction.is_some();
+    if !has_snapshot {
+        return read_field(item, schema, CastFields::TARGET_INFO).cloned();
+    }
+
+    let mut snapshot = json!({
+        "targetName": target_name,
+        "targetIsPlayer": target_is_player,
+        "targetCanAttack": target_can_attack,
+        "targetIsFriend": target_is_friend,
+        "targetReaction": target_reaction,
+    });
+    if let (Some(map), Some(disposition)) = (snapshot.as_object_mut(), target_disposition) {
+        map.insert("targetDisposition".to_string(), disposition.clone());
+    }
+    Some(snapshot)
+}
+
+fn normalize_cast_row(
+    item: &Value,
+    raw_index: i64,
+    schema: Option<&SchemaIndex>,
+    group_spell_id: Option<i64>,
+    attempt_count: usize,
+    cursor: &mut i64,
+) -> Option<NormalizedLocalSpellAttempt> {
+    let spell_id = to_integer(read_field(item, schema, CastFields::SPELL_ID))
+        .or(group_spell_id)
+        .filter(|id| *id > 0)?;
+    let cast_guid = to_trimmed_string(read_field(item, schema, CastFields::CAST_GUID));
+
+    let explicit_time = seconds_from_centiseconds(read_field(item, schema, CastFields::TIME));
+    let explicit_start =
+        seconds_from_centiseconds(read_field(item, schema, CastFields::START_TIME));
+    let explicit_end = seconds_from_centiseconds(read_field(item, schema, CastFields::END_TIME));
+    let explicit_duration =
+        seconds_from_centiseconds(read_field(item, schema, CastFields::DURATION));
+    let start_time = explicit_start
+        .or(explicit_time)
+        .or(explicit_end)
+        .unwrap_or(0.0);
+    let end_time = explicit_end.unwrap_or_else(|| match explicit_duration {
+        Some(duration) => start_time + duration,
+        None => explicit_time.unwrap_or(start_time),
+    });
+    let end_time = if end_time >= start_time {
+        end_time
+    } else {
+        start_time
+    };
+
+    let first_event = normalize_event_name(read_field(item, schema, CastFields::FIRST_EVENT));
+    let terminal_event = normalize_event_name(read_field(item, schema, CastFields::TERMINAL_EVENT));
+    let outcome = normalize_outcome(read_field(item, schema, CastFields::OUTCOME))
+        .or_else(|| terminal_event.as_deref().and_then(normalize_outcome_str));
+
+    let stop_reason = read_field(item, schema, CastFields::FAKE_CAST_STOP_REASON);
+    let fake_cast_stop_reason = to_trimmed_string(stop_reason).or_else(|| {
+        stop_reason
+            .and_then(|v| v.as_f64())
+            .filter(|v| *v > 0.0)
+            .map(|v| format!("manualStopReason:{}", v.trunc() as i64))
+    });
+    let provenance = coerce_string_array(read_field(item, schema, CastFields::PROVENANCE));
+
+    let events = PseudoEvents {
+        cursor,
+        spell_id,
+        cast_guid: cast_guid.as_deref(),
+    }
+    .build(
+        start_time,
+        end_time,
+        first_event.as_deref(),
+        terminal_event.as_deref(),
+        outcome,
+    );
+
+    Some(NormalizedLocalSpellAttempt {
+        id: cast_guid
+            .clone()
+            .unwrap_or_else(|| format!("v4:{spell_id}:{raw_index}:{attempt_count}")),
+        spell_id,
+        grouping: if cast_guid.is_some() {
+            LocalSpellAttemptGrouping::CastGuid
+        } else {
+            LocalSpellAttemptGrouping::Normalized
+        },
+        cast_guid,
+        start_time,
+        end_time,
+        window_ms: (end_time - start_time).max(0.0),
+        events,
+        outcomes: outcome.into_iter().collect(),
+        resolved_outcome: outcome,
+        round_index: to_integer(read_field(item, schema, CastFields::ROUND_INDEX)),
+        interruptible: to_boolean(read_field(item, schema, CastFields::INTERRUPTIBLE)),
+        interrupted_by: read_field(item, schema, CastFields::INTERRUPTED_BY).cloned(),
+        linked_loc: Some(coerce_linked_loc(read_field(
+            item,
+            schema,
+            CastFields::LINKED_LOC,
+        ))),
+        fake_cast_stop_reason,
+        target_info: target_info(item, schema),
+        provenance: (!provenance.is_empty()).then_some(provenance),
+    })
+}
+
+fn normalize_v4(raw_match: &Map<String, Value>) -> Option<NormalizedLocalSpellModel> {
+    let capture = raw_match
+        .get("localSpellCapture")
+        .filter(|v| v.is_array() || v.is_object())?;
+    let capture_root = capture
+        .get("bySpellID")
+        .filter(|v| capture.is_object() && v.is_object())
+        .unwrap_or(capture);
+
+    let groups: Vec<(String, &Value)> = match capture_root {
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (index.to_string(), item))
+            .collect(),
+        Value::Object(map) => js_entries(map)
+            .into_iter()
+            .map(|(key, value)| (key.clone(), value))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let loc_entries = normalize_loc_entries(raw_match);
+    let mut attempts: Vec<NormalizedLocalSpellAttempt> = Vec::new();
+    let mut events: Vec<NormalizedLocalSpellEvent> = Vec::new();
+    let mut cursor: i64 = 0;
+    let mut unsupported_array_group = false;
+
+    for (group_key, group_value) in groups {
+        if group_key == "schema" || group_key == "fields" {
+            continue;
+        }
+
+        let group = group_value.as_object();
+        let casts = group
+            .and_then(|group| group.get("casts"))
+            .filter(|v| !v.is_null())
+            .unwrap_or(group_value);
+        let cast_rows = to_ordered_indexed_rows(Some(casts));
+        if cast_rows.is_empty() {
+            continue;
+        }
+
+        let SchemaRows { schema, data_rows } = resolve_schema_rows(&cast_rows);
+        if schema.is_none() && cast_rows.iter().any(|(_, item)| item.is_array()) {
+            unsupported_array_group = true;
+            continue;
+        }
+
+        let group_key_value = Value::String(group_key);
+        let group_spell_id = to_integer(
+            group
+                .and_then(|group| first_present(group, &["spellID", "spellId"]))
+                .or(Some(&group_key_value)),
+        );
+
+        for (raw_index, item) in data_rows {
+            if let Some(attempt) = normalize_cast_row(
+                item,
+                raw_index,
+                schema.as_ref(),
+                group_spell_id,
+                attempts.len(),
+                &mut cursor,
+            ) {
+                events.extend(attempt.events.iter().cloned());
+                attempts.push(attempt);
+            }
+        }
+    }
+
+    attempts
+        .sort_by(|a, b| by_time_then(a.start_time, b.start_time, || locale_compare(&a.id, &b.id)));
+    sort_events(&mut events);
+    let duration_seconds_hint = attempts
+        .iter()
+        .map(|attempt| attempt.end_time)
+        .chain(
+            loc_entries
+                .iter()
+                .map(|entry| entry.end_time.unwrap_or(entry.t)),
+        )
+        .filter(|value| value.is_finite() && *value > 0.0)
+        .reduce(f64::max);
+
+    Some(NormalizedLocalSpellModel {
+        schema_version: MODEL_SCHEMA_VERSION,
+        source_format: LocalSpellSourceFormat::V4LocalSpellCapture,
+        detail_available: !attempts.is_empty() || !loc_entries.is_empty(),
+        failure_reason: (attempts.is_empty() && unsupported_array_group)
+            .then(|| UNSUPPORTED_V4_SCHEMA.to_string()),
+        attempts,
+        events,
+        loc_entries,
+        duration_seconds_hint,
+    })
+}
+
+fn coerce_persisted_event(value: &Value) -> Option<NormalizedLocalSpellEvent> {
+    let map = value.as_object()?;
+    let id = to_integer(map.get("id")).or_else(|| to_integer(map.get("index")))?;
+    let spell_id = to_integer(map.get("spellId"))?;
+    let event = to_trimmed_string(map.get("event"))?;
+    Some(NormalizedLocalSpellEvent {
+        id,
+        t: to_finite_number(map.get("t")).unwrap_or(0.0),
+        spell_id,
+        event,
+        cast_guid: to_trimmed_string(first_present(map, &["castGUID", "castGuid"])),
+        index: to_integer(map.get("index")).unwrap_or(id),
+        pseudo: to_boolean(map.get("pseudo")),
+    })
+}
+
+fn coerce_persisted_attempt(value: &Value) -> Option<NormalizedLocalSpellAttempt> {
+    let map = value.as_object()?;
+    let id = to_trimmed_string(map.get("id"))?;
+    let spell_id = to_integer(map.get("spellId")).filter(|id| *id > 0)?;
+
+    let events = map
+        .get("events")
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(coerce_persisted_event).collect())
+        .unwrap_or_default();
+    let outcomes: Vec<SpellOutcomeResult> = map
+        .get("outcomes")
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| normalize_outcome(Some(item)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let resolved_outcome =
+        normalize_outcome(map.get("resolvedOutcome")).or(outcomes.first().copied());
+    let grouping = match map.get("grouping").and_then(|v| v.as_str()) {
+        Some("castGUID") => LocalSpellAttemptGrouping::CastGuid,
+        Some("fallback") => LocalSpellAttemptGrouping::Fallback,
+        _ => LocalSpellAttemptGrouping::Normalized,
+    };
+
+    Some(NormalizedLocalSpellAttempt {
+        id,
+        spell_id,
+        cast_guid: to_trimmed_string(first_present(map, &["castGUID", "castGuid"])),
+        start_time: to_finite_number(map.get("startTime")).unwrap_or(0.0),
+        end_time: to_finite_number(map.get("endTime")).unwrap_or(0.0),
+        window_ms: to_finite_number(map.get("windowMs")).unwrap_or(0.0),
+        grouping,
+        events,
+        outcomes,
+        resolved_outcome,
+        round_index: to_integer(map.get("roundIndex")),
+        interruptible: to_boolean(map.get("interruptible")),
+        interrupted_by: map.get("interruptedBy").cloned(),
+        linked_loc: Some(coerce_linked_loc(map.get("linkedLoc"))),
+        fake_cast_stop_reason: to_trimmed_string(map.get("fakeCastStopReason")),
+        target_info: map.get("targetInfo").cloned(),
+        provenance: Some(coerce_string_array(map.get("provenance"))),
+    })
+}
+
+fn coerce_persisted_loc_entry(value: &Value) -> Option<NormalizedLocalLossOfControlEntry> {
+    let map = value.as_object()?;
+    let id = to_integer(map.get("id")).filter(|id| *id >= 0)?;
+    Some(NormalizedLocalLossOfControlEntry {
+        id,
+        t: to_finite_number(map.get("t")).unwrap_or(0.0),
+        duration: to_finite_number(map.get("duration")),
+        end_time: to_finite_number(map.get("endTime")),
+        round_index: to_integer(map.get("roundIndex")),
+        loc_type: to_trimmed_string(map.get("locType")),
+        display_text: to_trimmed_string(map.get("displayText")),
+        issued_by_guid: to_trimmed_string(map.get("issuedByGuid")),
+        aura_instance_id: to_integer(map.get("auraInstanceId")),
+        school: map
+            .get("school")
+            .filter(|v| v.is_string() || v.is_number())
+            .cloned(),
+        raw: map.get("raw").cloned(),
+    })
+}
+
+fn coerce_list<T>(
+    model: &Map<String, Value>,
+    key: &str,
+    coerce: fn(&Value) -> Option<T>,
+) -> Vec<T> {
+    model
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(coerce).collect())
+        .unwrap_or_default()
+}
+
+fn read_persisted(raw_match: &Map<String, Value>) -> Option<NormalizedLocalSpellModel> {
+    let model = raw_match
+        .get("computed")
+        .and_then(|computed| computed.get("localSpellModel"))
+        .and_then(|model| model.as_object())?;
+
+    let attempts = coerce_list(model, "attempts", coerce_persisted_attempt);
+    let mut events = coerce_list(model, "events", coerce_persisted_event);
+    let loc_entries = coerce_list(model, "locEntries", coerce_persisted_loc_entry);
+    if events.is_empty() {
+        events = attempts
+            .iter()
+            .flat_map(|attempt| attempt.events.iter().cloned())
+            .collect();
+        sort_events(&mut events);
+    }
+    let source_format = match to_trimmed_string(model.get("sourceFormat")).as_deref() {
+        Some("legacy-timeline") => LocalSpellSourceFormat::LegacyTimeline,
+        Some("v4-local-spell-capture") => LocalSpellSourceFormat::V4LocalSpellCapture,
+        _ => LocalSpellSourceFormat::PersistedNormalizedV2,
+    };
+
+    Some(NormalizedLocalSpellModel {
+        schema_version: MODEL_SCHEMA_VERSION,
+        source_format,
+        detail_available: to_boolean(model.get("detailAvailable")).unwrap_or(false)
+            || !attempts.is_empty()
+            || !loc_entries.is_empty(),
+        failure_reason: to_trimmed_string(model.get("failureReason")),
+        attempts,
+        events,
+        loc_entries,
+        duration_seconds_hint: to_finite_number(model.get("durationSecondsHint")),
+    })
+}
+
+/// The match's spell model: the persisted `computed.localSpellModel` when present, otherwise
+/// normalized from the v4 `localSpellCapture`, otherwise from the legacy `timeline`.
+pub fn resolve_local_spell_model(raw_match: &Value) -> Option<NormalizedLocalSpellModel> {
+    let raw_match = raw_match.as_object()?;
+    read_persisted(raw_match)
+        .or_else(|| normalize_v4(raw_match))
+        .or_else(|| normalize_legacy(raw_match))
+}
+
+/// Resolved attempts per spell id.
+pub fn build_spell_outcome_counts(
+    model: &NormalizedLocalSpellModel,
+) -> BTreeMap<i64, ComputedSpellOutcomeCounts> {
+    let mut out: BTreeMap<i64, ComputedSpellOutcomeCounts> = BTreeMap::new();
+    for attempt in &model.attempts {
+        let Some(outcome) = attempt.resolved_outcome else {
+            continue;
+        };
+        let row = out.entry(attempt.spell_id).or_default();
+        match outcome {
+            SpellOutcomeResult::Succeeded => row.succeeded += 1,
+            SpellOutcomeResult::Interrupted => row.interrupted += 1,
+            SpellOutcomeResult::Failed => row.failed += 1,
+        }
+    }
+    out
+}
+
+/// Stores the resolved model and its outcome counts under `computed`, next to the raw capture
+/// payloads, which stay so the model can be rebuilt when normalization changes. The owner kick
+/// summary the webview computed is carried over. Entries without spell data are left untouched.
+pub fn attach_to_entry(entry: &mut Value) {
+    let Some(model) = resolve_local_spell_model(entry) else {
+        return;
+    };
+    let Some(root) = entry.as_object_mut() else {
+        return;
+    };
+
+    let computed = ComputedAnalyticsV2 {
+        schema_version: MODEL_SCHEMA_VERSION,
+        spell_outcomes_by_spell_id: Some(build_spell_outcome_counts(&model)),
+        owner_kicks: root
+            .get("computed")
+            .and_then(|computed| computed.get("ownerKicks"))
+            .and_then(|kicks| serde_json::from_value(kicks.clone()).ok()),
+        local_spell_model: Some(model),
+    };
+    let Ok(computed) = serde_json::to_value(computed) else {
+        return;
+    };
+    root.insert("computed".to_string(), computed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{attach_to_entry, build_spell_outcome_counts, resolve_local_spell_model};
+    use serde_json::{json, Map, Value};
+
+    // Input and the frontend's output for it, as `{ localSpellModel, spellOutcomesBySpellId }`.
+    // Regenerate the expected files from `src/Domain/localSpellModel.ts` when it changes.
+    const PARITY: [(&str, &str, &str); 3] = [
+        (
+            "legacy-timeline",
+            include_str!("../fixtures/local_spell_model/legacy_timeline.input.json"),
+            include_str!("../fixtures/local_spell_model/legacy_timeline.expected.json"),
+        ),
+        (
+            "v4-local-spell-capture",
+            include_str!("../fixtures/local_spell_model/v4_local_spell_capture.input.json"),
+            include_str!("../fixtures/local_spell_model/v4_local_spell_capture.expected.json"),
+        ),
+        (
+            "persisted-normalized-v2",
+            include_str!("../fixtures/local_spell_model/persisted_normalized_v2.input.json"),
+            include_str!("../fixtures/local_spell_model/persisted_normalized_v2.expected.json"),
+        ),
+    ];
+
+    // The frontend writes `null` where the backend omits the field, whole numbers may come out
+    // as either integers or floats, and float parsing may be off in the last bit.
+    fn comparable(value: &Value) -> Value {
+        match value {
+            Value::Number(number) => json!(number.as_f64().map(|v| (v * 1e9).round() / 1e9)),
+            Value::Array(items) => Value::Array(items.iter().map(comparable).collect()),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .filter(|(_, value)| !value.is_null())
+                    .map(|(key, value)| (key.clone(), comparable(value)))
+                    .collect::<Map<String, Value>>(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn parse(raw: &str) -> Value {
+        serde_json::from_str(raw).expect("fixture is valid JSON")
+    }
+
+    #[test]
+    fn matches_frontend_output() {
+        for (source_format, input, expected) in PARITY {
+            let model = resolve_local_spell_model(&parse(input)).expect("model resolves");
+            let actual = json!({
+                "localSpellModel": model,
+                "spellOutcomesBySpellId": build_spell_outcome_counts(&model),
+            });
+            assert_eq!(json!(model.source_format), json!(source_format));
+            assert_eq!(
+                comparable(&actual),
+                comparable(&parse(expected)),
+                "{source_format}"
+            );
+        }
+    }
+
+    #[test]
+    fn attached_model_is_read_back_from_computed() {
+        let raw = parse(PARITY[1].1);
+        let mut entry = raw.clone();
+        let model = resolve_local_spell_model(&entry).expect("model resolves");
+        attach_to_entry(&mut entry);
+
+        assert_eq!(entry["localSpellCapture"], raw["localSpellCapture"]);
+        assert_eq!(
+            entry.get("localLossOfControl"),
+            raw.get("localLossOfControl")
+        );
+        assert_eq!(entry["computed"]["schemaVersion"], json!(2));
+        assert_eq!(
+            entry["computed"]["spellOutcomesBySpellId"],
+            json!(build_spell_outcome_counts(&model))
+        );
+
+        let stored = resolve_local_spell_model(&entry).expect("stored model resolves");
+        assert_eq!(stored.source_format, model.source_format);
+        assert_eq!(stored.attempts.len(), model.attempts.len());
+        assert_eq!(
+            build_spell_outcome_counts(&stored),
+            build_spell_outcome_counts(&model)
+        );
+    }
+
+    #[test]
+    fn entries_without_spell_data_are_untouched() {
+        let mut entry = json!({ "matchKey": "k", "players": [] });
+        attach_to_entry(&mut entry);
+        assert_eq!(entry, json!({ "matchKey": "k", "players": [] }));
+    }
+}
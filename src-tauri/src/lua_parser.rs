@@ -0,0 +1,953 @@
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use std::fmt;
+use ts_rs::TS;
+
+// Nesting guard so a corrupt file cannot blow the stack.
+const MAX_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../src/Interfaces/LuaParseErrorKind.ts")]
+pub enum LuaParseErrorKind {
+    UnexpectedEof,
+    UnexpectedToken,
+    UnterminatedString,
+    InvalidEscape,
+    InvalidNumber,
+    InvalidKey,
+    TooDeep,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/LuaParseError.ts")]
+pub struct LuaParseError {
+    pub kind: LuaParseErrorKind,
+    pub message: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for LuaParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (line {}, column {})",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for LuaParseError {}
+
+/// Table key with its Lua type. Integral numbers are indices; fractional ones keep their value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LuaKey {
+    Index(i64),
+    Name(String),
+    Bool(bool),
+    Number(Number),
+}
+
+/// Structural view of a parsed value: values, key types and field order survive, so writing a
+/// table back yields the same data. Source formatting does not: numbers are re-emitted in their
+/// shortest form (`1.50` becomes `1.5`). Keyed `nil` fields are dropped as Lua itself does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LuaValue {
+    Nil,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Table(Vec<(LuaKey, LuaValue)>),
+}
+
+impl LuaValue {
+    pub fn to_json(&self) -> Value {
+        match self {
+            LuaValue::Nil => Value::Null,
+            LuaValue::Bool(flag) => Value::Bool(*flag),
+            LuaValue::Number(number) => Value::Number(number.clone()),
+            LuaValue::String(text) => Value::String(text.clone()),
+            LuaValue::Table(fields) => table_to_json(fields),
+        }
+    }
+
+    /// Sets `key` on a table, replacing an existing field in place or appending a new one.
+    pub fn set_field(&mut self, key: LuaKey, value: LuaValue) -> bool {
+        let LuaValue::Table(fields) = self else {
+            return false;
+        };
+        match fields.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, slot)) => *slot = value,
+            None => fields.push((key, value)),
+        }
+        true
+    }
+
+    /// Value of the string-keyed field `name`, when this is a table that has it.
+    pub fn field(&self, name: &str) -> Option<&LuaValue> {
+        let LuaValue::Table(fields) = self else {
+            return None;
+        };
+        fields
+            .iter()
+            .find(|(key, _)| matches!(key, LuaKey::Name(existing) if existing == name))
+            .map(|(_, value)| value)
+    }
+
+    pub fn field_mut(&mut self, name: &str) -> Option<&mut LuaValue> {
+        let LuaValue::Table(fields) = self else {
+            return None;
+        };
+        fields
+            .iter_mut()
+            .find(|(key, _)| matches!(key, LuaKey::Name(existing) if existing == name))
+            .map(|(_, value)| value)
+    }
+}
+
+/// A top-level `name = value` assignment and its byte range in the source.
+pub struct LuaRoot {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    pub value: LuaValue,
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            bytes: src.as_bytes(),
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn error_at(
+        &self,
+        pos: usize,
+        kind: LuaParseErrorKind,
+        message: impl Into<String>,
+    ) -> LuaParseError {
+        // Line/column are only resolved on failure, so the happy path never pays for them.
+        let pos = pos.min(self.bytes.len());
+        let consumed = &self.src[..floor_char_boundary(self.src, pos)];
+        let line = consumed.matches('\n').count() + 1;
+        let line_start = consumed.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+        let column = consumed[line_start..].chars().count() + 1;
+
+        LuaParseError {
+            kind,
+            message: message.into(),
+            line: line as u32,
+            column: column as u32,
+        }
+    }
+
+    fn error(&self, kind: LuaParseErrorKind, message: impl Into<String>) -> LuaParseError {
+        self.error_at(self.pos, kind, message)
+    }
+
+    fn eof(&self, context: &str) -> LuaParseError {
+        self.error(
+            LuaParseErrorKind::UnexpectedEof,
+            format!("Unexpected end of input while reading {context}"),
+        )
+    }
+
+    fn unexpected(&self, expected: &str) -> LuaParseError {
+        let found = self.src[self.pos..].chars().next().unwrap_or('\0');
+        self.error(
+            LuaParseErrorKind::UnexpectedToken,
+            format!("Expected {expected}, found '{found}'"),
+        )
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(self.pos + offset).copied()
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), LuaParseError> {
+        while let Some(ch) = self.peek() {
+            if ch.is_ascii_whitespace() {
+                self.pos += 1;
+                continue;
+            }
+
+            if ch == b'-' && self.peek_at(1) == Some(b'-') {
+                let comment_start = self.pos;
+                self.pos += 2;
+                if let Some(level) = self.long_bracket_level() {
+                    self.skip_long_bracket(level).ok_or_else(|| {
+                        self.error_at(
+                            comment_start,
+                            LuaParseErrorKind::UnexpectedEof,
+                            "Unterminated block comment",
+                        )
+                    })?;
+                } else {
+                    while let Some(next) = self.peek() {
+                        if next == b'\n' {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                }
+                continue;
+            }
+
+            break;
+        }
+
+        Ok(())
+    }
+
+    // Returns the `=` count of a `[[` / `[==[` opener at the cursor and consumes it.
+    fn long_bracket_level(&mut self) -> Option<usize> {
+        if self.peek() != Some(b'[') {
+            return None;
+        }
+        let mut level = 0;
+        while self.peek_at(1 + level) == Some(b'=') {
+            level += 1;
+        }
+        if self.peek_at(1 + level) != Some(b'[') {
+            return None;
+        }
+        self.pos += level + 2;
+        Some(level)
+    }
+
+    fn skip_long_bracket(&mut self, level: usize) -> Option<()> {
+        while self.pos < self.bytes.len() {
+            if self.bytes[self.pos] == b']' {
+                let mut eqs = 0;
+                while self.peek_at(1 + eqs) == Some(b'=') {
+                    eqs += 1;
+                }
+                if eqs == level && self.peek_at(1 + eqs) == Some(b']') {
+                    self.pos += eqs + 2;
+                    return Some(());
+                }
+            }
+            self.pos += 1;
+        }
+        None
+    }
+
+    fn expect(&mut self, byte: u8, expected: &str) -> Result<(), LuaParseError> {
+        self.skip_trivia()?;
+        match self.peek() {
+            Some(ch) if ch == byte => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(_) => Err(self.unexpected(expected)),
+            None => Err(self.eof(expected)),
+        }
+    }
+
+    fn read_identifier(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        match self.peek() {
+            Some(ch) if ch.is_ascii_alphabetic() || ch == b'_' => self.pos += 1,
+            _ => return None,
+        }
+        while let Some(ch) = self.peek() {
+            if ch.is_ascii_alphanumeric() || ch == b'_' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        Some(&self.src[start..self.pos])
+    }
+
+    fn parse_chunk(&mut self, wanted: &[&str]) -> Result<Vec<LuaRoot>, LuaParseError> {
+        let mut roots = Vec::new();
+
+        loop {
+            self.skip_trivia()?;
+            if self.peek().is_none() {
+                break;
+            }
+
+            let name_start = self.pos;
+            let name = self
+                .read_identifier()
+                .ok_or_else(|| self.unexpected("a global name"))?;
+            if is_keyword(name) {
+                return Err(self.error_at(
+                    name_start,
+                    LuaParseErrorKind::UnexpectedToken,
+                    format!("Expected a global name, found keyword '{name}'"),
+                ));
+            }
+
+            self.expect(b'=', "'='")?;
+            let value = self.parse_value()?;
+            let end = self.pos;
+
+            self.skip_trivia()?;
+            if self.peek() == Some(b';') {
+                self.pos += 1;
+            }
+
+            if wanted.contains(&name) {
+                roots.push(LuaRoot {
+                    name: name.to_string(),
+                    start: name_start,
+                    end,
+                    value,
+                });
+            }
+        }
+
+        Ok(roots)
+    }
+
+    fn parse_value(&mut self) -> Result<LuaValue, LuaParseError> {
+        self.skip_trivia()?;
+        let Some(ch) = self.peek() else {
+            return Err(self.eof("a value"));
+        };
+
+        match ch {
+            b'{' => self.parse_table(),
+            b'"' | b'\'' => self.parse_string().map(LuaValue::String),
+            b'[' => self.parse_long_string().map(LuaValue::String),
+            b'-' | b'.' | b'0'..=b'9' => self.parse_number(),
+            _ => {
+                let start = self.pos;
+                match self.read_identifier() {
+                    Some("true") => Ok(LuaValue::Bool(true)),
+                    Some("false") => Ok(LuaValue::Bool(false)),
+                    Some("nil") => Ok(LuaValue::Nil),
+                    _ => {
+                        self.pos = start;
+                        Err(self.unexpected("a value"))
+                    }
+                }
+            }
+        }
+    }
+
+    fn parse_table(&mut self) -> Result<LuaValue, LuaParseError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(LuaParseErrorKind::TooDeep, "Table nesting is too deep"));
+        }
+        self.depth += 1;
+        self.pos += 1; // '{'
+
+        let mut fields: Vec<(LuaKey, LuaValue)> = Vec::new();
+        let mut next_index: i64 = 1;
+
+        loop {
+            self.skip_trivia()?;
+            match self.peek() {
+                None => return Err(self.eof("a table")),
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => {}
+            }
+
+            if let Some(key) = self.parse_field_key()? {
+                let value = self.parse_value()?;
+                // `key = nil` removes the key in Lua, so it never reaches the output.
+                if !matches!(value, LuaValue::Nil) {
+                    fields.push((key, value));
+                }
+            } else {
+                let value = self.parse_value()?;
+                fields.push((LuaKey::Index(next_index), value));
+                next_index += 1;
+            }
+
+            self.skip_trivia()?;
+            match self.peek() {
+                Some(b',') | Some(b';') => self.pos += 1,
+                Some(b'}') => {}
+                Some(_) => return Err(self.unexpected("',' or '}'")),
+                None => return Err(self.eof("a table")),
+            }
+        }
+
+        self.depth -= 1;
+        Ok(LuaValue::Table(fields))
+    }
+
+    // Consumes `[key] =` or `name =` when present; positional fields leave the cursor untouched.
+    fn parse_field_key(&mut self) -> Result<Option<LuaKey>, LuaParseError> {
+        let start = self.pos;
+
+        // `[[` and `[=` open a long string, which can only be a positional value.
+        if self.peek() == Some(b'[') && !matches!(self.peek_at(1), Some(b'[' | b'=')) {
+            self.pos += 1;
+            self.skip_trivia()?;
+            let key_start = self.pos;
+            let key = match self.parse_value()? {
+                LuaValue::String(name) => LuaKey::Name(name),
+                LuaValue::Bool(flag) => LuaKey::Bool(flag),
+                LuaValue::Number(number) => number_key(&number),
+                LuaValue::Nil => {
+                    return Err(self.error_at(
+                        key_start,
+                        LuaParseErrorKind::InvalidKey,
+                        "Table key cannot be nil",
+                    ))
+                }
+                _ => {
+                    return Err(self.error_at(
+                        key_start,
+                        LuaParseErrorKind::InvalidKey,
+                        "Table key must be a string, number or boolean",
+                    ))
+                }
+            };
+            self.expect(b']', "']'")?;
+            self.expect(b'=', "'='")?;
+            return Ok(Some(key));
+        }
+
+        if let Some(name) = self.read_identifier() {
+            if !is_keyword(name) {
+                self.skip_trivia()?;
+                if self.peek() == Some(b'=') && self.peek_at(1) != Some(b'=') {
+                    self.pos += 1;
+                    return Ok(Some(LuaKey::Name(name.to_string())));
+                }
+            }
+        }
+
+        self.pos = start;
+        Ok(None)
+    }
+
+    fn parse_string(&mut self) -> Result<String, LuaParseError> {
+        let open = self.pos;
+        let quote = self.bytes[self.pos];
+        self.pos += 1;
+
+        // Lua strings are byte strings; decimal escapes may spell out UTF-8 sequences.
+        let mut out: Vec<u8> = Vec::new();
+
+        loop {
+            let Some(ch) = self.peek() else {
+                return Err(self.error_at(
+                    open,
+                    LuaParseErrorKind::UnterminatedString,
+                    "Unterminated string",
+                ));
+            };
+
+            match ch {
+                _ if ch == quote => {
+                    self.pos += 1;
+                    break;
+                }
+                b'\n' => {
+                    return Err(self.error_at(
+                        open,
+                        LuaParseErrorKind::UnterminatedString,
+                        "Unterminated string",
+                    ))
+                }
+                b'\\' => {
+                    self.pos += 1;
+                    self.parse_escape(&mut out)?;
+                }
+                _ => {
+                    out.push(ch);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    // `[[...]]` / `[==[...]==]`: no escapes, and a line break right after the opener is dropped.
+    fn parse_long_string(&mut self) -> Result<String, LuaParseError> {
+        let open = self.pos;
+        let Some(level) = self.long_bracket_level() else {
+            return Err(self.unexpected("a value"));
+        };
+        match self.peek() {
+            Some(b'\r') => {
+                self.pos += 1;
+                if self.peek() == Some(b'\n') {
+                    self.pos += 1;
+                }
+            }
+            Some(b'\n') => {
+                self.pos += 1;
+                if self.peek() == Some(b'\r') {
+                    self.pos += 1;
+                }
+            }
+            _ => {}
+        }
+
+        let content_start = self.pos;
+        self.skip_long_bracket(level).ok_or_else(|| {
+            self.error_at(
+                open,
+                LuaParseErrorKind::UnterminatedString,
+                "Unterminated long string",
+            )
+        })?;
+        Ok(self.src[content_start..self.pos - level - 2].to_string())
+    }
+
+    fn parse_escape(&mut self, out: &mut Vec<u8>) -> Result<(), LuaParseError> {
+        let escape_start = self.pos - 1;
+        let Some(ch) = self.peek() else {
+            return Err(self.eof("an escape sequence"));
+        };
+        self.pos += 1;
+
+        match ch {
+            b'n' => out.push(b'\n'),
+            b't' => out.push(b'\t'),
+            b'r' => out.push(b'\r'),
+            b'a' => out.push(0x07),
+            b'b' => out.push(0x08),
+            b'f' => out.push(0x0c),
+            b'v' => out.push(0x0b),
+            b'\\' => out.push(b'\\'),
+            b'"' => out.push(b'"'),
+            b'\'' => out.push(b'\''),
+            // `%q` writes embedded newlines as a backslash followed by a real line break.
+            b'\n' => {
+                out.push(b'\n');
+                if self.peek() == Some(b'\r') {
+                    self.pos += 1;
+                }
+            }
+            b'\r' => {
+                out.push(b'\n');
+                if self.peek() == Some(b'\n') {
+                    self.pos += 1;
+                }
+            }
+            b'z' => {
+                while matches!(self.peek(), Some(next) if next.is_ascii_whitespace()) {
+                    self.pos += 1;
+                }
+            }
+            b'x' => {
+                let digits = self.src.get(self.pos..self.pos + 2).unwrap_or("");
+                let byte = u8::from_str_radix(digits, 16).map_err(|_| {
+                    self.error_at(
+                        escape_start,
+                        LuaParseErrorKind::InvalidEscape,
+                        "Invalid hexadecimal escape",
+                    )
+                })?;
+                out.push(byte);
+                self.pos += 2;
+            }
+            b'0'..=b'9' => {
+                let mut code: u32 = u32::from(ch - b'0');
+                for _ in 0..2 {
+                    match self.peek() {
+                        Some(digit @ b'0'..=b'9') => {
+                            code = code * 10 + u32::from(digit - b'0');
+                            self.pos += 1;
+                        }
+                        _ => break,
+                    }
+                }
+                let byte = u8::try_from(code).map_err(|_| {
+                    self.error_at(
+                        escape_start,
+                        LuaParseErrorKind::InvalidEscape,
+                        "Decimal escape is out of range",
+                    )
+                })?;
+                out.push(byte);
+            }
+            b'u' => {
+                let invalid = |parser: &Self| {
+                    parser.error_at(
+                        escape_start,
+                        LuaParseErrorKind::InvalidEscape,
+                        "Invalid unicode escape",
+                    )
+                };
+                if self.peek() != Some(b'{') {
+                    return Err(invalid(self));
+                }
+                let close = self.src[self.pos..]
+                    .find('}')
+                    .ok_or_else(|| invalid(self))?;
+                let digits = &self.src[self.pos + 1..self.pos + close];
+                let scalar = u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid(self))?;
+                let mut buf = [0_u8; 4];
+                out.extend_from_slice(scalar.encode_utf8(&mut buf).as_bytes());
+                self.pos += close + 1;
+            }
+            _ => {
+                return Err(self.error_at(
+                    escape_start,
+                    LuaParseErrorKind::InvalidEscape,
+                    format!("Invalid escape sequence '\\{}'", ch as char),
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_number(&mut self) -> Result<LuaValue, LuaParseError> {
+        let start = self.pos;
+        let negative = self.peek() == Some(b'-');
+        if negative {
+            self.pos += 1;
+            self.skip_trivia()?;
+        }
+        let digits_start = self.pos;
+
+        let is_hex = self.peek() == Some(b'0') && matches!(self.peek_at(1), Some(b'x' | b'X'));
+        if is_hex {
+            self.pos += 2;
+        }
+
+        let mut is_float = false;
+        while let Some(ch) = self.peek() {
+            let accepted = if is_hex {
+                ch.is_ascii_hexdigit()
+            } else if ch.is_ascii_digit() {
+                true
+            } else if ch == b'.' {
+                is_float = true;
+                true
+            } else if ch == b'e' || ch == b'E' {
+                is_float = true;
+                if matches!(self.peek_at(1), Some(b'+' | b'-')) {
+                    self.pos += 1;
+                }
+                true
+            } else {
+                false
+            };
+            if !accepted {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        let literal = &self.src[digits_start..self.pos];
+        let invalid = || {
+            self.error_at(
+                start,
+                LuaParseErrorKind::InvalidNumber,
+                format!("Invalid number '{}'", &self.src[start..self.pos]),
+            )
+        };
+
+        if is_hex {
+            let magnitude = i64::from_str_radix(&literal[2..], 16).map_err(|_| invalid())?;
+            return Ok(LuaValue::Number(Number::from(if negative {
+                -magnitude
+            } else {
+                magnitude
+            })));
+        }
+
+        if !is_float {
+            if let Ok(magnitude) = literal.parse::<i64>() {
+                return Ok(LuaValue::Number(Number::from(if negative {
+                    -magnitude
+                } else {
+                    magnitude
+                })));
+            }
+        }
+
+        let magnitude = literal.parse::<f64>().map_err(|_| invalid())?;
+        let value = if negative { -magnitude } else { magnitude };
+        Number::from_f64(value)
+            .map(LuaValue::Number)
+            .ok_or_else(invalid)
+    }
+}
+
+fn floor_char_boundary(src: &str, mut pos: usize) -> usize {
+    while pos > 0 && !src.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    pos
+}
+
+fn is_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "and"
+            | "break"
+            | "do"
+            | "else"
+            | "elseif"
+            | "end"
+            | "false"
+            | "for"
+            | "function"
+            | "goto"
+            | "if"
+            | "in"
+            | "local"
+            | "nil"
+            | "not"
+            | "or"
+            | "repeat"
+            | "return"
+            | "then"
+            | "true"
+            | "until"
+            | "while"
+    )
+}
+
+fn number_key(number: &Number) -> LuaKey {
+    if let Some(index) = number.as_i64() {
+        return LuaKey::Index(index);
+    }
+    match number.as_f64() {
+        Some(value) if value.fract() == 0.0 && value.abs() < i64::MAX as f64 => {
+            LuaKey::Index(value as i64)
+        }
+        _ => LuaKey::Number(number.clone()),
+    }
+}
+
+// Tables whose keys are exactly 1..n become arrays, everything else becomes an object.
+// This mirrors what `lua-json` produced on the frontend, including `{}` -> `[]`.
+fn table_to_json(fields: &[(LuaKey, LuaValue)]) -> Value {
+    let count = fields.len();
+    let is_sequence = fields.iter().all(
+        |(key, _)| matches!(key, LuaKey::Index(index) if *index >= 1 && (*index as usize) <= count),
+    );
+
+    if is_sequence {
+        let mut slots: Vec<Option<Value>> = vec![None; count];
+        let mut duplicate = false;
+        for (key, value) in fields.iter() {
+            if let LuaKey::Index(index) = key {
+                let slot = &mut slots[(*index - 1) as usize];
+                duplicate |= slot.is_some();
+                *slot = Some(value.to_json());
+            }
+        }
+        if !duplicate {
+            return Value::Array(
+                slots
+                    .into_iter()
+                    .map(|slot| slot.unwrap_or(Value::Null))
+                    .collect(),
+            );
+        }
+    }
+
+    let mut object = Map::new();
+    for (key, value) in fields {
+        let name = match key {
+            LuaKey::Index(index) => index.to_string(),
+            LuaKey::Name(name) => name.clone(),
+            LuaKey::Bool(flag) => flag.to_string(),
+            LuaKey::Number(number) => number.to_string(),
+        };
+        object.insert(name, value.to_json());
+    }
+    Value::Object(object)
+}
+
+/// Parses a SavedVariables file, keeping only the listed root assignments.
+pub fn parse_root_tables(
+    content: &str,
+    roots: &[&str],
+) -> Result<Map<String, Value>, LuaParseError> {
+    Ok(parse_roots(content, roots)?
+        .into_iter()
+        .map(|root| (root.name, root.value.to_json()))
+        .collect())
+}
+
+/// Like [`parse_root_tables`] but keeps the typed [`LuaValue`]s and their source ranges.
+pub fn parse_roots(content: &str, roots: &[&str]) -> Result<Vec<LuaRoot>, LuaParseError> {
+    Parser::new(content).parse_chunk(roots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_root_tables, parse_roots, LuaKey, LuaParseErrorKind, LuaValue, MAX_DEPTH};
+    use serde_json::{json, Value};
+
+    fn parse(src: &str) -> Value {
+        let mut roots = parse_root_tables(src, &["T"]).expect("parses");
+        roots.remove("T").expect("root T")
+    }
+
+    fn parse_err(src: &str) -> super::LuaParseError {
+        parse_root_tables(src, &["T"]).expect_err("fails")
+    }
+
+    #[test]
+    fn string_escapes() {
+        let value = parse(
+            r#"T = { "a\"b", 'it\'s', "tab\tnl\n", "\65\066\0671", "\x41\u{e9}", "\\", "a\z
+                   b", "line\
+break" }"#,
+        );
+        assert_eq!(
+            value,
+            json!([
+                "a\"b",
+                "it's",
+                "tab\tnl\n",
+                "ABC1",
+                "Aé",
+                "\\",
+                "ab",
+                "line\nbreak"
+            ])
+        );
+
+        // Decimal escapes are bytes, so multi-byte UTF-8 can be spelled out.
+        assert_eq!(parse(r#"T = "\195\169""#), json!("é"));
+        assert_eq!(
+            parse_err(r#"T = "\q""#).kind,
+            LuaParseErrorKind::InvalidEscape
+        );
+        assert_eq!(
+            parse_err(r#"T = "\256""#).kind,
+            LuaParseErrorKind::InvalidEscape
+        );
+        assert_eq!(
+            parse_err("T = \"open\nT2 = 1").kind,
+            LuaParseErrorKind::UnterminatedString
+        );
+    }
+
+    #[test]
+    fn long_strings_and_comments() {
+        let src = "-- header comment\n\
+                   --[[ block\n comment ]]\n\
+                   T = { [[\nraw \\n \"text\"]], [==[a ]] b]==], --[=[ inline ]=] 3 } -- trailing\n";
+        assert_eq!(parse(src), json!(["raw \\n \"text\"", "a ]] b", 3]));
+
+        assert_eq!(
+            parse_err("T = [[never closed").kind,
+            LuaParseErrorKind::UnterminatedString
+        );
+        assert_eq!(
+            parse_err("--[[ never closed\nT = 1").kind,
+            LuaParseErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            parse("T = { 42, -7, 0x1F, -0xff, 1.5, .5, 3., 1e3, 2.5E-2, - 4 }"),
+            json!([42, -7, 31, -255, 1.5, 0.5, 3.0, 1000.0, 0.025, -4])
+        );
+        // Integers beyond i64 fall back to a float, as Lua would read them.
+        assert_eq!(
+            parse("T = 18446744073709551616"),
+            json!(18446744073709551616.0_f64)
+        );
+        assert_eq!(
+            parse_err("T = 1.2.3").kind,
+            LuaParseErrorKind::InvalidNumber
+        );
+        assert_eq!(parse_err("T = 0xZZ").kind, LuaParseErrorKind::InvalidNumber);
+    }
+
+    #[test]
+    fn depth_limit() {
+        let nested = |depth: usize| format!("T = {}{}", "{".repeat(depth), "}".repeat(depth));
+        assert!(parse_root_tables(&nested(MAX_DEPTH), &["T"]).is_ok());
+        assert_eq!(
+            parse_err(&nested(MAX_DEPTH + 1)).kind,
+            LuaParseErrorKind::TooDeep
+        );
+    }
+
+    #[test]
+    fn errors_report_line_and_column() {
+        let err = parse_err("T = {\n  a = 1,\n  b = ,\n}");
+        assert_eq!(err.kind, LuaParseErrorKind::UnexpectedToken);
+        assert_eq!((err.line, err.column), (3, 7));
+
+        // Columns count characters, not bytes.
+        let err = parse_err("T = { \"é\", ? }");
+        assert_eq!((err.line, err.column), (1, 12));
+
+        let err = parse_err("T = {\n  1,\n");
+        assert_eq!(err.kind, LuaParseErrorKind::UnexpectedEof);
+        assert_eq!(err.line, 3);
+
+        assert_eq!(
+            parse_err("T = { [nil] = 1 }").kind,
+            LuaParseErrorKind::InvalidKey
+        );
+        assert_eq!(
+            parse_err("local = 1").kind,
+            LuaParseErrorKind::UnexpectedToken
+        );
+    }
+
+    #[test]
+    fn tables_become_arrays_only_for_dense_sequences() {
+        assert_eq!(parse("T = {}"), json!([]));
+        assert_eq!(parse("T = { 'a', 'b' }"), json!(["a", "b"]));
+        assert_eq!(parse("T = { [2] = 'b', [1] = 'a' }"), json!(["a", "b"]));
+        // Sparse.
+        assert_eq!(
+            parse("T = { [1] = 'a', [3] = 'c' }"),
+            json!({ "1": "a", "3": "c" })
+        );
+        // Mixed array and hash parts.
+        assert_eq!(
+            parse("T = { 'a', 'b', x = 1, [\"y z\"] = { 2 } }"),
+            json!({ "1": "a", "2": "b", "x": 1, "y z": [2] })
+        );
+        // Duplicate indices keep the object form with the last value.
+        assert_eq!(parse("T = { 'a', [1] = 'b' }"), json!({ "1": "b" }));
+        // Keyed nils disappear, positional ones keep their slot.
+        assert_eq!(parse("T = { a = nil, b = 1 }"), json!({ "b": 1 }));
+        assert_eq!(parse("T = { 1, nil, 3 }"), json!([1, null, 3]));
+    }
+
+    #[test]
+    fn roots_keep_source_ranges_and_last_assignment() {
+        let src = "A = 1\nT = { x = 1 }\nT = { y = 2 };\n";
+        let roots = parse_roots(src, &["T"]).expect("parses");
+        assert_eq!(roots.len(), 2);
+        let last = roots.last().unwrap();
+        assert_eq!(&src[last.start..last.end], "T = { y = 2 }");
+        assert_eq!(
+            last.value,
+            LuaValue::Table(vec![(LuaKey::Name("y".into()), LuaValue::Number(2.into()))])
+        );
+        assert_eq!(parse(src), json!({ "y": 2 }));
+    }
+}
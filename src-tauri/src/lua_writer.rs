@@ -0,0 +1,275 @@
+use crate::lua_parser::{self, LuaKey, LuaValue};
+use serde_json::Number;
+use std::fmt::Write;
+
+fn push_indent(out: &mut String, depth: usize) {
+    (0..depth).for_each(|_| out.push('\t'));
+}
+
+fn write_string(out: &mut String, text: &str) {
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 || ch == '\u{7f}' => {
+                let _ = write!(out, "\\{:03}", ch as u32);
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
+
+// Integers print as-is; floats use the shortest form that parses back to the same f64,
+// keeping a `.0` so they stay floats.
+fn write_number(out: &mut String, number: &Number) {
+    if number.is_f64() {
+        let _ = write!(out, "{:?}", number.as_f64().unwrap_or(0.0));
+    } else {
+        let _ = write!(out, "{number}");
+    }
+}
+
+fn write_key(out: &mut String, key: &LuaKey) {
+    out.push('[');
+    match key {
+        LuaKey::Index(index) => {
+            let _ = write!(out, "{index}");
+        }
+        LuaKey::Name(name) => write_string(out, name),
+        LuaKey::Bool(flag) => out.push_str(if *flag { "true" } else { "false" }),
+        LuaKey::Number(number) => write_number(out, number),
+    }
+    out.push_str("] = ");
+}
+
+// WoW's layout: tab indentation, the array part positional with `-- [n]` comments, every other
+// field as `["key"] = value,`.
+fn write_table(out: &mut String, fields: &[(LuaKey, LuaValue)], depth: usize) {
+    out.push_str("{\n");
+    let mut next_index: i64 = 1;
+    for (key, value) in fields {
+        let positional = *key == LuaKey::Index(next_index);
+        if !positional && matches!(value, LuaValue::Nil) {
+            continue;
+        }
+
+        push_indent(out, depth + 1);
+        if !positional {
+            write_key(out, key);
+        }
+        write_value(out, value, depth + 1);
+        out.push(',');
+        if positional {
+            let _ = write!(out, " -- [{next_index}]");
+            next_index += 1;
+        }
+        out.push('\n');
+    }
+    push_indent(out, depth);
+    out.push('}');
+}
+
+fn write_value(out: &mut String, value: &LuaValue, depth: usize) {
+    match value {
+        LuaValue::Nil => out.push_str("nil"),
+        LuaValue::Bool(flag) => out.push_str(if *flag { "true" } else { "false" }),
+        LuaValue::Number(number) => write_number(out, number),
+        LuaValue::String(text) => write_string(out, text),
+        LuaValue::Table(fields) => write_table(out, fields, depth),
+    }
+}
+
+/// Serializes one `name = value` root assignment the way the client writes SavedVariables.
+pub fn serialize_root(name: &str, value: &LuaValue) -> String {
+    let mut out = format!("{name} = ");
+    write_value(&mut out, value, 0);
+    out.push('\n');
+    out
+}
+
+// What the parser will read back: keyed `nil` fields disappear.
+fn without_keyed_nils(value: &LuaValue) -> LuaValue {
+    let LuaValue::Table(fields) = value else {
+        return value.clone();
+    };
+
+    let mut next_index: i64 = 1;
+    let mut kept = Vec::with_capacity(fields.len());
+    for (key, item) in fields {
+        let positional = *key == LuaKey::Index(next_index);
+        if positional {
+            next_index += 1;
+        } else if matches!(item, LuaValue::Nil) {
+            continue;
+        }
+        kept.push((key.clone(), without_keyed_nils(item)));
+    }
+    LuaValue::Table(kept)
+}
+
+/// Rewrites the given root assignments inside `content`, leaving everything else untouched.
+/// Roots that are missing get appended. The result is parsed again and must reproduce every
+/// written value exactly, otherwise nothing is returned.
+pub fn replace_roots(content: &str, updates: &[(&str, LuaValue)]) -> Result<String, String> {
+    let names: Vec<&str> = updates.iter().map(|(name, _)| *name).collect();
+    let existing = lua_parser::parse_roots(content, &names)
+        .map_err(|e| format!("Failed parsing SavedVariables: {e}"))?;
+
+    let mut splices: Vec<(usize, usize, String)> = Vec::new();
+    let mut appended = String::new();
+    for (name, value) in updates {
+        let serialized = serialize_root(name, value);
+        // Lua keeps the last assignment, so that is the one to replace.
+        match existing.iter().rev().find(|root| root.name == *name) {
+            Some(root) => splices.push((
+                root.start,
+                root.end,
+                serialized.trim_end_matches('\n').to_string(),
+            )),
+            None => appended.push_str(&serialized),
+        }
+    }
+
+    let mut out = content.to_string();
+    splices.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+    for (start, end, text) in splices {
+        out.replace_range(start..end, &text);
+    }
+    if !appended.is_empty() {
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&appended);
+    }
+
+    let reparsed = lua_parser::parse_roots(&out, &names)
+        .map_err(|e| format!("Rewritten SavedVariables failed to parse: {e}"))?;
+    for (name, value) in updates {
+        let round_trip = reparsed.iter().rev().find(|root| root.name == *name);
+        if round_trip.map(|root| &root.value) != Some(&without_keyed_nils(value)) {
+            return Err(format!("Rewritten {name} did not round-trip"));
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{replace_roots, serialize_root};
+    use crate::lua_parser::{parse_roots, LuaKey, LuaValue};
+    use crate::saved_vars_command::SAVED_VARIABLES_ROOTS;
+    use serde_json::Number;
+
+    // Written in the client's layout, so rewriting a root unchanged must reproduce it byte for byte.
+    const SAMPLE: &str = include_str!("../fixtures/saved_variables/PvP_Scalpel.lua");
+
+    fn root(content: &str, name: &str) -> LuaValue {
+        parse_roots(content, &[name])
+            .expect("parses")
+            .into_iter()
+            .rev()
+            .find(|root| root.name == name)
+            .expect("root present")
+            .value
+    }
+
+    fn name(key: &str) -> LuaKey {
+        LuaKey::Name(key.to_string())
+    }
+
+    #[test]
+    fn unchanged_roots_are_rewritten_byte_for_byte() {
+        let updates: Vec<(&str, LuaValue)> = SAVED_VARIABLES_ROOTS
+            .iter()
+            .map(|name| (*name, root(SAMPLE, name)))
+            .collect();
+        assert_eq!(replace_roots(SAMPLE, &updates).expect("rewrites"), SAMPLE);
+    }
+
+    #[test]
+    fn only_the_updated_root_changes() {
+        let mut gc = root(SAMPLE, "PvP_Scalpel_GC");
+        gc.set_field(
+            name("2026-03-14T19:42:07|Nagrand Arena|3v3"),
+            LuaValue::String("synced".to_string()),
+        );
+        gc.set_field(
+            name("2026-03-15T10:00:00|Mugambala|Solo Shuffle"),
+            LuaValue::String("pending".to_string()),
+        );
+
+        let out = replace_roots(SAMPLE, &[("PvP_Scalpel_GC", gc.clone())]).expect("rewrites");
+        assert_eq!(root(&out, "PvP_Scalpel_GC"), gc);
+
+        let start = SAMPLE.find("PvP_Scalpel_GC = {").expect("GC root");
+        let end = SAMPLE
+            .find("PvP_Scalpel_Spell_Data = {")
+            .expect("spell data root");
+        assert_eq!(out[..start], SAMPLE[..start]);
+        assert!(out.ends_with(&SAMPLE[end..]));
+        assert!(out.contains("\t[\"2026-03-15T10:00:00|Mugambala|Solo Shuffle\"] = \"pending\",\n"));
+    }
+
+    #[test]
+    fn nested_mixed_and_escaped_values_survive() {
+        let db = root(SAMPLE, "PvP_Scalpel_DB");
+        let LuaValue::Table(matches) = &db else {
+            panic!("DB is a table");
+        };
+        let capture = matches[0].1.field("localSpellCapture").expect("capture");
+        let LuaValue::Table(fields) = capture else {
+            panic!("capture is a table");
+        };
+        let keys: Vec<&LuaKey> = fields.iter().map(|(key, _)| key).collect();
+        assert_eq!(
+            keys,
+            [
+                &LuaKey::Index(1),
+                &LuaKey::Index(2),
+                &LuaKey::Index(3),
+                &name("schema"),
+                &LuaKey::Index(5),
+                &LuaKey::Bool(true),
+                &LuaKey::Number(Number::from_f64(2.5).expect("finite")),
+            ]
+        );
+
+        let text = serialize_root("PvP_Scalpel_DB", &db);
+        assert!(text.contains("\t\t\t\"castGUID\", -- [2]\n"));
+        assert!(text.contains("\t\t\t[5] = \"sparse\",\n"));
+        assert!(text.contains("\t\t\t[true] = \"flag\",\n"));
+        assert!(text.contains("\t\t\t[2.5] = \"half\",\n"));
+        assert!(text.contains(r#""Release \"live\" build\nC:\\World of Warcraft\\_retail_""#));
+        assert_eq!(root(&text, "PvP_Scalpel_DB"), db);
+    }
+
+    #[test]
+    fn numbers_keep_their_value_but_not_their_text() {
+        let src = "T = { 1.50, 0x10, 2.0, -0.125, 1e300 }\n";
+        let out = replace_roots(src, &[("T", root(src, "T"))]).expect("rewrites");
+        assert_eq!(
+            out,
+            "T = {\n\t1.5, -- [1]\n\t16, -- [2]\n\t2.0, -- [3]\n\t-0.125, -- [4]\n\t1e300, -- [5]\n}\n"
+        );
+    }
+
+    #[test]
+    fn missing_roots_are_appended_and_keyed_nils_dropped() {
+        let value = LuaValue::Table(vec![
+            (LuaKey::Index(1), LuaValue::String("a\u{1}b".to_string())),
+            (name("gone"), LuaValue::Nil),
+            (name("kept"), LuaValue::Bool(false)),
+        ]);
+        let out = replace_roots("Other = 1", &[("T", value)]).expect("rewrites");
+        assert_eq!(
+            out,
+            "Other = 1\nT = {\n\t\"a\\001b\", -- [1]\n\t[\"kept\"] = false,\n}\n"
+        );
+    }
+}
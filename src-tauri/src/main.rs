@@ -13,6 +13,26 @@ mod launcher_command;
 mod log_command;
 mod computed_matches_command;
 mod gc_state_command;
+mod lua_parser;
+mod match_model;
+mod saved_vars_command;
+mod savedvars_delta;
+mod computed_store;
+mod computed_query;
+mod computed_duplicates;
+mod computed_merge;
+mod computed_migrations;
+mod local_spell_model;
+mod fs_atomic;
+mod file_lock;
+mod lua_writer;
+mod gc_lifecycle;
+mod savedvars_prune;
+mod match_time;
+mod savedvars_vault;
+mod game_monitor;
+mod app_config;
+mod watcher_service;
 
 use tauri::{
     menu::{Menu, MenuItem, PredefinedMenuItem},
@@ -22,23 +42,18 @@ use tauri::{
     Manager,
 };
 
-use notify::{RecommendedWatcher, RecursiveMode, Watcher};
-use std::sync::Mutex;
 use std::time::Duration;
 
 #[cfg(not(debug_assertions))]
 use std::{
     io::{Read, Write},
     net::{TcpListener, TcpStream},
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 
 #[cfg(not(debug_assertions))]
 use xxhash_rust::xxh64::xxh64;
 
-#[derive(Default)]
-struct WatcherKeeper(Mutex<Option<RecommendedWatcher>>);
-
 #[cfg(not(debug_assertions))]
 const APP_IDENTIFIER: &str = "bg.pvpscalpel.desktop";
 
@@ -94,14 +109,13 @@ fn update_tray_state(
 
 #[tauri::command]
 fn scan_saved_vars(app: AppHandle) -> Result<(), String> {
-    if let Some(path) = gwp_command::get_wow_path() {
-        let root = std::path::PathBuf::from(path);
-        let found = watcher::emit_existing_saved_vars(&app, &root);
-        if found {
-            log_command::emit_log(&app, "SavedVariables discovered");
-        } else {
-            log_command::emit_log(&app, "SavedVariables not found");
-        }
+    if gwp_command::find_installs().is_empty() {
+        return Ok(());
+    }
+    if watcher::rescan(&app) {
+        log_command::emit_log(&app, "SavedVariables discovered");
+    } else {
+        log_command::emit_log(&app, "SavedVariables not found");
     }
     Ok(())
 }
@@ -216,7 +230,7 @@ fn main() {
     }
 
     tauri::Builder::default()
-    .manage(WatcherKeeper::default())
+    .manage(watcher_service::WatcherKeeper::default())
     .setup(move |app| {
             #[cfg(not(debug_assertions))]
             if let Ok(mut stored) = single_instance_handle.lock() {
@@ -228,38 +242,27 @@ fn main() {
                 let _ = window.set_shadow(false);
             }
             let handle = app.handle().clone();
-            let root = if let Some(path) = gwp_command::get_wow_path() {
-                log_command::emit_log(&handle, "WoW path detected");
-                std::path::PathBuf::from(path)
-            } else {
+            if let Ok(dir) = handle.path().app_data_dir() {
+                file_lock::set_lock_dir(dir.join("locks"));
+            }
+            gwp_command::load_wow_path_override(&handle);
+            let installs = gwp_command::find_installs();
+            if installs.is_empty() {
+                // Keep going: `set_wow_path` attaches the watchers once the user picks a folder.
                 log_command::emit_log(&handle, "WoW path not found");
-                return Ok(());
-            };
-
-            if root.exists() {
-                let mut watcher =
-                    watcher::create_watcher(handle).expect("failed to create watcher");
-
-                watcher
-                    .watch(&root, RecursiveMode::Recursive)
-                    .expect("failed to watch folder");
-
-                // Watcher in mem stored
-                let keeper = app.state::<WatcherKeeper>();
-                *keeper.0.lock().unwrap() = Some(watcher);
-                log_command::emit_log(&app.handle(), "Addon watcher registered");
             } else {
-                log_command::emit_log(&app.handle(), "Addon watcher started");
-            };
+                log_command::emit_log(&handle, &format!("WoW path detected ({} installs)", installs.len()));
+            }
+            watcher_service::watch_installs(&handle, &installs);
 
             discord_rpc::start_rich_presence(); // Start Discord presence
+            game_monitor::start(app.handle().clone());
 
-            if let Some(path) = gwp_command::get_wow_path() {
+            if !installs.is_empty() {
                 let app_handle = app.handle().clone();
-                let root = std::path::PathBuf::from(path);
                 std::thread::spawn(move || {
                     for _ in 0..30 {
-                        if watcher::emit_existing_saved_vars(&app_handle, &root) {
+                        if watcher::rescan(&app_handle) {
                             log_command::emit_log(&app_handle, "Addon SavedVariables discovered");
                             break;
                         }
@@ -310,10 +313,7 @@ fn main() {
         })
         .on_page_load(|window, _| {
             log_command::emit_log(window.app_handle(), "App initialized");
-            if let Some(path) = gwp_command::get_wow_path() {
-                let root = std::path::PathBuf::from(path);
-                watcher::emit_existing_saved_vars(window, &root);
-            }
+            watcher::rescan(window);
             let versions = version_command::get_local_versions();
             match versions.desktop_version.as_deref() {
                 Some(version) => {
@@ -334,6 +334,7 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             read_saved_variables,
+            saved_vars_command::parse_saved_variables,
             im_command::identify_match,
             gc_command::get_config,
             gc_command::get_local_config,
@@ -350,7 +351,23 @@ fn main() {
             computed_matches_command::load_computed_matches,
             computed_matches_command::load_all_computed_matches,
             computed_matches_command::upsert_computed_matches,
+            computed_matches_command::query_computed_matches,
+            computed_matches_command::find_duplicate_matches,
+            computed_matches_command::merge_computed_matches,
+            computed_matches_command::plan_computed_store_migrations,
+            computed_matches_command::restore_computed_store,
             gc_state_command::mark_gc_matches_synced,
+            gc_state_command::get_pending_savedvars_writes,
+            gc_state_command::get_gc_states,
+            gc_state_command::transition_gc_states,
+            savedvars_prune::prune_synced_matches,
+            savedvars_vault::list_savedvars_backups,
+            savedvars_vault::restore_savedvars_backup,
+            game_monitor::get_game_state,
+            gwp_command::find_wow_installs,
+            gwp_command::get_wow_path_settings,
+            watcher_service::get_watcher_status,
+            gwp_command::set_wow_path,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri app");
@@ -0,0 +1,678 @@
+use crate::game_monitor::GameFlavor;
+use serde::de::{self, DeserializeOwned, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use ts_rs::TS;
+
+// Typed view of the v4 telemetry written by the addon. Stores keep the raw JSON;
+// these structs are the contract used to validate entries at the Rust boundary.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../../src/Interfaces/MatchWinner.ts")]
+pub enum MatchWinner {
+    Victory,
+    Defeat,
+    Draw,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/BuildInfoSnapshot.ts")]
+pub struct BuildInfoSnapshot {
+    pub version: String,
+    #[ts(type = "string | number")]
+    pub build: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub date: Option<String>,
+    pub interface: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub localized: Option<String>,
+    pub info: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub version_string: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/MatchDetailsV2.ts")]
+pub struct MatchDetailsV2 {
+    pub timestamp: String,
+    pub format: String,
+    pub map_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub bg_game_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub build: Option<BuildInfoSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/PlayerEntryV2.ts")]
+pub struct PlayerEntryV2 {
+    pub name: String,
+    pub realm: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub guid: Option<String>,
+    #[serde(rename = "class", default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub class_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub spec: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub faction: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub rating: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub rating_change: Option<f64>,
+    #[serde(
+        rename = "prematchMMR",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub prematch_mmr: Option<f64>,
+    #[serde(
+        rename = "postmatchMMR",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub postmatch_mmr: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub damage: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub healing: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub interrupts: Option<(f64, f64)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub kills: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub deaths: Option<f64>,
+    #[serde(
+        rename = "MSS",
+        default,
+        deserialize_with = "lua_seq_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub mss: Option<Vec<(String, f64)>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub is_owner: Option<bool>,
+    #[serde(
+        default,
+        deserialize_with = "lua_seq_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub pvp_talents: Option<Vec<u32>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/PvpStatValue.ts")]
+pub struct PvpStatValue {
+    #[serde(rename = "pvpStatID")]
+    pub pvp_stat_id: u32,
+    pub pvp_stat_value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/ScoreSnapshotPlayer.ts")]
+pub struct ScoreSnapshotPlayer {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub realm: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub guid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub class_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub talent_spec: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub faction: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub rating: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub rating_change: Option<f64>,
+    #[serde(
+        rename = "prematchMMR",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub prematch_mmr: Option<f64>,
+    #[serde(
+        rename = "postmatchMMR",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub postmatch_mmr: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub damage_done: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub healing_done: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub killing_blows: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub deaths: Option<f64>,
+    #[serde(
+        default,
+        deserialize_with = "lua_seq_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub stats: Option<Vec<PvpStatValue>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/PvpStatColumn.ts")]
+pub struct PvpStatColumn {
+    #[serde(rename = "pvpStatID")]
+    pub pvp_stat_id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/ScoreSnapshot.ts")]
+pub struct ScoreSnapshot {
+    #[serde(deserialize_with = "lua_seq")]
+    pub stat_columns: Vec<PvpStatColumn>,
+    #[serde(deserialize_with = "lua_seq")]
+    pub players: Vec<ScoreSnapshotPlayer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/SoloShuffleRoundOutcome.ts")]
+pub struct SoloShuffleRoundOutcome {
+    pub result: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/SoloShuffleRoundV4.ts")]
+pub struct SoloShuffleRoundV4 {
+    pub round_index: u32,
+    pub state_start_time: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub state_end_time: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub duration: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub score_snapshot: Option<ScoreSnapshot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub outcome: Option<SoloShuffleRoundOutcome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/SoloShuffleSummaryV4.ts")]
+pub struct SoloShuffleSummaryV4 {
+    #[serde(deserialize_with = "lua_seq")]
+    pub stat_columns: Vec<PvpStatColumn>,
+    #[serde(deserialize_with = "lua_seq")]
+    pub players: Vec<ScoreSnapshotPlayer>,
+    pub rating_change: f64,
+    #[serde(rename = "prematchMMR")]
+    pub prematch_mmr: f64,
+    #[serde(rename = "postmatchMMR")]
+    pub postmatch_mmr: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/CaptureIntegrity.ts")]
+pub struct CaptureIntegrity {
+    pub scoreboard_complete: bool,
+    pub timeline_complete: bool,
+    pub rounds_complete: bool,
+    #[serde(deserialize_with = "lua_seq")]
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/SoloShuffleDataV4.ts")]
+pub struct SoloShuffleDataV4 {
+    pub match_key: String,
+    pub timestamp: String,
+    pub format: String,
+    pub map_name: String,
+    pub duration: f64,
+    pub rounds_expected: u32,
+    pub rounds_captured: u32,
+    #[serde(deserialize_with = "lua_seq")]
+    pub rounds: Vec<SoloShuffleRoundV4>,
+    pub match_summary: SoloShuffleSummaryV4,
+    pub integrity: CaptureIntegrity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/SpellTotalsEntry.ts")]
+pub struct SpellTotalsEntry {
+    pub damage: f64,
+    pub healing: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub overheal: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub absorbed: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub hits: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub crits: Option<f64>,
+    #[serde(
+        default,
+        deserialize_with = "lua_map_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub targets: Option<HashMap<String, f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub interrupts: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub dispels: Option<f64>,
+}
+
+// The capture payloads are column-schema tables whose layout the addon still evolves,
+// so only the outer shape is fixed here; rows stay raw for the local spell model.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/Interfaces/LocalSpellCaptureGroup.ts")]
+pub struct LocalSpellCaptureGroup {
+    #[serde(rename = "spellID", default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub spell_id_upper: Option<f64>,
+    #[serde(rename = "spellId", default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub spell_id: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "unknown[] | Record<string, unknown>")]
+    pub casts: Option<Value>,
+    #[serde(flatten)]
+    #[ts(skip)]
+    pub extra: Map<String, Value>,
+}
+
+/// A keyed payload mixes per-spell groups with schema and metadata fields.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(untagged)]
+#[ts(export, export_to = "../../src/Interfaces/LocalSpellCaptureField.ts")]
+pub enum LocalSpellCaptureField {
+    Group(LocalSpellCaptureGroup),
+    #[ts(type = "unknown")]
+    Other(Value),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(untagged)]
+#[ts(export, export_to = "../../src/Interfaces/LocalSpellCapturePayload.ts")]
+pub enum LocalSpellCapturePayload {
+    Keyed(HashMap<String, LocalSpellCaptureField>),
+    #[ts(type = "unknown[]")]
+    Rows(Vec<Value>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(
+    export,
+    export_to = "../../src/Interfaces/LocalLossOfControlPayload.ts"
+)]
+pub struct LocalLossOfControlPayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "unknown[] | Record<string, unknown>")]
+    pub entries: Option<Value>,
+    #[serde(flatten)]
+    #[ts(skip)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/MatchV4.ts")]
+pub struct MatchV4 {
+    pub match_key: String,
+    pub telemetry_version: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub duration_seconds: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub winner: Option<MatchWinner>,
+    pub match_details: MatchDetailsV2,
+    #[serde(deserialize_with = "lua_seq")]
+    pub players: Vec<PlayerEntryV2>,
+    #[serde(
+        default,
+        deserialize_with = "lua_map_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub spell_totals_by_source: Option<HashMap<String, HashMap<String, SpellTotalsEntry>>>,
+    #[serde(
+        default,
+        deserialize_with = "lua_map_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub interrupt_spells_by_source: Option<HashMap<String, HashMap<String, f64>>>,
+    #[serde(
+        default,
+        deserialize_with = "lua_map_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional, type = "Record<string, unknown>")]
+    pub crowd_control_taken_by_source: Option<HashMap<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub local_spell_capture: Option<LocalSpellCapturePayload>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub local_loss_of_control: Option<LocalLossOfControlPayload>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, as = "Option<crate::local_spell_model::ComputedAnalyticsV2>")]
+    pub computed: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub solo_shuffle: Option<SoloShuffleDataV4>,
+    /// Client the match was played on; set by the desktop when it stores the match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub flavor: Option<GameFlavor>,
+    // Desktop-side annotations (bracketId, ...) ride along untouched.
+    #[serde(flatten)]
+    #[ts(skip)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/MatchValidationError.ts")]
+pub struct MatchValidationError {
+    pub match_key: Option<String>,
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for MatchValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Match {} rejected at `{}`: {}",
+            self.match_key.as_deref().unwrap_or("<unknown>"),
+            self.path,
+            self.message
+        )
+    }
+}
+
+pub fn telemetry_version_of(value: &Value) -> Option<f64> {
+    let raw = value.get("telemetryVersion")?;
+    raw.as_f64()
+        .or_else(|| raw.as_str().and_then(|v| v.trim().parse::<f64>().ok()))
+}
+
+pub fn is_match_v4(value: &Value) -> bool {
+    telemetry_version_of(value)
+        .map(|version| version >= 4.0)
+        .unwrap_or(false)
+}
+
+/// Decodes a v4 match, reporting the first offending field path on failure.
+pub fn parse_match_v4(value: &Value) -> Result<MatchV4, MatchValidationError> {
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        let path = err.path().to_string();
+        MatchValidationError {
+            match_key: value
+                .get("matchKey")
+                .and_then(|v| v.as_str())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+            path: if path.is_empty() {
+                ".".to_string()
+            } else {
+                path
+            },
+            message: err.into_inner().to_string(),
+        }
+    })
+}
+
+/// Only v4+ entries have a typed contract; older telemetry passes through unchecked.
+pub fn validate_match(value: &Value) -> Result<(), MatchValidationError> {
+    if is_match_v4(value) {
+        parse_match_v4(value)?;
+    }
+    Ok(())
+}
+
+// Lua has no distinct empty map/array, and sparse arrays come out of the parser as
+// objects keyed by index. These adapters accept both shapes for sequence/map fields.
+
+struct LuaSeqVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for LuaSeqVisitor<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array or an index-keyed table")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut out = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            out.push(item);
+        }
+        Ok(out)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut indexed: Vec<(u64, T)> = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let index = key
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| de::Error::custom(format!("expected an index key, found `{key}`")))?;
+            indexed.push((index, map.next_value()?));
+        }
+        indexed.sort_by_key(|(index, _)| *index);
+        Ok(indexed.into_iter().map(|(_, item)| item).collect())
+    }
+}
+
+struct LuaMapVisitor<V>(PhantomData<V>);
+
+impl<'de, V: Deserialize<'de>> Visitor<'de> for LuaMapVisitor<V> {
+    type Value = HashMap<String, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a table")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut out = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, value)) = map.next_entry::<String, V>()? {
+            out.insert(key, value);
+        }
+        Ok(out)
+    }
+
+    // A sequence is a map keyed 1..n in Lua terms.
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut out = HashMap::new();
+        let mut index = 1_usize;
+        while let Some(item) = seq.next_element()? {
+            out.insert(index.to_string(), item);
+            index += 1;
+        }
+        Ok(out)
+    }
+}
+
+fn lua_seq<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    deserializer.deserialize_any(LuaSeqVisitor(PhantomData))
+}
+
+fn lua_seq_opt<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    Option::<LuaSeq<T>>::deserialize(deserializer).map(|value| value.map(|seq| seq.0))
+}
+
+fn lua_map_opt<'de, D, V>(deserializer: D) -> Result<Option<HashMap<String, V>>, D::Error>
+where
+    D: Deserializer<'de>,
+    V: DeserializeOwned,
+{
+    Option::<LuaMap<V>>::deserialize(deserializer).map(|value| value.map(|map| map.0))
+}
+
+struct LuaSeq<T>(Vec<T>);
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for LuaSeq<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        lua_seq(deserializer).map(LuaSeq)
+    }
+}
+
+struct LuaMap<V>(HashMap<String, V>);
+
+impl<'de, V: DeserializeOwned> Deserialize<'de> for LuaMap<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(LuaMapVisitor(PhantomData))
+            .map(LuaMap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_match;
+    use crate::lua_parser::parse_roots;
+    use serde_json::{json, Value};
+
+    const SAMPLE: &str = include_str!("../fixtures/saved_variables/PvP_Scalpel.lua");
+
+    fn sample_match() -> Value {
+        let db = parse_roots(SAMPLE, &["PvP_Scalpel_DB"])
+            .expect("parses")
+            .pop()
+            .expect("DB root")
+            .value
+            .to_json();
+        db[0].clone()
+    }
+
+    #[test]
+    fn sample_match_validates_without_optional_build_fields() {
+        let entry = sample_match();
+        assert_eq!(entry["telemetryVersion"], json!(4));
+        assert!(entry["matchDetails"]["build"]
+            .get("versionString")
+            .is_none());
+        assert!(validate_match(&entry).is_ok());
+    }
+
+    #[test]
+    fn missing_field_is_reported_on_its_parent() {
+        let mut entry = sample_match();
+        entry["matchDetails"]
+            .as_object_mut()
+            .expect("details")
+            .remove("mapName");
+
+        let err = validate_match(&entry).expect_err("rejected");
+        assert_eq!(err.path, "matchDetails");
+        assert!(err.message.contains("mapName"), "{}", err.message);
+        assert_eq!(err.match_key.as_deref(), entry["matchKey"].as_str());
+    }
+
+    #[test]
+    fn wrong_type_is_reported_at_the_field() {
+        let mut entry = sample_match();
+        entry["players"][1]["damage"] = json!("lots");
+
+        let err = validate_match(&entry).expect_err("rejected");
+        assert_eq!(err.path, "players[1].damage");
+    }
+
+    #[test]
+    fn index_keyed_tables_count_as_sequences() {
+        let mut entry = sample_match();
+        let players = entry["players"].as_array().expect("players").clone();
+        entry["players"] = json!({ "2": players[1], "1": players[0] });
+        assert!(validate_match(&entry).is_ok());
+
+        entry["players"] = json!({ "first": players[0] });
+        let err = validate_match(&entry).expect_err("rejected");
+        assert_eq!(err.path, "players");
+        assert!(err.message.contains("`first`"), "{}", err.message);
+    }
+
+    #[test]
+    fn older_telemetry_and_root_errors() {
+        assert!(validate_match(&json!({ "telemetryVersion": 3, "players": "?" })).is_ok());
+
+        let err = validate_match(&json!({ "telemetryVersion": 4 })).expect_err("rejected");
+        assert_eq!(err.path, ".");
+        assert_eq!(err.match_key, None);
+    }
+}
@@ -0,0 +1,35 @@
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's `days_from_civil`).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Seconds for an addon timestamp (`2025-02-14 21:17:05`). The client writes local time; it is
+/// read as UTC, which keeps differences between timestamps from one machine exact.
+pub fn timestamp_seconds(raw: &str) -> Option<i64> {
+    let raw = raw.trim();
+    let mut date = raw
+        .get(..10)?
+        .split('-')
+        .map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let mut time = raw
+        .get(11..)
+        .unwrap_or_default()
+        .split(':')
+        .map(|part| part.trim().parse::<i64>().unwrap_or(0));
+    let seconds =
+        time.next().unwrap_or(0) * 3600 + time.next().unwrap_or(0) * 60 + time.next().unwrap_or(0);
+    Some(days_from_civil(year, month, day) * SECONDS_PER_DAY + seconds)
+}
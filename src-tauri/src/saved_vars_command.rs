@@ -0,0 +1,56 @@
+use crate::lua_parser::{self, LuaParseError};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use ts_rs::TS;
+
+// Root tables the addon writes into PvP_Scalpel.lua.
+pub const SAVED_VARIABLES_ROOTS: [&str; 4] = [
+    "PvP_Scalpel_DB",
+    "PvP_Scalpel_GC",
+    "PvP_Scalpel_Spell_Data",
+    "PvP_Scalpel_InteruptSpells",
+];
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/SavedVariablesError.ts")]
+pub enum SavedVariablesError {
+    Read { message: String },
+    Parse(LuaParseError),
+}
+
+impl From<LuaParseError> for SavedVariablesError {
+    fn from(value: LuaParseError) -> Self {
+        SavedVariablesError::Parse(value)
+    }
+}
+
+/// Reads and parses the requested root tables; roots missing from the file come back as `null`.
+pub fn read_root_tables(
+    path: &str,
+    roots: &[&str],
+) -> Result<Map<String, Value>, SavedVariablesError> {
+    let content = fs::read_to_string(path).map_err(|e| SavedVariablesError::Read {
+        message: format!("Failed reading file: {e}"),
+    })?;
+
+    let mut parsed = lua_parser::parse_root_tables(&content, roots)?;
+    roots.iter().for_each(|root| {
+        parsed.entry(root.to_string()).or_insert(Value::Null);
+    });
+    Ok(parsed)
+}
+
+#[tauri::command]
+pub fn parse_saved_variables(
+    path: String,
+    roots: Option<Vec<String>>,
+) -> Result<Map<String, Value>, SavedVariablesError> {
+    let requested: Vec<&str> = match roots.as_ref() {
+        Some(names) if !names.is_empty() => names.iter().map(|name| name.trim()).collect(),
+        _ => SAVED_VARIABLES_ROOTS.to_vec(),
+    };
+
+    read_root_tables(&path, &requested)
+}
@@ -0,0 +1,174 @@
+use crate::game_monitor::GameFlavor;
+use crate::lua_parser;
+use crate::saved_vars_command::SAVED_VARIABLES_ROOTS;
+use crate::watcher;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use tauri::{Emitter, Runtime};
+use ts_rs::TS;
+use xxhash_rust::xxh64::xxh64;
+
+const DB_ROOT: &str = "PvP_Scalpel_DB";
+
+// Last parsed snapshot per SavedVariables path, reduced to content hashes.
+static SNAPSHOT_CACHE: OnceLock<Mutex<HashMap<String, CachedSnapshot>>> = OnceLock::new();
+
+#[derive(Default)]
+struct CachedSnapshot {
+    matches: HashMap<String, u64>,
+    roots: HashMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/SavedVarsDelta.ts")]
+pub struct SavedVarsDelta {
+    pub account: String,
+    pub path: String,
+    pub flavor: Option<GameFlavor>,
+    pub game_state: String,
+    /// True when there was no previous snapshot and `added` holds every match.
+    pub full: bool,
+    #[ts(type = "Record<string, unknown>")]
+    pub added: Map<String, Value>,
+    #[ts(type = "Record<string, unknown>")]
+    pub changed: Map<String, Value>,
+    pub removed: Vec<String>,
+    /// Every match key in file order, so consumers can rebuild the list without the payloads.
+    pub order: Vec<String>,
+    /// Non-DB root tables (GC, spell data, interrupt ids) whose content changed.
+    #[ts(type = "Record<string, unknown>")]
+    pub roots: Map<String, Value>,
+}
+
+fn hash_value(value: &Value) -> u64 {
+    serde_json::to_vec(value)
+        .map(|bytes| xxh64(&bytes, 0))
+        .unwrap_or(0)
+}
+
+fn db_entries(db: Option<&Value>) -> Vec<&Value> {
+    match db {
+        Some(Value::Array(items)) => items.iter().filter(|item| !item.is_null()).collect(),
+        Some(Value::Object(map)) => map.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Entries without a matchKey still need a stable identity, so fall back to their content hash.
+fn entry_key(entry: &Value, hash: u64) -> String {
+    entry
+        .get("matchKey")
+        .and_then(|v| v.as_str())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| format!("hash:{hash:016x}"))
+}
+
+fn diff_snapshot(
+    previous: Option<&CachedSnapshot>,
+    parsed: &Map<String, Value>,
+    delta: &mut SavedVarsDelta,
+) -> CachedSnapshot {
+    let mut next = CachedSnapshot::default();
+
+    for entry in db_entries(parsed.get(DB_ROOT)) {
+        let hash = hash_value(entry);
+        let key = entry_key(entry, hash);
+        match previous.and_then(|snapshot| snapshot.matches.get(&key)) {
+            None => {
+                delta.added.insert(key.clone(), entry.clone());
+            }
+            Some(old_hash) if *old_hash != hash => {
+                delta.changed.insert(key.clone(), entry.clone());
+            }
+            Some(_) => {}
+        }
+        if next.matches.insert(key.clone(), hash).is_none() {
+            delta.order.push(key);
+        }
+    }
+
+    if let Some(snapshot) = previous {
+        delta.removed = snapshot
+            .matches
+            .keys()
+            .filter(|key| !next.matches.contains_key(*key))
+            .cloned()
+            .collect();
+        delta.removed.sort();
+    }
+
+    for (name, value) in parsed.iter().filter(|(name, _)| name.as_str() != DB_ROOT) {
+        let hash = hash_value(value);
+        let unchanged = previous
+            .and_then(|snapshot| snapshot.roots.get(name))
+            .map(|old_hash| *old_hash == hash)
+            .unwrap_or(false);
+        if !unchanged {
+            delta.roots.insert(name.clone(), value.clone());
+        }
+        next.roots.insert(name.clone(), hash);
+    }
+
+    next
+}
+
+/// Parses the content of `path` and diffs it against the cached snapshot. Parse failures
+/// leave the cache untouched.
+fn compute_delta(
+    path: &Path,
+    content: &str,
+    account: &str,
+    game_state: &str,
+    reset: bool,
+) -> Result<SavedVarsDelta, String> {
+    let mut parsed = lua_parser::parse_root_tables(content, &SAVED_VARIABLES_ROOTS)
+        .map_err(|e| format!("Failed parsing SavedVariables: {e}"))?;
+    SAVED_VARIABLES_ROOTS.iter().for_each(|root| {
+        parsed.entry(root.to_string()).or_insert(Value::Null);
+    });
+
+    let cache_key = path.to_string_lossy().to_string();
+    let store = SNAPSHOT_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut guard = store
+        .lock()
+        .map_err(|_| "SavedVariables snapshot cache poisoned".to_string())?;
+
+    let previous = if reset { None } else { guard.get(&cache_key) };
+    let mut delta = SavedVarsDelta {
+        account: account.to_string(),
+        path: cache_key.clone(),
+        flavor: watcher::extract_flavor(path),
+        game_state: game_state.to_string(),
+        full: previous.is_none(),
+        added: Map::new(),
+        changed: Map::new(),
+        removed: Vec::new(),
+        order: Vec::new(),
+        roots: Map::new(),
+    };
+    let next = diff_snapshot(previous, &parsed, &mut delta);
+    guard.insert(cache_key, next);
+
+    Ok(delta)
+}
+
+/// Emits `savedvars-delta` for `path`; `reset` forces a full snapshot (used by rescans).
+/// Sent for every settled write, even an empty one, since the frontend refreshes on it.
+pub fn emit_delta<R: Runtime, E: Emitter<R>>(
+    emitter: &E,
+    path: &Path,
+    content: &str,
+    account: &str,
+    game_state: &str,
+    reset: bool,
+) -> Result<(), String> {
+    let delta = compute_delta(path, content, account, game_state, reset)?;
+    emitter
+        .emit("savedvars-delta", delta)
+        .map_err(|e| format!("SavedVariables delta event failed: {e}"))
+}
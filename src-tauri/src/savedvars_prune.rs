@@ -0,0 +1,175 @@
+use crate::computed_matches_command;
+use crate::file_lock;
+use crate::fs_atomic;
+use crate::gc_lifecycle::{self, GcEntry, GcState, GC_ROOT};
+use crate::log_command;
+use crate::lua_parser::{self, LuaKey, LuaValue};
+use crate::lua_writer;
+use crate::match_time::{self, SECONDS_PER_DAY};
+use crate::savedvars_vault;
+use crate::watcher;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use ts_rs::TS;
+
+const DB_ROOT: &str = "PvP_Scalpel_DB";
+const DEFAULT_MAX_AGE_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/SavedVarsPruneReport.ts")]
+pub struct SavedVarsPruneReport {
+    pub removed_keys: Vec<String>,
+    #[ts(type = "number")]
+    pub bytes_before: u64,
+    #[ts(type = "number")]
+    pub bytes_after: u64,
+    #[ts(type = "number")]
+    pub bytes_reclaimed: u64,
+    /// Copy of the file taken before rewriting it; `null` when nothing was pruned.
+    pub backup_path: Option<String>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn string_field<'a>(value: &'a LuaValue, name: &str) -> Option<&'a str> {
+    match value.field(name) {
+        Some(LuaValue::String(text)) => Some(text.trim()).filter(|v| !v.is_empty()),
+        _ => None,
+    }
+}
+
+// When the match was synced, falling back to when it was played for keys synced before the
+// GC table carried timestamps.
+fn reference_seconds(entry: &LuaValue, gc: &GcEntry) -> Option<i64> {
+    gc.entered_at.get(&GcState::Synced).copied().or_else(|| {
+        entry
+            .field("matchDetails")
+            .and_then(|details| string_field(details, "timestamp"))
+            .and_then(match_time::timestamp_seconds)
+    })
+}
+
+// Drops the pruned entries and renumbers the positional part so the array stays contiguous.
+fn without_entries(fields: Vec<(LuaKey, LuaValue)>, pruned: &HashSet<usize>) -> LuaValue {
+    let mut next_index: i64 = 1;
+    let mut position: i64 = 1;
+    let mut kept = Vec::with_capacity(fields.len());
+    for (index, (key, value)) in fields.into_iter().enumerate() {
+        let positional = key == LuaKey::Index(position);
+        if positional {
+            position += 1;
+        }
+        if pruned.contains(&index) {
+            continue;
+        }
+        if positional {
+            kept.push((LuaKey::Index(next_index), value));
+            next_index += 1;
+        } else {
+            kept.push((key, value));
+        }
+    }
+    LuaValue::Table(kept)
+}
+
+/// Removes matches from `PvP_Scalpel_DB` that are synced, present in the computed store and
+/// older than `max_age_days` (default 30). Their GC keys move to `expired`. Refuses to run
+/// while the game is open, and snapshots the file into the backup vault before rewriting it.
+#[tauri::command]
+pub fn prune_synced_matches(
+    app: AppHandle,
+    path: String,
+    max_age_days: Option<u32>,
+) -> Result<SavedVarsPruneReport, String> {
+    if watcher::is_wow_running(Path::new(&path)) {
+        return Err("Close WoW before pruning SavedVariables".to_string());
+    }
+    let account = watcher::extract_account_name(Path::new(&path))
+        .ok_or_else(|| "Not an account SavedVariables file".to_string())?;
+    let flavor = watcher::extract_flavor(Path::new(&path));
+    let persisted = computed_matches_command::persisted_match_keys(&app, &account, flavor)?;
+    let max_age_days = max_age_days.unwrap_or(DEFAULT_MAX_AGE_DAYS);
+    let cutoff = (now_ms() / 1000) as i64 - i64::from(max_age_days) * SECONDS_PER_DAY;
+
+    let _lock = file_lock::lock(Path::new(&path))?;
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed reading SavedVariables: {e}"))?;
+    let bytes_before = content.len() as u64;
+    let mut report = SavedVarsPruneReport {
+        removed_keys: Vec::new(),
+        bytes_before,
+        bytes_after: bytes_before,
+        bytes_reclaimed: 0,
+        backup_path: None,
+    };
+
+    let mut roots: HashMap<String, LuaValue> =
+        lua_parser::parse_roots(&content, &[DB_ROOT, GC_ROOT])
+            .map_err(|e| format!("Failed parsing SavedVariables: {e}"))?
+            .into_iter()
+            .map(|root| (root.name, root.value))
+            .collect();
+    let (Some(LuaValue::Table(db_fields)), Some(mut gc)) =
+        (roots.remove(DB_ROOT), roots.remove(GC_ROOT))
+    else {
+        return Ok(report);
+    };
+
+    let gc_entries: HashMap<String, GcEntry> = gc_lifecycle::read_entries(&gc)
+        .into_iter()
+        .map(|entry| (entry.key.clone(), entry))
+        .collect();
+    let mut pruned = HashSet::new();
+    for (index, (_, entry)) in db_fields.iter().enumerate() {
+        let Some(key) = string_field(entry, "matchKey") else {
+            continue;
+        };
+        let Some(gc_entry) = gc_entries.get(key) else {
+            continue;
+        };
+        let old_enough = reference_seconds(entry, gc_entry).is_some_and(|at| at <= cutoff);
+        if gc_entry.state == Some(GcState::Synced) && persisted.contains(key) && old_enough {
+            pruned.insert(index);
+            report.removed_keys.push(key.to_string());
+        }
+    }
+    if pruned.is_empty() {
+        return Ok(report);
+    }
+
+    let now_s = (now_ms() / 1000) as i64;
+    for key in &report.removed_keys {
+        gc_lifecycle::apply_transition(&mut gc, key, GcState::Expired, now_s)
+            .map_err(|r| format!("Unable to expire GC key {}", r.key))?;
+    }
+    let db = without_entries(db_fields, &pruned);
+    let updated = lua_writer::replace_roots(&content, &[(DB_ROOT, db), (GC_ROOT, gc)])?;
+
+    let backup_path =
+        savedvars_vault::snapshot(&app, &account, flavor, content.as_bytes(), "pre-prune")?;
+    fs_atomic::write_atomic(Path::new(&path), updated.as_bytes())
+        .map_err(|e| format!("Failed writing SavedVariables: {e}"))?;
+
+    report.bytes_after = updated.len() as u64;
+    report.bytes_reclaimed = bytes_before.saturating_sub(report.bytes_after);
+    report.backup_path = Some(backup_path.to_string_lossy().to_string());
+    log_command::emit_log(
+        &app,
+        &format!(
+            "SavedVariables pruned ({} matches, {} bytes)",
+            report.removed_keys.len(),
+            report.bytes_reclaimed
+        ),
+    );
+    Ok(report)
+}
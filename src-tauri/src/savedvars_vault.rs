@@ -0,0 +1,274 @@
+use crate::file_lock;
+use crate::fs_atomic;
+use crate::game_monitor::GameFlavor;
+use crate::log_command;
+use crate::lua_parser;
+use crate::saved_vars_command::SAVED_VARIABLES_ROOTS;
+use crate::watcher;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+use xxhash_rust::xxh64::xxh64;
+
+const VAULT_DIR: &str = "savedvars_backups";
+const WATCHER_REASON: &str = "watcher";
+// Retention: the newest snapshots are always kept, older ones thin out to one per day and
+// disappear after two weeks. The latest snapshot of every other reason (`pre-prune`,
+// `pre-restore`) is a safety copy and is always kept too.
+const KEEP_RECENT: usize = 10;
+const KEEP_DAILY_DAYS: u64 = 14;
+const DAY_MS: u64 = 86_400_000;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/SavedVarsBackup.ts")]
+pub struct SavedVarsBackup {
+    pub account: String,
+    pub flavor: GameFlavor,
+    pub file_name: String,
+    /// xxh64 of the file content, hex.
+    pub hash: String,
+    /// What took the snapshot: `watcher`, `pre-prune` or `pre-restore`.
+    pub reason: String,
+    #[ts(type = "number")]
+    pub created_at_ms: u64,
+    #[ts(type = "number")]
+    pub size: u64,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// The same account exists once per flavor, so each flavor has its own vault.
+fn vault_root(app: &AppHandle, flavor: GameFlavor) -> Result<PathBuf, String> {
+    let mut dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Unable to resolve app data directory: {e}"))?;
+    dir.push(VAULT_DIR);
+    Ok(flavor.partition(dir))
+}
+
+// Account names come from WTF folder names, but commands also take them from the frontend.
+fn account_dir(app: &AppHandle, account: &str, flavor: GameFlavor) -> Result<PathBuf, String> {
+    let account = account.trim();
+    if account.is_empty() || account == "." || account == ".." || account.contains(['/', '\\']) {
+        return Err(format!("Invalid account name: {account}"));
+    }
+    Ok(vault_root(app, flavor)?.join(account))
+}
+
+// Snapshots are named `<created_at_ms>-<hash>-<reason>.lua`.
+fn parse_backup(account: &str, flavor: GameFlavor, path: &Path) -> Option<SavedVarsBackup> {
+    let file_name = path.file_name()?.to_str()?.to_string();
+    let stem = file_name.strip_suffix(".lua")?;
+    let mut parts = stem.splitn(3, '-');
+    let created_at_ms = parts.next()?.parse::<u64>().ok()?;
+    let hash = parts.next().filter(|hash| hash.len() == 16)?.to_string();
+    let reason = parts.next()?.to_string();
+    let size = fs::metadata(path).ok()?.len();
+
+    Some(SavedVarsBackup {
+        account: account.to_string(),
+        flavor,
+        file_name,
+        hash,
+        reason,
+        created_at_ms,
+        size,
+    })
+}
+
+/// Snapshots of one account, newest first.
+fn list_account(dir: &Path, account: &str, flavor: GameFlavor) -> Vec<SavedVarsBackup> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<SavedVarsBackup> = read_dir
+        .flatten()
+        .filter_map(|entry| parse_backup(account, flavor, &entry.path()))
+        .collect();
+    backups.sort_by(|a, b| {
+        b.created_at_ms
+            .cmp(&a.created_at_ms)
+            .then_with(|| b.file_name.cmp(&a.file_name))
+    });
+    backups
+}
+
+fn apply_retention(dir: &Path, account: &str, flavor: GameFlavor) -> usize {
+    let now = now_ms();
+    let mut days_kept = HashSet::new();
+    let mut reasons_kept = HashSet::new();
+    let mut removed = 0;
+    for (index, backup) in list_account(dir, account, flavor).into_iter().enumerate() {
+        let day = backup.created_at_ms / DAY_MS;
+        let recent_day = now.saturating_sub(backup.created_at_ms) < KEEP_DAILY_DAYS * DAY_MS;
+        // `days_kept.insert` runs for every snapshot so a day counts as covered by its newest one.
+        let first_of_day = days_kept.insert(day);
+        let latest_safety_copy =
+            backup.reason != WATCHER_REASON && reasons_kept.insert(backup.reason.clone());
+        if index < KEEP_RECENT || (recent_day && first_of_day) || latest_safety_copy {
+            continue;
+        }
+        if fs::remove_file(dir.join(&backup.file_name)).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Stores `bytes` in the account's vault unless a snapshot with the same content already
+/// exists, then applies retention. A safety snapshot only reuses one taken for the same reason,
+/// so it never depends on a `watcher` snapshot that retention may drop. Returns the snapshot
+/// holding this content. Installs outside a `_<flavor>_` folder share the retail vault.
+pub fn snapshot(
+    app: &AppHandle,
+    account: &str,
+    flavor: Option<GameFlavor>,
+    bytes: &[u8],
+    reason: &str,
+) -> Result<PathBuf, String> {
+    let flavor = flavor.unwrap_or_default();
+    let dir = account_dir(app, account, flavor)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Unable to create backup directory: {e}"))?;
+
+    let hash = format!("{:016x}", xxh64(bytes, 0));
+    if let Some(existing) = list_account(&dir, account, flavor)
+        .into_iter()
+        .find(|backup| backup.hash == hash && (reason == WATCHER_REASON || backup.reason == reason))
+    {
+        return Ok(dir.join(existing.file_name));
+    }
+
+    let path = dir.join(format!("{}-{hash}-{reason}.lua", now_ms()));
+    fs_atomic::write_atomic(&path, bytes)
+        .map_err(|e| format!("Unable to write SavedVariables backup: {e}"))?;
+    apply_retention(&dir, account, flavor);
+    Ok(path)
+}
+
+/// Snapshots content the watcher has seen settle.
+pub fn snapshot_settled(app: &AppHandle, account: &str, flavor: Option<GameFlavor>, content: &str) {
+    if let Err(e) = snapshot(app, account, flavor, content.as_bytes(), WATCHER_REASON) {
+        log_command::emit_log(app, &format!("SavedVariables backup skipped: {e}"));
+    }
+}
+
+/// Vault contents of one flavor (retail when omitted), newest first; all accounts when
+/// `account` is omitted.
+#[tauri::command]
+pub fn list_savedvars_backups(
+    app: AppHandle,
+    account: Option<String>,
+    flavor: Option<GameFlavor>,
+) -> Result<Vec<SavedVarsBackup>, String> {
+    let flavor = flavor.unwrap_or_default();
+    if let Some(account) = account.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+        return Ok(list_account(
+            &account_dir(&app, account, flavor)?,
+            account,
+            flavor,
+        ));
+    }
+
+    let Ok(read_dir) = fs::read_dir(vault_root(&app, flavor)?) else {
+        return Ok(Vec::new());
+    };
+    let mut backups: Vec<SavedVarsBackup> = read_dir
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let account = entry.file_name().to_str()?.to_string();
+            // The retail vault also holds the other flavors' vaults.
+            if GameFlavor::from_folder(&account).is_some() {
+                return None;
+            }
+            Some(list_account(&entry.path(), &account, flavor))
+        })
+        .flatten()
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at_ms));
+    Ok(backups)
+}
+
+/// Replaces the SavedVariables file at `path` with a snapshot of the same account. The current
+/// file is snapshotted first; refuses while the game is running since it would overwrite it.
+#[tauri::command]
+pub fn restore_savedvars_backup(
+    app: AppHandle,
+    path: String,
+    file_name: String,
+) -> Result<SavedVarsBackup, String> {
+    let target = Path::new(&path);
+    if watcher::is_wow_running(target) {
+        return Err("Close WoW before restoring SavedVariables".to_string());
+    }
+    let account = watcher::extract_account_name(target)
+        .ok_or_else(|| "Not an account SavedVariables file".to_string())?;
+    let flavor = watcher::extract_flavor(target).unwrap_or_default();
+    let dir = account_dir(&app, &account, flavor)?;
+    let backup = list_account(&dir, &account, flavor)
+        .into_iter()
+        .find(|backup| backup.file_name == file_name)
+        .ok_or_else(|| format!("Backup not found: {file_name}"))?;
+
+    let content = fs::read_to_string(dir.join(&backup.file_name))
+        .map_err(|e| format!("Unable to read SavedVariables backup: {e}"))?;
+    lua_parser::parse_roots(&content, &SAVED_VARIABLES_ROOTS)
+        .map_err(|e| format!("Backup does not parse: {e}"))?;
+
+    let _lock = file_lock::lock(target)?;
+    if let Ok(current) = fs::read(target) {
+        snapshot(&app, &account, Some(flavor), &current, "pre-restore")?;
+    }
+    fs_atomic::write_atomic(target, content.as_bytes())
+        .map_err(|e| format!("Failed writing SavedVariables: {e}"))?;
+
+    log_command::emit_log(&app, &format!("SavedVariables restored from {file_name}"));
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retention_keeps_the_latest_safety_snapshot_of_each_reason() {
+        let dir = std::env::temp_dir().join(format!("pvp-scalpel-vault-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let now = now_ms();
+        let write = |age: u64, reason: &str| {
+            let name = format!("{}-{:016x}-{reason}.lua", now - age, age);
+            fs::write(dir.join(&name), b"PvP_Scalpel_DB = {}").unwrap();
+            name
+        };
+        let older_prune = write(60_000, "pre-prune");
+        let prune = write(50_000, "pre-prune");
+        let restore = write(40_000, "pre-restore");
+        for age in 1..=KEEP_RECENT as u64 {
+            write(age * 1_000, WATCHER_REASON);
+        }
+
+        let removed = apply_retention(&dir, "ACCOUNT", GameFlavor::Retail);
+        let kept: HashSet<String> = list_account(&dir, "ACCOUNT", GameFlavor::Retail)
+            .into_iter()
+            .map(|backup| backup.file_name)
+            .collect();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(removed, 1);
+        assert!(kept.contains(&prune) && kept.contains(&restore));
+        assert!(!kept.contains(&older_prune));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::gwp_command;
+#[cfg(windows)]
 use registry::{Data, Hive, Security};
 use serde::Serialize;
 use std::sync::OnceLock;
@@ -15,6 +17,7 @@ pub struct LocalVersions {
     pub addon_version: Option<String>,
 }
 
+#[cfg(windows)]
 fn read_desktop_version() -> Option<String> {
     // Desktop version is stored in uninstall registry entries (read-only).
     let keys = [
@@ -40,34 +43,22 @@ fn read_desktop_version() -> Option<String> {
     None
 }
 
-fn read_addon_version() -> Option<String> {
-    // Resolve addon version from the WoW install path in the registry.
-    let main_key = r"SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall\Battle.net\Game\wow";
-
-    if let Ok(key) = Hive::LocalMachine.open(main_key, Security::Read) {
-        if let Ok(Data::String(path)) = key.value("InstallLocation") {
-            let wow = path.to_string_lossy();
-            let addons_root = format!("{}\\Interface\\AddOns", wow.trim_end_matches(['\\', '/']));
-            return read_addon_version_from_root(&addons_root);
-        }
-    }
-
-    let fallback = r"SOFTWARE\WOW6432Node\Blizzard Entertainment\World of Warcraft";
-
-    if let Ok(key) = Hive::LocalMachine.open(fallback, Security::Read) {
-        if let Ok(Data::String(path)) = key.value("InstallPath") {
-            let wow = path.to_string_lossy();
-            let addons_root = format!("{}\\Interface\\AddOns", wow.trim_end_matches(['\\', '/']));
-            return read_addon_version_from_root(&addons_root);
-        }
-    }
-
+#[cfg(not(windows))]
+fn read_desktop_version() -> Option<String> {
+    // Only Windows installs register an uninstall entry.
     None
 }
 
-fn read_addon_version_from_root(addons_root: &str) -> Option<String> {
+fn read_addon_version() -> Option<String> {
+    // Resolve addon version from the detected install: `<flavor>/WTF/Account` -> `<flavor>/Interface/AddOns`.
+    let account_path = gwp_command::get_wow_path()?;
+    let flavor_dir = Path::new(&account_path).parent()?.parent()?;
+    read_addon_version_from_root(&flavor_dir.join("Interface").join("AddOns"))
+}
+
+fn read_addon_version_from_root(addons_root: &Path) -> Option<String> {
     // Addon version is stored inside the .toc file.
-    let toc_path = Path::new(addons_root).join("PvP_Scalpel").join("PvP_Scalpel.toc");
+    let toc_path = addons_root.join("PvP_Scalpel").join("PvP_Scalpel.toc");
     let contents = fs::read_to_string(&toc_path).ok()?;
 
     for line in contents.lines() {
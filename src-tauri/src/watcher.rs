@@ -1,10 +1,31 @@
-use notify::{Config, Event, RecommendedWatcher, Watcher};
+use notify::Event;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
-use sysinfo::{ProcessesToUpdate, System};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 use tauri::{AppHandle, Emitter, Runtime};
+use ts_rs::TS;
+use xxhash_rust::xxh64::xxh64;
+use crate::game_monitor::{self, GameFlavor};
+use crate::gc_state_command;
+use crate::gwp_command;
 use crate::log_command;
+use crate::lua_parser;
+use crate::saved_vars_command::SAVED_VARIABLES_ROOTS;
+use crate::savedvars_delta;
+use crate::savedvars_vault;
+
+// Write-settle detection: how often pending paths are re-checked, how long size and mtime must
+// hold still, and when a write that never settles is given up on.
+const SETTLE_TICK: Duration = Duration::from_millis(100);
+const SETTLE_QUIET: Duration = Duration::from_millis(300);
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(15);
+
+const SAVED_VARS_DIR: &str = "SavedVariables";
+const SAVED_VARS_FILE: &str = "PvP_Scalpel.lua";
 
 #[derive(Clone, Copy)]
 enum GameRuntimeState {
@@ -37,106 +58,269 @@ fn resolve_game_runtime_state(is_running_now: bool, was_running: &mut bool) -> G
     GameRuntimeState::NotRunning
 }
 
-#[cfg(target_os = "windows")]
-fn is_wow_running() -> bool {
-    let mut system = System::new_all();
-    system.refresh_processes(ProcessesToUpdate::All, true);
-    system.processes().values().any(|process| {
-        let name = process.name().to_string_lossy().to_ascii_lowercase();
-        name == "wow.exe" || name == "wow"
-    })
-}
-
-#[cfg(not(target_os = "windows"))]
-fn is_wow_running() -> bool {
-    let mut system = System::new_all();
-    system.refresh_processes(ProcessesToUpdate::All, true);
-    system.processes().values().any(|process| {
-        let name = process.name().to_string_lossy().to_ascii_lowercase();
-        name == "world of warcraft" || name == "wow"
-    })
-}
-
-pub fn create_watcher(app: AppHandle) -> notify::Result<RecommendedWatcher> {
-    let mut was_running = is_wow_running();
-    RecommendedWatcher::new(
-        move |res: Result<Event, notify::Error>| match res {
-            Ok(event) => {
-                let mut game_state: Option<GameRuntimeState> = None;
-                for path in event.paths {
-
-                    if is_our_saved_vars(&path) {
-                        let runtime_state = *game_state.get_or_insert_with(|| {
-                            resolve_game_runtime_state(is_wow_running(), &mut was_running)
-                        });
-                        log_command::emit_log(&app, "SavedVariables updated");
-                        if let Some(account) = extract_account_name(&path) {
-                            let payload = serde_json::json!({
-                                "account": account,
-                                "path": path.to_string_lossy(),
-                                "gameState": runtime_state.as_str(),
-                            });
-
-                            if app.emit("savedvars-updated", payload).is_err() {
-                                log_command::emit_log(&app, "SavedVariables event failed");
-                            }
-                        }
-                    }
-                }
-            }
-            Err(_) => log_command::emit_log(&app, "Watcher error"),
-        },
-        Config::default().with_poll_interval(Duration::from_millis(400)),
-    )
+/// Whether the client that owns the SavedVariables file at `path` is running.
+pub fn is_wow_running(path: &Path) -> bool {
+    game_monitor::is_flavor_running(extract_flavor(path))
 }
 
-pub fn emit_existing_saved_vars<R: Runtime, E: Emitter<R>>(emitter: &E, root: &Path) -> bool {
-    if !root.exists() {
-        return false;
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/SavedVarsUpdated.ts")]
+pub struct SavedVarsUpdated {
+    pub account: String,
+    pub path: String,
+    /// `null` when the install is not inside a `_<flavor>_` folder.
+    pub flavor: Option<GameFlavor>,
+    pub game_state: String,
+    /// xxh64 of the settled file content, hex; `null` when a rescan could not read the file.
+    pub content_hash: Option<String>,
+}
+
+fn content_hash(content: &str) -> String {
+    format!("{:016x}", xxh64(content.as_bytes(), 0))
+}
+
+// Raw notify events for one path, collected until the file stops changing.
+struct PendingSettle {
+    first_event: Instant,
+    last_event: Instant,
+    signature: Option<(u64, Option<SystemTime>)>,
+    stable_since: Instant,
+}
+
+impl PendingSettle {
+    fn new(now: Instant) -> Self {
+        PendingSettle {
+            first_event: now,
+            last_event: now,
+            signature: None,
+            stable_since: now,
+        }
     }
+}
 
-    let mut found_any = false;
-    let mut stack: Vec<PathBuf> = vec![root.to_path_buf()];
-
-    while let Some(dir) = stack.pop() {
-        let entries = match fs::read_dir(&dir) {
-            Ok(entries) => entries,
-            Err(_) => continue,
-        };
-
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                stack.push(path);
-                continue;
+fn file_signature(path: &Path) -> Option<(u64, Option<SystemTime>)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()))
+}
+
+/// Returns the content once size and mtime have held still for `SETTLE_QUIET` after the last
+/// event and every root table parses; a half-written file fails one of the two.
+fn settled_content(path: &Path, pending: &mut PendingSettle, now: Instant) -> Option<String> {
+    let signature = file_signature(path);
+    if signature != pending.signature {
+        pending.signature = signature;
+        pending.stable_since = now;
+        return None;
+    }
+    let (size, _) = signature?;
+    if now - pending.last_event < SETTLE_QUIET || now - pending.stable_since < SETTLE_QUIET {
+        return None;
+    }
+
+    let content = fs::read_to_string(path).ok()?;
+    if content.len() as u64 != size {
+        return None;
+    }
+    lua_parser::parse_roots(&content, &SAVED_VARIABLES_ROOTS).ok()?;
+    Some(content)
+}
+
+fn emit_settled(
+    app: &AppHandle,
+    path: &Path,
+    content: &str,
+    runtime_state: GameRuntimeState,
+    last_hashes: &mut HashMap<PathBuf, String>,
+) {
+    let hash = content_hash(content);
+    if last_hashes.get(path) == Some(&hash) {
+        return;
+    }
+    last_hashes.insert(path.to_path_buf(), hash.clone());
+
+    log_command::emit_log(app, "SavedVariables updated");
+    let Some(account) = extract_account_name(path) else {
+        return;
+    };
+    let flavor = extract_flavor(path);
+    let payload = SavedVarsUpdated {
+        account: account.clone(),
+        path: path.to_string_lossy().to_string(),
+        flavor,
+        game_state: runtime_state.as_str().to_string(),
+        content_hash: Some(hash),
+    };
+    if app.emit("savedvars-updated", payload).is_err() {
+        log_command::emit_log(app, "SavedVariables event failed");
+    }
+
+    if savedvars_delta::emit_delta(app, path, content, &account, runtime_state.as_str(), false)
+        .is_err()
+    {
+        log_command::emit_log(app, "SavedVariables delta skipped");
+    }
+
+    savedvars_vault::snapshot_settled(app, &account, flavor, content);
+}
+
+// Owns everything downstream of notify for one install: coalesces events per path and handles
+// each settled write once. Exits when the watcher, and with it the sender, is dropped.
+fn run_settle_loop(app: AppHandle, events: Receiver<PathBuf>, flavor: Option<GameFlavor>) {
+    let mut was_running = game_monitor::is_flavor_running(flavor);
+    let mut pending: HashMap<PathBuf, PendingSettle> = HashMap::new();
+    let mut last_hashes: HashMap<PathBuf, String> = HashMap::new();
+
+    loop {
+        match events.recv_timeout(SETTLE_TICK) {
+            Ok(path) => {
+                let now = Instant::now();
+                pending
+                    .entry(path)
+                    .and_modify(|entry| entry.last_event = now)
+                    .or_insert_with(|| PendingSettle::new(now));
             }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
 
-            if is_our_saved_vars(&path) {
-                found_any = true;
-                let runtime_state = if is_wow_running() {
-                    GameRuntimeState::Running
-                } else {
-                    GameRuntimeState::NotRunning
-                };
-                let payload = serde_json::json!({
-                    "account": extract_account_name(&path).unwrap_or_default(),
-                    "path": path.to_string_lossy(),
-                    "gameState": runtime_state.as_str(),
-                });
-
-                let _ = emitter.emit("savedvars-updated", payload);
+        let now = Instant::now();
+        let mut settled: Vec<(PathBuf, String)> = Vec::new();
+        pending.retain(|path, entry| {
+            if let Some(content) = settled_content(path, entry, now) {
+                settled.push((path.clone(), content));
+                return false;
+            }
+            if now - entry.first_event > SETTLE_TIMEOUT {
+                log_command::emit_log(&app, "SavedVariables write did not settle");
+                return false;
+            }
+            true
+        });
+        if settled.is_empty() {
+            continue;
+        }
+
+        let runtime_state =
+            resolve_game_runtime_state(game_monitor::is_flavor_running(flavor), &mut was_running);
+        // The game monitor flushes when the client exits; re-check once its logout write has
+        // settled, for anything queued in between.
+        if matches!(runtime_state, GameRuntimeState::JustClosed) {
+            gc_state_command::flush_pending_writes(&app, flavor);
+        }
+        for (path, content) in settled {
+            emit_settled(&app, &path, &content, runtime_state, &mut last_hashes);
+        }
+    }
+}
+
+/// Starts the settle loop of one install; it runs until every clone of the returned sender is
+/// dropped. `flavor` is the client whose runs gate its writes.
+pub fn spawn_settle_loop(app: AppHandle, flavor: Option<GameFlavor>) -> Sender<PathBuf> {
+    let (sender, events) = mpsc::channel::<PathBuf>();
+    thread::spawn(move || run_settle_loop(app, events, flavor));
+    sender
+}
+
+/// notify callback: forwards our SavedVariables paths to the settle loop, signals `layout` when
+/// an entry appears directly under `WTF/Account`, and reports errors to the watcher's supervisor.
+pub fn event_handler(
+    app: AppHandle,
+    events: Sender<PathBuf>,
+    layout: Sender<()>,
+    errors: Sender<String>,
+) -> impl FnMut(Result<Event, notify::Error>) + Send + 'static {
+    move |res| match res {
+        Ok(event) => {
+            let created = event.kind.is_create();
+            for path in event.paths {
+                if is_our_saved_vars(&path) {
+                    let _ = events.send(path);
+                } else if created && is_account_entry(&path) {
+                    let _ = layout.send(());
+                }
             }
         }
+        Err(e) => {
+            log_command::emit_log(&app, "Watcher error");
+            let _ = errors.send(e.to_string());
+        }
     }
+}
 
+/// Emits every install's SavedVariables again; true when any were found.
+pub fn rescan<R: Runtime, E: Emitter<R>>(emitter: &E) -> bool {
+    let mut found = false;
+    for install in gwp_command::find_installs() {
+        found |= emit_existing_saved_vars(emitter, Path::new(&install.account_path));
+    }
+    found
+}
+
+/// `<Account>/SavedVariables` folders under a `WTF/Account` root.
+pub fn saved_variables_dirs(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path().join(SAVED_VARS_DIR))
+        .filter(|dir| dir.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+pub fn emit_existing_saved_vars<R: Runtime, E: Emitter<R>>(emitter: &E, root: &Path) -> bool {
+    let mut found_any = false;
+    for dir in saved_variables_dirs(root) {
+        found_any |= emit_saved_vars_in(emitter, &dir);
+    }
     found_any
 }
 
+/// Emits the addon file of one SavedVariables folder; false when it has none.
+pub fn emit_saved_vars_in<R: Runtime, E: Emitter<R>>(emitter: &E, dir: &Path) -> bool {
+    let path = dir.join(SAVED_VARS_FILE);
+    if !path.is_file() || !is_our_saved_vars(&path) {
+        return false;
+    }
+
+    let runtime_state = if is_wow_running(&path) {
+        GameRuntimeState::Running
+    } else {
+        GameRuntimeState::NotRunning
+    };
+    let account = extract_account_name(&path).unwrap_or_default();
+    let content = fs::read_to_string(&path).ok();
+    let payload = SavedVarsUpdated {
+        account: account.clone(),
+        path: path.to_string_lossy().to_string(),
+        flavor: extract_flavor(&path),
+        game_state: runtime_state.as_str().to_string(),
+        content_hash: content.as_deref().map(content_hash),
+    };
+
+    let _ = emitter.emit("savedvars-updated", payload);
+    // Rescans start consumers from scratch, so send the whole snapshot.
+    if let Some(content) = content {
+        let _ = savedvars_delta::emit_delta(
+            emitter,
+            &path,
+            &content,
+            &account,
+            runtime_state.as_str(),
+            true,
+        );
+    }
+    true
+}
+
 fn extract_account_saved_vars_info(path: &Path) -> Option<String> {
     let mut comps = path.components().rev();
 
     comps.next()?; // filename
-    if comps.next()?.as_os_str() != "SavedVariables" {
+    if comps.next()?.as_os_str() != SAVED_VARS_DIR {
         return None;
     }
 
@@ -149,12 +333,28 @@ fn extract_account_saved_vars_info(path: &Path) -> Option<String> {
 }
 
 fn is_our_saved_vars(path: &Path) -> bool {
-    matches!(
-        path.file_name().and_then(|n| n.to_str()),
-        Some("PvP_Scalpel.lua")
-    ) && extract_account_saved_vars_info(path).is_some()
+    path.file_name().and_then(|n| n.to_str()) == Some(SAVED_VARS_FILE)
+        && extract_account_saved_vars_info(path).is_some()
+}
+
+// A direct child of `WTF/Account`, i.e. a new account folder.
+fn is_account_entry(path: &Path) -> bool {
+    path.parent().and_then(|parent| parent.file_name()) == Some("Account".as_ref())
 }
 
 pub fn extract_account_name(path: &Path) -> Option<String> {
     extract_account_saved_vars_info(path)
 }
+
+/// Flavor of a SavedVariables path, from the `_<flavor>_` folder above `WTF`.
+pub fn extract_flavor(path: &Path) -> Option<GameFlavor> {
+    let mut comps = path.components().rev().skip(4); // file, SavedVariables, account, Account
+    if comps.next()?.as_os_str() != "WTF" {
+        return None;
+    }
+    comps
+        .next()?
+        .as_os_str()
+        .to_str()
+        .and_then(GameFlavor::from_folder)
+}
@@ -0,0 +1,358 @@
+use crate::game_monitor::GameFlavor;
+use crate::gwp_command::WowInstallCandidate;
+use crate::log_command;
+use crate::watcher;
+use notify::{Config, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+// How often a supervisor checks its watcher and folder.
+const HEALTH_TICK: Duration = Duration::from_secs(2);
+// Retry delay doubles per consecutive failure, up to the cap.
+const RETRY_BASE: Duration = Duration::from_secs(1);
+const RETRY_MAX: Duration = Duration::from_secs(60);
+// Consecutive native failures before switching to polling for good.
+const NATIVE_ATTEMPTS: u32 = 3;
+// A watcher that stays up this long clears the failure count.
+const HEALTHY_AFTER: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// One supervisor per install, keyed by its `WTF/Account` folder. Dropping an entry stops it.
+#[derive(Default)]
+pub struct WatcherKeeper(Mutex<HashMap<PathBuf, Supervised>>);
+
+pub struct Supervised {
+    status: Arc<Mutex<WatcherStatus>>,
+    // Never sent on; the supervisor exits once this is dropped.
+    _stop: Sender<()>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../src/Interfaces/WatcherMode.ts")]
+pub enum WatcherMode {
+    Native,
+    Polling,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../src/Interfaces/WatcherHealth.ts")]
+pub enum WatcherHealth {
+    Watching,
+    Retrying,
+    /// The account folder does not exist (yet); attaches once it appears.
+    WaitingForFolder,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../../src/Interfaces/WatcherStatus.ts")]
+pub struct WatcherStatus {
+    pub account_path: String,
+    pub flavor: Option<GameFlavor>,
+    pub health: WatcherHealth,
+    pub mode: WatcherMode,
+    /// Consecutive failures since the watcher last stayed up.
+    pub failures: u32,
+    pub last_error: Option<String>,
+    #[ts(type = "number")]
+    pub changed_at_ms: u64,
+    #[ts(type = "number | null")]
+    pub next_retry_at_ms: Option<u64>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn retry_delay(failures: u32) -> Duration {
+    RETRY_BASE
+        .saturating_mul(1 << failures.saturating_sub(1).min(16))
+        .min(RETRY_MAX)
+}
+
+// Missing folder is `None`; a recreated one has a new creation time. Where the platform does
+// not report one, only a disappearance seen between two ticks counts.
+fn folder_identity(root: &Path) -> Option<Option<SystemTime>> {
+    let metadata = fs::metadata(root).ok().filter(|m| m.is_dir())?;
+    Some(metadata.created().ok())
+}
+
+// Only the account root itself; its SavedVariables folders are added by `sync_dirs`.
+fn attach(
+    mode: WatcherMode,
+    root: &Path,
+    handler: impl FnMut(Result<notify::Event, notify::Error>) + Send + 'static,
+) -> notify::Result<Box<dyn Watcher + Send>> {
+    let mut watcher: Box<dyn Watcher + Send> = match mode {
+        WatcherMode::Native => Box::new(RecommendedWatcher::new(handler, Config::default())?),
+        WatcherMode::Polling => Box::new(PollWatcher::new(
+            handler,
+            Config::default().with_poll_interval(POLL_INTERVAL),
+        )?),
+    };
+    watcher.watch(root, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+struct Supervisor {
+    app: AppHandle,
+    root: PathBuf,
+    status: Arc<Mutex<WatcherStatus>>,
+    events: Sender<PathBuf>,
+    layout: Sender<()>,
+    errors: Sender<String>,
+    watcher: Option<Box<dyn Watcher + Send>>,
+    watched: HashSet<PathBuf>,
+    attached: Option<Option<SystemTime>>,
+    attached_at: Instant,
+    next_attempt: Instant,
+    has_attached: bool,
+}
+
+impl Supervisor {
+    fn update(&self, apply: impl FnOnce(&mut WatcherStatus)) {
+        if let Ok(mut status) = self.status.lock() {
+            let health = status.health;
+            apply(&mut status);
+            if status.health != health {
+                status.changed_at_ms = now_ms();
+            }
+        }
+    }
+
+    fn mode(&self) -> WatcherMode {
+        self.status
+            .lock()
+            .map(|status| status.mode)
+            .unwrap_or(WatcherMode::Polling)
+    }
+
+    fn fail(&mut self, error: String) {
+        let mut failures = 0;
+        let mut fallback = false;
+        self.update(|status| {
+            status.failures += 1;
+            status.last_error = Some(error);
+            if status.mode == WatcherMode::Native && status.failures >= NATIVE_ATTEMPTS {
+                status.mode = WatcherMode::Polling;
+                status.failures = 0;
+                fallback = true;
+            }
+            failures = status.failures;
+        });
+
+        let delay = if fallback {
+            log_command::emit_log(&self.app, "Addon watcher switched to polling");
+            Duration::ZERO
+        } else {
+            log_command::emit_log(&self.app, "Addon watcher failed, retrying");
+            retry_delay(failures)
+        };
+        self.next_attempt = Instant::now() + delay;
+        self.update(|status| {
+            status.health = WatcherHealth::Retrying;
+            status.next_retry_at_ms = Some(now_ms() + delay.as_millis() as u64);
+        });
+    }
+
+    fn try_attach(&mut self, identity: Option<Option<SystemTime>>) {
+        if identity.is_none() {
+            self.next_attempt = Instant::now() + HEALTH_TICK;
+            self.update(|status| {
+                status.health = WatcherHealth::WaitingForFolder;
+                status.next_retry_at_ms = None;
+            });
+            return;
+        }
+
+        let mode = self.mode();
+        let handler = watcher::event_handler(
+            self.app.clone(),
+            self.events.clone(),
+            self.layout.clone(),
+            self.errors.clone(),
+        );
+        match attach(mode, &self.root, handler) {
+            Ok(watcher) => {
+                self.watcher = Some(watcher);
+                self.watched.clear();
+                // Writes made while detached produced no events.
+                if let Err(e) = self.sync_dirs(self.has_attached) {
+                    self.watcher = None;
+                    self.fail(e.to_string());
+                    return;
+                }
+                self.attached = identity;
+                self.attached_at = Instant::now();
+                self.update(|status| {
+                    status.health = WatcherHealth::Watching;
+                    status.next_retry_at_ms = None;
+                });
+                log_command::emit_log(
+                    &self.app,
+                    match mode {
+                        WatcherMode::Native => "Addon watcher registered",
+                        WatcherMode::Polling => "Addon watcher registered (polling)",
+                    },
+                );
+                self.has_attached = true;
+            }
+            Err(e) => self.fail(e.to_string()),
+        }
+    }
+
+    /// Watches SavedVariables folders that appeared since the last call and forgets removed
+    /// ones. With `announce`, files already in a new folder are emitted as they raised no event.
+    fn sync_dirs(&mut self, announce: bool) -> notify::Result<()> {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return Ok(());
+        };
+        let dirs: HashSet<PathBuf> = watcher::saved_variables_dirs(&self.root)
+            .into_iter()
+            .collect();
+
+        for gone in self.watched.difference(&dirs) {
+            // The OS drops watches on deleted folders itself, so a failure here is expected.
+            let _ = watcher.unwatch(gone);
+        }
+        self.watched.retain(|dir| dirs.contains(dir));
+
+        for dir in dirs {
+            if self.watched.contains(&dir) {
+                continue;
+            }
+            watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+            if announce {
+                watcher::emit_saved_vars_in(&self.app, &dir);
+            }
+            self.watched.insert(dir);
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self, errors: &Receiver<String>) {
+        let mut error = None;
+        while let Ok(message) = errors.try_recv() {
+            error = Some(message);
+        }
+        let identity = folder_identity(&self.root);
+
+        if self.watcher.is_some() {
+            if let Some(message) = error {
+                self.watcher = None;
+                self.fail(message);
+            } else if identity != self.attached {
+                self.watcher = None;
+                self.next_attempt = Instant::now();
+                log_command::emit_log(&self.app, "Addon watcher detached");
+            } else if let Err(e) = self.sync_dirs(true) {
+                self.watcher = None;
+                self.fail(e.to_string());
+            } else if self.attached_at.elapsed() >= HEALTHY_AFTER {
+                self.update(|status| status.failures = 0);
+            }
+        }
+
+        if self.watcher.is_none() && Instant::now() >= self.next_attempt {
+            self.try_attach(identity);
+        }
+    }
+}
+
+fn supervise(
+    app: AppHandle,
+    install: WowInstallCandidate,
+    status: Arc<Mutex<WatcherStatus>>,
+    stop: Receiver<()>,
+) {
+    let (errors_tx, errors) = mpsc::channel::<String>();
+    let (layout_tx, layout) = mpsc::channel::<()>();
+    let mut supervisor = Supervisor {
+        events: watcher::spawn_settle_loop(app.clone(), install.flavor),
+        app,
+        root: PathBuf::from(&install.account_path),
+        status,
+        layout: layout_tx,
+        errors: errors_tx,
+        watcher: None,
+        watched: HashSet::new(),
+        attached: None,
+        attached_at: Instant::now(),
+        next_attempt: Instant::now(),
+        has_attached: false,
+    };
+
+    // A new account folder wakes the loop early so its SavedVariables are picked up at once.
+    loop {
+        supervisor.tick(&errors);
+        let _ = layout.recv_timeout(HEALTH_TICK);
+        while layout.try_recv().is_ok() {}
+        if let Err(TryRecvError::Disconnected) = stop.try_recv() {
+            break;
+        }
+    }
+}
+
+/// Stops the running supervisors and starts one per install; used at startup and whenever the
+/// WoW path changes.
+pub fn watch_installs(app: &AppHandle, installs: &[WowInstallCandidate]) {
+    let keeper = app.state::<WatcherKeeper>();
+    let Ok(mut supervisors) = keeper.0.lock() else {
+        return;
+    };
+    supervisors.clear();
+
+    for install in installs {
+        let status = Arc::new(Mutex::new(WatcherStatus {
+            account_path: install.account_path.clone(),
+            flavor: install.flavor,
+            health: WatcherHealth::Retrying,
+            mode: WatcherMode::Native,
+            failures: 0,
+            last_error: None,
+            changed_at_ms: now_ms(),
+            next_retry_at_ms: None,
+        }));
+        let (stop, stop_rx) = mpsc::channel::<()>();
+        let thread_app = app.clone();
+        let thread_install = install.clone();
+        let thread_status = Arc::clone(&status);
+        thread::spawn(move || supervise(thread_app, thread_install, thread_status, stop_rx));
+
+        supervisors.insert(
+            PathBuf::from(&install.account_path),
+            Supervised {
+                status,
+                _stop: stop,
+            },
+        );
+    }
+}
+
+/// Health of every install's watcher.
+#[tauri::command]
+pub fn get_watcher_status(app: AppHandle) -> Vec<WatcherStatus> {
+    let keeper = app.state::<WatcherKeeper>();
+    let Ok(supervisors) = keeper.0.lock() else {
+        return Vec::new();
+    };
+    let mut statuses: Vec<WatcherStatus> = supervisors
+        .values()
+        .filter_map(|supervised| supervised.status.lock().ok().map(|status| status.clone()))
+        .collect();
+    statuses.sort_by(|a, b| a.account_path.cmp(&b.account_path));
+    statuses
+}
@@ -10,10 +10,8 @@ import {
     resolveTelemetryVersion,
     type KickTelemetrySnapshot,
 } from "./kickTelemetry";
-import {
-    buildSpellOutcomeCounts,
-    resolveLocalSpellModel,
-} from "../../Domain/localSpellModel";
+import { buildSpellOutcomeCounts } from "../../Domain/localSpellModel";
+import { readStoredSpellModel } from "../../Domain/computedMatch";
 import {
     BRACKET_SOLO_SHUFFLE,
     isRatedBracket,
@@ -220,7 +218,7 @@ export default function MatchDetailsPanel({ match, isLoading, onBack }: MatchDet
     const content = useMemo<MatchDetailsContent | null>(() => {
         if (!match) return null;
         const players = (match.raw.players ?? []) as MatchPlayer[];
-        const localSpellModel = resolveLocalSpellModel(match.raw);
+        const localSpellModel = readStoredSpellModel(match.raw);
         const isSoloShuffle = match.bracketId === BRACKET_SOLO_SHUFFLE;
         const showRating = isRatedBracket(match.bracketId);
         // Blizzard faction index in PvP scoreboards: 0 = Horde, 1 = Alliance.
@@ -1,10 +1,17 @@
 import { createContext, ReactNode, useEffect, useRef, useState } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
-import luaJson from "lua-json";
 import { Match, MatchV2, MatchV4, MatchWithId } from "../Interfaces/matches";
-import { buildMatchComputed, extractMatchKey, toStoredComputedMatch } from "../Domain/computedMatch";
-import { extractLuaRootTable } from "../Domain/luaSavedVariables";
+import type { SavedVarsDelta } from "../Interfaces/SavedVarsDelta";
+import type { GcTransitionReport } from "../Interfaces/GcTransitionReport";
+import type { MatchIdentity } from "../Interfaces/MatchIdentity";
+import type { MatchValidationError } from "../Interfaces/MatchValidationError";
+import {
+    buildMatchComputed,
+    extractMatchKey,
+    readStoredSpellModel,
+    toStoredComputedMatch,
+} from "../Domain/computedMatch";
 import { resolveMatchDurationSeconds } from "../Domain/localSpellModel";
 
 export type MatchesStatus = {
@@ -24,7 +31,6 @@ const isPlainObject = (value: unknown): value is Record<string, unknown> =>
     typeof value === "object" && value !== null && !Array.isArray(value);
 
 const MATCH_UPDATE_DEBOUNCE_MS = 750;
-const MATCH_RETRY_DELAYS_MS = [250, 500, 1000, 1500] as const;
 
 const debugMatches = (message: string, details?: unknown) => {
     if (!import.meta.env.DEV) return;
@@ -136,7 +142,7 @@ const buildMatchWithId = async (
               } as unknown)
             : parsedMatch;
 
-    const id = await invoke<string>("identify_match", {
+    const { id, legacyId } = await invoke<MatchIdentity>("identify_match", {
         obj: normalizedParsedMatch,
     });
 
@@ -159,6 +165,7 @@ const buildMatchWithId = async (
 
     return {
         id,
+        legacyId,
         ...(isTelemetryV2Plus
             ? ((telemetryVersion >= 4
                   ? (normalizedParsedMatch as MatchV4)
@@ -167,140 +174,6 @@ const buildMatchWithId = async (
         interruptSpellIds,
     };
 };
-const splitTopLevelLuaEntries = (table: string) => {
-    const trimmed = table.trim();
-    if (!trimmed.startsWith("{") || !trimmed.endsWith("}")) return [];
-    const body = trimmed.slice(1, -1);
-    const entries: string[] = [];
-
-    let start = 0;
-    let depthBrace = 0;
-    let depthBracket = 0;
-    let depthParen = 0;
-    let inString = false;
-    let stringChar = "";
-    let escaped = false;
-    let inLineComment = false;
-
-    for (let i = 0; i < body.length; i += 1) {
-        const ch = body[i];
-        const next = body[i + 1];
-
-        if (inLineComment) {
-            if (ch === "\n") inLineComment = false;
-            continue;
-        }
-
-        if (inString) {
-            if (escaped) {
-                escaped = false;
-                continue;
-            }
-            if (ch === "\\") {
-                escaped = true;
-                continue;
-            }
-            if (ch === stringChar) {
-                inString = false;
-                stringChar = "";
-            }
-            continue;
-        }
-
-        if (ch === "-" && next === "-" && depthBrace === 0 && depthBracket === 0 && depthParen === 0) {
-            inLineComment = true;
-            i += 1;
-            continue;
-        }
-
-        if (ch === "\"" || ch === "'") {
-            inString = true;
-            stringChar = ch;
-            continue;
-        }
-
-        if (ch === "{") depthBrace += 1;
-        else if (ch === "}") depthBrace = Math.max(0, depthBrace - 1);
-        else if (ch === "[") depthBracket += 1;
-        else if (ch === "]") depthBracket = Math.max(0, depthBracket - 1);
-        else if (ch === "(") depthParen += 1;
-        else if (ch === ")") depthParen = Math.max(0, depthParen - 1);
-        else if (ch === "," && depthBrace === 0 && depthBracket === 0 && depthParen === 0) {
-            const entry = body.slice(start, i).trim();
-            if (entry) entries.push(entry);
-            start = i + 1;
-        }
-    }
-
-    const tail = body.slice(start).trim();
-    if (tail) entries.push(tail);
-    return entries;
-};
-
-const findTopLevelAssign = (entry: string) => {
-    let depthBrace = 0;
-    let depthBracket = 0;
-    let depthParen = 0;
-    let inString = false;
-    let stringChar = "";
-    let escaped = false;
-
-    for (let i = 0; i < entry.length; i += 1) {
-        const ch = entry[i];
-
-        if (inString) {
-            if (escaped) {
-                escaped = false;
-                continue;
-            }
-            if (ch === "\\") {
-                escaped = true;
-                continue;
-            }
-            if (ch === stringChar) {
-                inString = false;
-                stringChar = "";
-            }
-            continue;
-        }
-
-        if (ch === "\"" || ch === "'") {
-            inString = true;
-            stringChar = ch;
-            continue;
-        }
-
-        if (ch === "{") depthBrace += 1;
-        else if (ch === "}") depthBrace = Math.max(0, depthBrace - 1);
-        else if (ch === "[") depthBracket += 1;
-        else if (ch === "]") depthBracket = Math.max(0, depthBracket - 1);
-        else if (ch === "(") depthParen += 1;
-        else if (ch === ")") depthParen = Math.max(0, depthParen - 1);
-        else if (ch === "=" && depthBrace === 0 && depthBracket === 0 && depthParen === 0) {
-            return i;
-        }
-    }
-
-    return -1;
-};
-
-const parseMatchesFallback = (table: string) => {
-    const entries = splitTopLevelLuaEntries(table);
-    const recovered: unknown[] = [];
-
-    entries.forEach((entry) => {
-        const assignIdx = findTopLevelAssign(entry);
-        const value = (assignIdx >= 0 ? entry.slice(assignIdx + 1) : entry).trim();
-        if (!value || value === "nil") return;
-        try {
-            recovered.push(luaJson.parse("return " + value));
-        } catch {
-            // ignore malformed single entries
-        }
-    });
-
-    return recovered;
-};
 
 const validateMatchV1 = (value: unknown) => {
     if (!isPlainObject(value)) {
@@ -346,131 +219,44 @@ const validateMatchV2Plus = (value: unknown) => {
     }
 };
 
-type MatchReadSuccess = {
-    status: "ok";
-    fileContent: string;
+type MatchSnapshot = {
     parsedMatches: unknown[];
     interruptSpellIds: number[];
     gcPendingKeys: Set<string>;
-    recoveredFallbackCount: number;
-};
-
-type MatchReadFailure = {
-    status: "retryable" | "terminal";
-    reason:
-        | "empty_content"
-        | "db_incomplete"
-        | "db_missing"
-        | "db_parse_error"
-        | "db_extract_error";
-};
-
-const waitFor = (ms: number) =>
-    new Promise<void>((resolve) => {
-        setTimeout(resolve, ms);
-    });
-
-const parseNumericIdTable = (content: string, key: string) => {
-    const tableResult = extractLuaRootTable(content, key);
-    if (tableResult.status !== "ok") return [];
-
-    try {
-        return coerceNumericIdArray(luaJson.parse("return " + tableResult.table));
-    } catch {
-        return [];
-    }
 };
 
-const parsePendingGcTable = (content: string) => {
-    const tableResult = extractLuaRootTable(content, "PvP_Scalpel_GC");
-    if (tableResult.status !== "ok") return new Set<string>();
-
-    try {
-        return toPendingMatchKeys(luaJson.parse("return " + tableResult.table));
-    } catch {
-        return new Set<string>();
-    }
+// File state rebuilt from `savedvars-delta` events, keyed by match key.
+type SavedVarsState = {
+    matches: Map<string, unknown>;
+    order: string[];
+    roots: Record<string, unknown>;
 };
 
-const readSavedVariablesSnapshot = async (path: string): Promise<MatchReadSuccess | MatchReadFailure> => {
-    const fileContent = await invoke<string>("read_saved_variables", {
-        path,
-    });
-
-    debugMatches("read_saved_variables completed", {
-        path,
-        contentLength: typeof fileContent === "string" ? fileContent.length : 0,
-    });
-
-    if (!fileContent) {
-        return {
-            status: "terminal",
-            reason: "empty_content",
-        };
-    }
-
-    const dbTableResult = extractLuaRootTable(fileContent, "PvP_Scalpel_DB");
-    if (dbTableResult.status !== "ok") {
-        debugMatches("root table extraction failed", {
-            path,
-            status: dbTableResult.status,
-        });
-        if (dbTableResult.status === "missing") {
-            return {
-                status: "retryable",
-                reason: "db_missing",
-            };
-        }
-
-        if (dbTableResult.status === "incomplete") {
-            return {
-                status: "retryable",
-                reason: "db_incomplete",
-            };
-        }
-
-        return {
-            status: "terminal",
-            reason: "db_extract_error",
-        };
-    }
-
-    let parsedArray: unknown;
-    let recoveredFallbackCount = 0;
-    try {
-        parsedArray = luaJson.parse("return " + dbTableResult.table);
-    } catch (parseErr) {
-        const recoveredMatches = parseMatchesFallback(dbTableResult.table);
-        if (recoveredMatches.length > 0) {
-            parsedArray = recoveredMatches;
-            recoveredFallbackCount = recoveredMatches.length;
-            if (import.meta.env.DEV) {
-                console.warn("[matches] full table parse failed; fallback recovered entries", {
-                    recovered: recoveredMatches.length,
-                    parseErr,
-                });
-            }
-        } else {
-            if (import.meta.env.DEV) {
-                console.error("[matches] lua-json parse failed", parseErr);
-            }
-            return {
-                status: "retryable",
-                reason: "db_parse_error",
-            };
-        }
-    }
+const applySavedVarsDelta = (
+    previous: SavedVarsState | undefined,
+    delta: SavedVarsDelta
+): SavedVarsState => {
+    const base = delta.full ? undefined : previous;
+    const matches = new Map<string, unknown>(base?.matches ?? []);
+    delta.removed.forEach((key) => matches.delete(key));
+    Object.entries(delta.added).forEach(([key, entry]) => matches.set(key, entry));
+    Object.entries(delta.changed).forEach(([key, entry]) => matches.set(key, entry));
 
     return {
-        status: "ok",
-        fileContent,
-        parsedMatches: coerceMatchesArray(parsedArray),
-        interruptSpellIds: parseNumericIdTable(fileContent, "PvP_Scalpel_InteruptSpells"),
-        gcPendingKeys: parsePendingGcTable(fileContent),
-        recoveredFallbackCount,
+        matches,
+        order: delta.order,
+        roots: { ...(base?.roots ?? {}), ...delta.roots },
     };
 };
 
+const toMatchSnapshot = (state: SavedVarsState): MatchSnapshot => ({
+    parsedMatches: state.order
+        .map((key) => state.matches.get(key))
+        .filter((entry) => entry !== undefined),
+    interruptSpellIds: coerceNumericIdArray(state.roots.PvP_Scalpel_InteruptSpells),
+    gcPendingKeys: toPendingMatchKeys(state.roots.PvP_Scalpel_GC),
+});
+
 export const MatchesContext = createContext<MatchWithId[] | null>(null);
 export const MatchesStatusContext = createContext<MatchesStatus | null>(null);
 
@@ -491,6 +277,7 @@ export const MatchesProvider = ({ children }: { children: ReactNode }) => {
     useEffect(() => {
         let timeout: ReturnType<typeof setTimeout> | null = null;
         let activeRunId = 0;
+        const savedVarsStates = new Map<string, SavedVarsState>();
         const markHydrationSettled = () => {
             hasHydratedFromStore.current = true;
             setStatus((prev) => ({
@@ -577,7 +364,7 @@ export const MatchesProvider = ({ children }: { children: ReactNode }) => {
         };
 
         const runSavedVariablesUpdate = async (
-            payload: { account: string; path: string; gameState?: string },
+            payload: SavedVarsDelta,
             runId: number
         ) => {
             try {
@@ -591,91 +378,19 @@ export const MatchesProvider = ({ children }: { children: ReactNode }) => {
                     path: payload.path,
                 });
 
-                let snapshot: MatchReadSuccess | null = null;
-
-                for (let attempt = 0; attempt <= MATCH_RETRY_DELAYS_MS.length; attempt += 1) {
-                    if (logStaleRun(runId, `before-read-attempt-${attempt}`)) return;
-
-                    const readResult = await readSavedVariablesSnapshot(payload.path);
-                    if (logStaleRun(runId, `after-read-attempt-${attempt}`)) return;
-
-                    debugMatches("snapshot attempt result", {
-                        runId,
-                        attempt,
-                        status: readResult.status,
-                        reason: readResult.status === "ok" ? null : readResult.reason,
-                    });
-
-                    if (readResult.status === "ok") {
-                        snapshot = readResult;
-                        debugMatches("snapshot ready", {
-                            runId,
-                            parsedMatches: readResult.parsedMatches.length,
-                            interruptSpellIds: readResult.interruptSpellIds.length,
-                            gcPendingKeys: readResult.gcPendingKeys.size,
-                            recoveredFallbackCount: readResult.recoveredFallbackCount,
-                        });
-                        break;
-                    }
-
-                    const isLastAttempt = attempt === MATCH_RETRY_DELAYS_MS.length;
-                    if (!isLastAttempt && readResult.status === "retryable") {
-                        if (
-                            readResult.reason === "db_incomplete" ||
-                            readResult.reason === "db_parse_error"
-                        ) {
-                            debugMatches("retrying transient snapshot failure", {
-                                runId,
-                                attempt,
-                                reason: readResult.reason,
-                                nextDelayMs: MATCH_RETRY_DELAYS_MS[attempt],
-                            });
-                            invoke("push_log", {
-                                message: "SavedVariables write in progress, retrying",
-                            }).catch(() => undefined);
-                        }
-                        await waitFor(MATCH_RETRY_DELAYS_MS[attempt]);
-                        continue;
-                    }
-
-                    if (readResult.reason === "db_incomplete") {
-                        pushTerminalParseMessage("PvP_Scalpel_DB incomplete after retries");
-                        return;
-                    }
-
-                    if (readResult.reason === "db_missing") {
-                        pushTerminalParseMessage("PvP_Scalpel_DB missing after retries");
-                        return;
-                    }
-
-                    if (readResult.reason === "db_parse_error") {
-                        pushTerminalParseMessage("Match parse failed after retries");
-                        return;
-                    }
-
-                    if (readResult.reason === "db_extract_error") {
-                        pushTerminalParseMessage("PvP_Scalpel_DB malformed after retries");
-                        return;
-                    }
-
-                    pushTerminalParseMessage("SavedVariables read returned empty content");
-                    return;
-                }
-
-                if (!snapshot) {
+                const state = savedVarsStates.get(payload.path);
+                if (!state) {
                     debugMatches("run finished without snapshot", { runId });
                     return;
                 }
-                if (logStaleRun(runId, "after-snapshot")) return;
-
-                const { parsedMatches, interruptSpellIds, gcPendingKeys, recoveredFallbackCount } =
-                    snapshot;
 
-                if (recoveredFallbackCount > 0) {
-                    invoke("push_log", {
-                        message: `Match parse fallback recovered ${recoveredFallbackCount} entries`,
-                    }).catch(() => undefined);
-                }
+                const { parsedMatches, interruptSpellIds, gcPendingKeys } = toMatchSnapshot(state);
+                debugMatches("snapshot ready", {
+                    runId,
+                    parsedMatches: parsedMatches.length,
+                    interruptSpellIds: interruptSpellIds.length,
+                    gcPendingKeys: gcPendingKeys.size,
+                });
 
                 invoke("push_log", {
                     message: `Parsed ${parsedMatches.length} raw match entries`,
@@ -684,6 +399,7 @@ export const MatchesProvider = ({ children }: { children: ReactNode }) => {
                 const shouldFinalizeComputed =
                     payload.gameState === "not_running" || payload.gameState === "just_closed";
                 const accountKey = payload.account?.trim() || "unknown";
+                const flavor = payload.flavor ?? null;
 
                 if (import.meta.env.DEV) {
                     console.log("[matches] computed finalize gate", {
@@ -701,7 +417,8 @@ export const MatchesProvider = ({ children }: { children: ReactNode }) => {
                             return !!matchKey && gcPendingKeys.has(matchKey);
                         })
                         .map((entry) => {
-                            const computed = buildMatchComputed(entry, interruptSpellIds);
+                            // upsert_computed_matches resolves the spell model from the raw capture.
+                            const computed = buildMatchComputed(entry, interruptSpellIds, null);
                             if (!computed) return null;
                             return toStoredComputedMatch(entry, computed);
                         })
@@ -720,43 +437,55 @@ export const MatchesProvider = ({ children }: { children: ReactNode }) => {
                     });
 
                     if (computedEntries.length > 0 && !isStaleRun(runId)) {
-                        const persisted = await invoke("upsert_computed_matches", {
+                        const rejected = await invoke<MatchValidationError[]>("upsert_computed_matches", {
                             account: accountKey,
+                            flavor,
                             matches: computedEntries,
-                        })
-                            .then(() => true)
-                            .catch(() => false);
+                        }).catch(() => null);
+                        const persisted = rejected !== null;
 
                         debugMatches("computed finalize persisted result", {
                             runId,
                             persisted,
                             count: computedEntries.length,
+                            rejected,
                         });
                         if (logStaleRun(runId, "after-upsert-computed-matches")) return;
 
                         if (persisted) {
+                            const rejectedKeys = new Set(rejected.map((error) => error.matchKey));
                             const syncedKeys = computedEntries
                                 .map((entry) =>
                                     typeof entry.matchKey === "string" ? entry.matchKey.trim() : ""
                                 )
-                                .filter((value): value is string => !!value);
+                                .filter((value): value is string => !!value && !rejectedKeys.has(value));
 
                             if (syncedKeys.length > 0) {
-                                const syncedCount = await invoke<number>("mark_gc_matches_synced", {
+                                const report = await invoke<GcTransitionReport>("mark_gc_matches_synced", {
                                     path: payload.path,
                                     keys: syncedKeys,
-                                }).catch(() => 0);
+                                }).catch(() => null);
 
                                 debugMatches("gc sync completed", {
                                     runId,
-                                    syncedCount,
+                                    report,
                                     keys: syncedKeys,
                                 });
                                 if (logStaleRun(runId, "after-mark-gc-matches-synced")) return;
 
-                                invoke("push_log", {
-                                    message: `GC state updated to synced (${syncedCount})`,
-                                }).catch(() => undefined);
+                                if (report) {
+                                    const syncedCount = report.changed + report.unchanged;
+                                    invoke("push_log", {
+                                        message: report.queued
+                                            ? `GC state synced once WoW closes (${syncedCount})`
+                                            : `GC state updated to synced (${syncedCount})`,
+                                    }).catch(() => undefined);
+                                    if (report.skipped.length > 0) {
+                                        invoke("push_log", {
+                                            message: `GC sync skipped (${report.skipped.length})`,
+                                        }).catch(() => undefined);
+                                    }
+                                }
                             }
 
                             invoke("push_log", {
@@ -768,6 +497,7 @@ export const MatchesProvider = ({ children }: { children: ReactNode }) => {
 
                 const persistedComputedMatches = await invoke<unknown[]>("load_computed_matches", {
                     account: accountKey,
+                    flavor,
                 }).catch(() => []);
 
                 debugMatches("loaded persisted computed matches", {
@@ -818,7 +548,13 @@ export const MatchesProvider = ({ children }: { children: ReactNode }) => {
 
                     const telemetryVersion = readTelemetryVersion(rawSource);
                     if (rawSource && Number.isFinite(telemetryVersion) && telemetryVersion >= 3) {
-                        const recomputed = buildMatchComputed(rawSource, interruptSpellIds);
+                        // Entries stored without a model get one from upsert_computed_matches,
+                        // which resolves it from the raw capture kept on the entry.
+                        const recomputed = buildMatchComputed(
+                            rawSource,
+                            interruptSpellIds,
+                            readStoredSpellModel(entry)
+                        );
                         if (recomputed) {
                             const currentComputed = isPlainObject((patched ?? entry).computed)
                                 ? (patched ?? entry).computed
@@ -841,6 +577,7 @@ export const MatchesProvider = ({ children }: { children: ReactNode }) => {
                 if (computedBackfill.length > 0 && !isStaleRun(runId)) {
                     await invoke("upsert_computed_matches", {
                         account: accountKey,
+                        flavor,
                         matches: computedBackfill,
                     }).catch(() => undefined);
 
@@ -951,11 +688,18 @@ export const MatchesProvider = ({ children }: { children: ReactNode }) => {
             }
         };
 
-        const unlistenPromise = listen<{ account: string; path: string; gameState?: string }>(
-            "savedvars-updated",
+        const unlistenPromise = listen<SavedVarsDelta>(
+            "savedvars-delta",
             async ({ payload }) => {
                 if (timeout) clearTimeout(timeout);
 
+                const previous = savedVarsStates.get(payload.path);
+                savedVarsStates.set(payload.path, applySavedVarsDelta(previous, payload));
+                if (!payload.full && !previous) {
+                    // Missed the initial snapshot; a rescan resends every match.
+                    invoke("scan_saved_vars").catch(() => undefined);
+                }
+
                 activeRunId += 1;
                 const runId = activeRunId;
                 debugMatches("savedvars event queued", {
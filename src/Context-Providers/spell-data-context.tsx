@@ -1,9 +1,8 @@
 import { createContext, ReactNode, useEffect, useRef, useState } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
-import luaJson from "lua-json";
-import { extractLuaRootTable } from "../Domain/luaSavedVariables";
 import { SpellDataBucket } from "../Interfaces/spell-data";
+import type { SavedVarsDelta } from "../Interfaces/SavedVarsDelta";
 
 export const SpellDataContext = createContext<SpellDataBucket | null>(null);
 
@@ -107,52 +106,27 @@ export const SpellDataProvider = ({ children }: { children: ReactNode }) => {
     const hasParseError = useRef(false);
 
     useEffect(() => {
-        let timeout: ReturnType<typeof setTimeout> | null = null;
-
-        const unlistenPromise = listen<{ account: string; path: string }>(
-            "savedvars-updated",
-            async ({ payload }) => {
-                if (timeout) clearTimeout(timeout);
-
-                timeout = setTimeout(async () => {
-                    try {
-                        const fileContent = await invoke<string>("read_saved_variables", {
-                            path: payload.path,
-                        });
-
-                        if (!fileContent) return;
-
-                        const luaTableResult = extractLuaRootTable(fileContent, "PvP_Scalpel_Spell_Data");
-                        if (luaTableResult.status !== "ok") return;
-
-                        let parsed: unknown;
-                        try {
-                            parsed = luaJson.parse("return " + luaTableResult.table);
-                        } catch {
-                            return;
-                        }
-
-                        if (!parsed || typeof parsed !== "object" || Array.isArray(parsed)) {
-                            return;
-                        }
-
-                        validateSpellDataSchema(parsed);
-                        setSpellData(parsed as SpellDataBucket);
-                        hasParseError.current = false;
-                    } catch (err) {
-                        if (import.meta.env.DEV) {
-                            console.error("Spell data read error:", err);
-                        }
-                        if (!hasParseError.current) {
-                            hasParseError.current = true;
-                            invoke("push_log", {
-                                message: "Spell data update failed",
-                            }).catch(() => undefined);
-                        }
-                    }
-                }, 350);
+        // Deltas only carry roots that changed, so each one is applied as it arrives.
+        const unlistenPromise = listen<SavedVarsDelta>("savedvars-delta", ({ payload }) => {
+            try {
+                const parsed = payload.roots.PvP_Scalpel_Spell_Data;
+                if (!isPlainObject(parsed)) return;
+
+                validateSpellDataSchema(parsed);
+                setSpellData(parsed as SpellDataBucket);
+                hasParseError.current = false;
+            } catch (err) {
+                if (import.meta.env.DEV) {
+                    console.error("Spell data read error:", err);
+                }
+                if (!hasParseError.current) {
+                    hasParseError.current = true;
+                    invoke("push_log", {
+                        message: "Spell data update failed",
+                    }).catch(() => undefined);
+                }
             }
-        );
+        });
 
         unlistenPromise
             .then(() => invoke("scan_saved_vars"))
@@ -162,7 +136,6 @@ export const SpellDataProvider = ({ children }: { children: ReactNode }) => {
 
         return () => {
             unlistenPromise.then((unlisten) => unlisten());
-            if (timeout) clearTimeout(timeout);
         };
     }, []);
 
@@ -3,12 +3,12 @@ import {
     computeKickTelemetrySnapshot,
     resolveTelemetryVersion,
 } from "../Components/DataActivity/kickTelemetry";
-import {
-    buildSpellOutcomeCounts,
-    resolveLocalSpellModel,
-    resolveMatchDurationSeconds,
-} from "./localSpellModel";
-import type { ComputedAnalyticsV2, ComputedOwnerKickSummary } from "../Interfaces/local-spell-model";
+import { buildSpellOutcomeCounts, resolveMatchDurationSeconds } from "./localSpellModel";
+import type {
+    ComputedAnalyticsV2,
+    ComputedOwnerKickSummary,
+    NormalizedLocalSpellModel,
+} from "../Interfaces/local-spell-model";
 
 export type MatchComputed = ComputedAnalyticsV2;
 
@@ -31,11 +31,21 @@ export const extractMatchKey = (rawMatch: unknown) => {
     return trimmed ? trimmed : null;
 };
 
-export const buildMatchComputed = (rawMatch: unknown, kickSpellIds: number[]): MatchComputed | null => {
+// The model upsert_computed_matches normalized and stored; the webview never resolves its own.
+export const readStoredSpellModel = (rawMatch: unknown): NormalizedLocalSpellModel | null => {
+    if (!isRecord(rawMatch) || !isRecord(rawMatch.computed)) return null;
+    const model = rawMatch.computed.localSpellModel;
+    return isRecord(model) ? (model as unknown as NormalizedLocalSpellModel) : null;
+};
+
+export const buildMatchComputed = (
+    rawMatch: unknown,
+    kickSpellIds: number[],
+    localSpellModel: NormalizedLocalSpellModel | null
+): MatchComputed | null => {
     if (!isRecord(rawMatch)) return null;
 
     const players = Array.isArray(rawMatch.players) ? (rawMatch.players as MatchPlayer[]) : [];
-    const localSpellModel = resolveLocalSpellModel(rawMatch);
     const spellOutcomesBySpellId = buildSpellOutcomeCounts(localSpellModel);
     const owner = players.find((player) => player.isOwner) ?? players[0] ?? null;
     const telemetryVersion = resolveTelemetryVersion(rawMatch);
@@ -80,16 +90,9 @@ export const buildMatchComputed = (rawMatch: unknown, kickSpellIds: number[]): M
 export const toStoredComputedMatch = (rawMatch: unknown, computed: MatchComputed) => {
     const cloned = JSON.parse(JSON.stringify(rawMatch ?? {})) as Record<string, unknown>;
     const durationSeconds = resolveMatchDurationSeconds(rawMatch);
-    const canStripV4Payload =
-        isRecord(computed.localSpellModel) &&
-        computed.localSpellModel.detailAvailable === true;
     delete cloned.timeline;
     delete cloned.castRecords;
     delete cloned.castOutcomes;
-    if (canStripV4Payload) {
-        delete cloned.localSpellCapture;
-        delete cloned.localLossOfControl;
-    }
 
     if (isRecord(cloned.soloShuffle)) {
         const soloShuffle = cloned.soloShuffle as Record<string, unknown>;
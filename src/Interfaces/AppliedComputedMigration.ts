@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type AppliedComputedMigration = { name: string, fromVersion: number, toVersion: number, entries: number, backupPath: string | null, appliedAtMs: number, };
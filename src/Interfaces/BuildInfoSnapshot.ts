@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type BuildInfoSnapshot = { version: string, build: string | number, date?: string, interface: number, localized?: string, info: string, versionString?: string, };
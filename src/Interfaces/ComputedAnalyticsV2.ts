@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ComputedOwnerKickSummary } from "./ComputedOwnerKickSummary";
+import type { ComputedSpellOutcomeCounts } from "./ComputedSpellOutcomeCounts";
+import type { NormalizedLocalSpellModel } from "./NormalizedLocalSpellModel";
+
+/**
+ * The `computed` block stored on a match.
+ */
+export type ComputedAnalyticsV2 = { schemaVersion: 2, spellOutcomesBySpellId?: { [key in string]?: ComputedSpellOutcomeCounts }, ownerKicks?: ComputedOwnerKickSummary, localSpellModel?: NormalizedLocalSpellModel, };
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ComputedMatchResult } from "./ComputedMatchResult";
+import type { GameFlavor } from "./GameFlavor";
+
+export type ComputedMatchFilter = { 
+/**
+ * Store partition to page through; retail when omitted.
+ */
+flavor?: GameFlavor, account?: string, 
+/**
+ * Owner character key as built by `buildCharacterKey` (`char:<realm>:<name>`).
+ */
+character?: string, bracketIds?: Array<number>, rated?: boolean, 
+/**
+ * Inclusive bounds compared against `matchDetails.timestamp`; a date-only `to` covers the whole day.
+ */
+from?: string, to?: string, map?: string, result?: ComputedMatchResult, 
+/**
+ * Enemy spec line-ups; a match passes when its opponents equal any of them, in any order.
+ */
+opponentComps?: Array<Array<string>>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ComputedMatchPage = { matches: unknown[], 
+/**
+ * Pass back as `cursor` to fetch the next page; `null` once the history is exhausted.
+ */
+nextCursor: string | null, };
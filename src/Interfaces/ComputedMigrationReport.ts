@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AppliedComputedMigration } from "./AppliedComputedMigration";
+import type { ComputedMigrationStep } from "./ComputedMigrationStep";
+
+export type ComputedMigrationReport = { currentVersion: number, dryRun: boolean, 
+/**
+ * Steps that would run (dry run) or just ran, with the number of entries each touches.
+ */
+pending: Array<ComputedMigrationStep>, 
+/**
+ * Entries whose payload could not be parsed and were left untouched.
+ */
+skipped: number, 
+/**
+ * Entries written by a newer app version; kept as-is.
+ */
+newer: number, backupPath: string | null, applied: Array<AppliedComputedMigration>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Owner interrupt totals computed by the webview; older entries carry only some of them.
+ */
+export type ComputedOwnerKickSummary = { total?: number, intentAttempts?: number, landed?: number, confirmedInterrupts?: number, missed?: number, succeeded?: number, failed?: number, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DuplicateMatchRef } from "./DuplicateMatchRef";
+
+export type DuplicateMatchSuggestion = { 
+/**
+ * Most complete capture of the cluster; the one to collapse the others into.
+ */
+keep: DuplicateMatchRef, duplicates: Array<DuplicateMatchRef>, 
+/**
+ * 0..1, from the weakest pair that links the cluster together.
+ */
+confidence: number, 
+/**
+ * Smallest roster overlap (shared players / smaller roster) among the linking pairs.
+ */
+rosterOverlap: number, maxTimeDeltaSeconds: number, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameProcess } from "./GameProcess";
+
+export type GameState = { running: boolean, processes: Array<GameProcess>, 
+/**
+ * When `running` last changed (or the monitor first polled), in ms since the epoch;
+ * `null` for a one-off scan made before the monitor started.
+ */
+changedAtMs: number | null, };
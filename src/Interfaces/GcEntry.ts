@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GcState } from "./GcState";
+
+export type GcEntry = { key: string, 
+/**
+ * `None` when the stored value is not a known state; `raw` then holds what is there.
+ */
+state: GcState | null, raw: string, retries: number, updatedAt: number | null, 
+/**
+ * Unix seconds at which the key last entered each state.
+ */
+enteredAt: Partial<Record<GcState, number>>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GcState } from "./GcState";
+
+export type GcRejectedTransition = { key: string, 
+/**
+ * Current stored value, `null` when the key is not in the table.
+ */
+from: string | null, to: GcState, };
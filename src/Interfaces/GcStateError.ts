@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GcRejectedTransition } from "./GcRejectedTransition";
+
+export type GcStateError = { "type": "io", message: string, } | { "type": "validation", message: string, rejected: Array<GcRejectedTransition>, };
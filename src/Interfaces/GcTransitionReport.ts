@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GcRejectedTransition } from "./GcRejectedTransition";
+
+export type GcTransitionReport = { changed: number, 
+/**
+ * Keys that already were in the target state.
+ */
+unchanged: number, 
+/**
+ * True when the game is running and the write waits for it to close.
+ */
+queued: boolean, 
+/**
+ * Keys left as they were because the transition is illegal. Only
+ * `mark_gc_matches_synced` skips keys; the bulk command rejects the whole batch.
+ */
+skipped: Array<GcRejectedTransition>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LocalSpellCaptureGroup } from "./LocalSpellCaptureGroup";
+
+/**
+ * A keyed payload mixes per-spell groups with schema and metadata fields.
+ */
+export type LocalSpellCaptureField = LocalSpellCaptureGroup | unknown;
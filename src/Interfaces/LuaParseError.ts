@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LuaParseErrorKind } from "./LuaParseErrorKind";
+
+export type LuaParseError = { kind: LuaParseErrorKind, message: string, line: number, column: number, };
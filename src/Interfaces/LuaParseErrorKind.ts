@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type LuaParseErrorKind = "unexpected_eof" | "unexpected_token" | "unterminated_string" | "invalid_escape" | "invalid_number" | "invalid_key" | "too_deep";
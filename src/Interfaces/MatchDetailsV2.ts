@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BuildInfoSnapshot } from "./BuildInfoSnapshot";
+
+export type MatchDetailsV2 = { timestamp: string, format: string, mapName: string, bgGameType?: string, build?: BuildInfoSnapshot, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type MatchIdentity = { 
+/**
+ * Current fingerprint, `v2:` followed by 16 hex digits.
+ */
+id: string, 
+/**
+ * Unversioned v1 fingerprint that older stored matches and server records were keyed by.
+ */
+legacyId: string, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ComputedAnalyticsV2 } from "./ComputedAnalyticsV2";
+import type { GameFlavor } from "./GameFlavor";
+import type { LocalLossOfControlPayload } from "./LocalLossOfControlPayload";
+import type { LocalSpellCapturePayload } from "./LocalSpellCapturePayload";
+import type { MatchDetailsV2 } from "./MatchDetailsV2";
+import type { MatchWinner } from "./MatchWinner";
+import type { PlayerEntryV2 } from "./PlayerEntryV2";
+import type { SoloShuffleDataV4 } from "./SoloShuffleDataV4";
+import type { SpellTotalsEntry } from "./SpellTotalsEntry";
+
+export type MatchV4 = { matchKey: string, telemetryVersion: number, durationSeconds?: number, winner?: MatchWinner, matchDetails: MatchDetailsV2, players: Array<PlayerEntryV2>, spellTotalsBySource?: { [key in string]?: { [key in string]?: SpellTotalsEntry } }, interruptSpellsBySource?: { [key in string]?: { [key in string]?: number } }, crowdControlTakenBySource?: Record<string, unknown>, localSpellCapture?: LocalSpellCapturePayload, localLossOfControl?: LocalLossOfControlPayload, computed?: ComputedAnalyticsV2, soloShuffle?: SoloShuffleDataV4, 
+/**
+ * Client the match was played on; set by the desktop when it stores the match.
+ */
+flavor?: GameFlavor, };
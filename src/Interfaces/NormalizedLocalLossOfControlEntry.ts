@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NormalizedLocalLossOfControlEntry = { id: number, t: number, duration?: number | null, endTime?: number | null, roundIndex?: number | null, locType?: string | null, displayText?: string | null, issuedByGuid?: string | null, auraInstanceId?: number | null, 
+/**
+ * The raw school value, a string or a number.
+ */
+school?: string | number | null, raw?: unknown, };
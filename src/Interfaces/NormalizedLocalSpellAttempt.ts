@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LocalSpellAttemptGrouping } from "./LocalSpellAttemptGrouping";
+import type { NormalizedLocalSpellEvent } from "./NormalizedLocalSpellEvent";
+import type { SpellOutcomeResult } from "./SpellOutcomeResult";
+
+export type NormalizedLocalSpellAttempt = { id: string, spellId: number, castGUID?: string, startTime: number, endTime: number, windowMs: number, grouping: LocalSpellAttemptGrouping, events: Array<NormalizedLocalSpellEvent>, outcomes: Array<SpellOutcomeResult>, resolvedOutcome?: SpellOutcomeResult, roundIndex?: number | null, interruptible?: boolean | null, interruptedBy?: unknown, linkedLoc?: number[], fakeCastStopReason?: string | null, targetInfo?: unknown, provenance?: Array<string>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NormalizedLocalSpellEvent = { id: number, t: number, spellId: number, event: string, castGUID?: string, index: number, 
+/**
+ * Synthesized from a v4 cast row rather than observed.
+ */
+pseudo?: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LocalSpellSourceFormat } from "./LocalSpellSourceFormat";
+import type { NormalizedLocalLossOfControlEntry } from "./NormalizedLocalLossOfControlEntry";
+import type { NormalizedLocalSpellAttempt } from "./NormalizedLocalSpellAttempt";
+import type { NormalizedLocalSpellEvent } from "./NormalizedLocalSpellEvent";
+
+export type NormalizedLocalSpellModel = { schemaVersion: 2, sourceFormat: LocalSpellSourceFormat, detailAvailable: boolean, failureReason?: string | null, attempts: Array<NormalizedLocalSpellAttempt>, events: Array<NormalizedLocalSpellEvent>, locEntries: Array<NormalizedLocalLossOfControlEntry>, durationSecondsHint?: number | null, };
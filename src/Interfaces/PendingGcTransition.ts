@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GcState } from "./GcState";
+
+export type PendingGcTransition = { key: string, to: GcState, };
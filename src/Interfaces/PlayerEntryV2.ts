@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type PlayerEntryV2 = { name: string, realm: string, guid?: string, class?: string, spec?: string, faction?: number, rating?: number, ratingChange?: number, prematchMMR?: number, postmatchMMR?: number, damage?: number, healing?: number, interrupts?: [number, number], kills?: number, deaths?: number, MSS?: Array<[string, number]>, isOwner?: boolean, pvpTalents?: Array<number>, };
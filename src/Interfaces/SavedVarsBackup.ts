@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameFlavor } from "./GameFlavor";
+
+export type SavedVarsBackup = { account: string, flavor: GameFlavor, fileName: string, 
+/**
+ * xxh64 of the file content, hex.
+ */
+hash: string, 
+/**
+ * What took the snapshot: `watcher`, `pre-prune` or `pre-restore`.
+ */
+reason: string, createdAtMs: number, size: number, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameFlavor } from "./GameFlavor";
+
+export type SavedVarsDelta = { account: string, path: string, flavor: GameFlavor | null, gameState: string, 
+/**
+ * True when there was no previous snapshot and `added` holds every match.
+ */
+full: boolean, added: Record<string, unknown>, changed: Record<string, unknown>, removed: Array<string>, 
+/**
+ * Every match key in file order, so consumers can rebuild the list without the payloads.
+ */
+order: Array<string>, 
+/**
+ * Non-DB root tables (GC, spell data, interrupt ids) whose content changed.
+ */
+roots: Record<string, unknown>, };
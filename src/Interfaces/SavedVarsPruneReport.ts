@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SavedVarsPruneReport = { removedKeys: Array<string>, bytesBefore: number, bytesAfter: number, bytesReclaimed: number, 
+/**
+ * Copy of the file taken before rewriting it; `null` when nothing was pruned.
+ */
+backupPath: string | null, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameFlavor } from "./GameFlavor";
+
+export type SavedVarsUpdated = { account: string, path: string, 
+/**
+ * `null` when the install is not inside a `_<flavor>_` folder.
+ */
+flavor: GameFlavor | null, gameState: string, 
+/**
+ * xxh64 of the settled file content, hex; `null` when a rescan could not read the file.
+ */
+contentHash: string | null, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PvpStatColumn } from "./PvpStatColumn";
+import type { ScoreSnapshotPlayer } from "./ScoreSnapshotPlayer";
+
+export type ScoreSnapshot = { statColumns: Array<PvpStatColumn>, players: Array<ScoreSnapshotPlayer>, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PvpStatValue } from "./PvpStatValue";
+
+export type ScoreSnapshotPlayer = { name?: string, realm?: string, guid?: string, classToken?: string, talentSpec?: string, faction?: number, rating?: number, ratingChange?: number, prematchMMR?: number, postmatchMMR?: number, damageDone?: number, healingDone?: number, killingBlows?: number, deaths?: number, stats?: Array<PvpStatValue>, };
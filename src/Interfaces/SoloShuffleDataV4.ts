@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CaptureIntegrity } from "./CaptureIntegrity";
+import type { SoloShuffleRoundV4 } from "./SoloShuffleRoundV4";
+import type { SoloShuffleSummaryV4 } from "./SoloShuffleSummaryV4";
+
+export type SoloShuffleDataV4 = { matchKey: string, timestamp: string, format: string, mapName: string, duration: number, roundsExpected: number, roundsCaptured: number, rounds: Array<SoloShuffleRoundV4>, matchSummary: SoloShuffleSummaryV4, integrity: CaptureIntegrity, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScoreSnapshot } from "./ScoreSnapshot";
+import type { SoloShuffleRoundOutcome } from "./SoloShuffleRoundOutcome";
+
+export type SoloShuffleRoundV4 = { roundIndex: number, stateStartTime: number, stateEndTime?: number, duration?: number, scoreSnapshot?: ScoreSnapshot, outcome?: SoloShuffleRoundOutcome, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SpellTotalsEntry = { damage: number, healing: number, overheal?: number, absorbed?: number, hits?: number, crits?: number, targets?: { [key in string]?: number }, interrupts?: number, dispels?: number, };
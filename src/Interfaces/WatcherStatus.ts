@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameFlavor } from "./GameFlavor";
+import type { WatcherHealth } from "./WatcherHealth";
+import type { WatcherMode } from "./WatcherMode";
+
+export type WatcherStatus = { accountPath: string, flavor: GameFlavor | null, health: WatcherHealth, mode: WatcherMode, 
+/**
+ * Consecutive failures since the watcher last stayed up.
+ */
+failures: number, lastError: string | null, changedAtMs: number, nextRetryAtMs: number | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameFlavor } from "./GameFlavor";
+import type { WowInstallSource } from "./WowInstallSource";
+
+export type WowInstallCandidate = { 
+/**
+ * `<install>/<flavor>/WTF/Account`, the folder the watcher scans.
+ */
+accountPath: string, flavor: GameFlavor | null, source: WowInstallSource, 
+/**
+ * What pointed at it: registry key, Wine prefix, Lutris/Bottles config, compatdata folder or
+ * the folder the user chose.
+ */
+origin: string, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WowInstallCandidate } from "./WowInstallCandidate";
+
+export type WowPathSettings = { 
+/**
+ * Folder set with `set_wow_path`; `null` when the installs are detected.
+ */
+overridePath: string | null, 
+/**
+ * Installs being watched.
+ */
+installs: Array<WowInstallCandidate>, 
+/**
+ * What detection finds, whether or not an override is set.
+ */
+detected: Array<WowInstallCandidate>, };
@@ -9,5 +9,7 @@ import type { MatchV4 } from "./matches-v4";
 
 export type MatchWithId = (Match | MatchV2 | MatchV4) & {
     id: string;
+    /** Pre-v2 fingerprint, kept so older records stay linkable. */
+    legacyId?: string;
     interruptSpellIds?: number[];
 };